@@ -14,6 +14,10 @@ fn main() {
         "src/detour_crowd/path_corridor.rs",
         "src/detour_crowd/obstacle_avoidance.rs",
         "src/detour_crowd/crowd.rs",
+        "src/detour_tile_cache/builder.rs",
+        "src/detour_tile_cache/compressor.rs",
+        "src/detour_tile_cache/mesh_process.rs",
+        "src/detour_tile_cache/tile_cache.rs",
         "src/demo/demo.rs",
     ])
     .flag_if_supported("-std=c++14")
@@ -30,18 +34,25 @@ fn main() {
     .files(list_cpp_files("./recastnavigation/Detour/Source"))
     .include("./recastnavigation/DetourCrowd/Include")
     .files(list_cpp_files("./recastnavigation/DetourCrowd/Source"))
+    .include("./recastnavigation/DetourTileCache/Include")
+    .files(list_cpp_files("./recastnavigation/DetourTileCache/Source"))
     .include("./recastnavigation/RecastDemo/Include")
     .files([
         "./recastnavigation/RecastDemo/Source/MeshLoaderObj.cpp",
         "./recastnavigation/RecastDemo/Source/ChunkyTriMesh.cpp",
     ])
-    .files(["./src/demo/demo-ffi.cpp"])
+    .files([
+        "./src/recast/recast-ffi.cpp",
+        "./src/detour_tile_cache/tile-cache-ffi.cpp",
+        "./src/demo/demo-ffi.cpp",
+    ])
     .compile("recastnavigation");
 
     println!("cargo:rerun-if-changed=src/utils.h");
 
     println!("cargo:rerun-if-changed=src/recast/recast.rs");
     println!("cargo:rerun-if-changed=src/recast/recast-ffi.h");
+    println!("cargo:rerun-if-changed=src/recast/recast-ffi.cpp");
 
     println!("cargo:rerun-if-changed=src/detour/base.rs");
     println!("cargo:rerun-if-changed=src/detour/builder.rs");
@@ -55,6 +66,13 @@ fn main() {
     println!("cargo:rerun-if-changed=src/detour_crowd/crowd.rs");
     println!("cargo:rerun-if-changed=src/detour_crowd/crowd-ffi.h");
 
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/builder.rs");
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/compressor.rs");
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/mesh_process.rs");
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/tile_cache.rs");
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/tile-cache-ffi.h");
+    println!("cargo:rerun-if-changed=src/detour_tile_cache/tile-cache-ffi.cpp");
+
     println!("cargo:rerun-if-changed=src/demo/demo.rs");
     println!("cargo:rerun-if-changed=src/demo/demo-ffi.h");
     println!("cargo:rerun-if-changed=src/demo/demo-ffi.cpp");
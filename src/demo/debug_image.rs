@@ -0,0 +1,214 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::{RNError, RNResult};
+use crate::recast::{RcCompactHeightfield, RcHeightfieldLayer, RC_BORDER_REG, RC_NULL_AREA, RC_WALKABLE_AREA};
+
+// A top-down image of a recast field, one pixel per cell. Row 0 is the minimum z.
+// Saved as binary PGM (grayscale) or PPM (color), which most image viewers and diff tools open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RcDebugImage {
+    width: usize,
+    height: usize,
+    color: bool,
+    pixels: Vec<u8>,
+}
+
+impl RcDebugImage {
+    fn new(width: i32, height: i32, color: bool) -> RcDebugImage {
+        let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+        let channels = if color { 3 } else { 1 };
+        RcDebugImage {
+            width,
+            height,
+            color,
+            pixels: vec![0; width * height * channels],
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // True for RGB (PPM) images, false for grayscale (PGM) images.
+    #[inline]
+    pub fn is_color(&self) -> bool {
+        self.color
+    }
+
+    // Row-major pixels, 3 bytes per pixel for color images.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> &[u8] {
+        let channels = if self.color { 3 } else { 1 };
+        let idx = (x + y * self.width) * channels;
+        &self.pixels[idx..idx + channels]
+    }
+
+    #[inline]
+    fn set_gray(&mut self, x: i32, y: i32, value: u8) {
+        self.pixels[x as usize + y as usize * self.width] = value;
+    }
+
+    #[inline]
+    fn set_color(&mut self, x: i32, y: i32, color: [u8; 3]) {
+        let idx = (x as usize + y as usize * self.width) * 3;
+        self.pixels[idx..idx + 3].copy_from_slice(&color);
+    }
+
+    // Writes the image as binary PGM (P5) or PPM (P6).
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let magic = if self.color { "P6" } else { "P5" };
+        write!(w, "{}\n{} {}\n255\n", magic, self.width, self.height)?;
+        w.write_all(&self.pixels)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w)?;
+        w.flush()
+    }
+}
+
+// Selects the span of each column drawn by the compact heightfield exporters, for multi-level geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RcSpanSelector {
+    Lowest,
+    Highest,
+    // The n-th span from the bottom.
+    Nth(usize),
+    // The lowest span with a floor (`RcCompactSpan::y`) in [min, max). [Units: vx]
+    HeightSlice(u16, u16),
+}
+
+impl RcSpanSelector {
+    fn select(&self, chf: &RcCompactHeightfield, mut spans: Range<usize>) -> Option<usize> {
+        match *self {
+            RcSpanSelector::Lowest => spans.next(),
+            RcSpanSelector::Highest => spans.next_back(),
+            RcSpanSelector::Nth(n) => spans.nth(n),
+            RcSpanSelector::HeightSlice(min, max) => spans.find(|i| (min..max).contains(&chf.spans()[*i].y)),
+        }
+    }
+}
+
+// Same as duIntToCol() of DebugUtils, a stable color per id.
+pub fn rc_debug_id_color(id: u32) -> [u8; 3] {
+    let bit = |b: u32| ((id >> b) & 1) as u8;
+    let r = bit(1) + bit(3) * 2 + 1;
+    let g = bit(2) + bit(4) * 2 + 1;
+    let b = bit(0) + bit(5) * 2 + 1;
+    [r * 63, g * 63, b * 63]
+}
+
+// Same colors as DebugDrawGL::areaToCol() of RecastDemo: black for RC_NULL_AREA, light blue for RC_WALKABLE_AREA.
+pub fn rc_debug_area_color(area: u8) -> [u8; 3] {
+    match area {
+        RC_NULL_AREA => [0, 0, 0],
+        RC_WALKABLE_AREA => [0, 192, 255],
+        area => rc_debug_id_color(area as u32),
+    }
+}
+
+// The distance field of the selected spans, scaled to [0, 255] by `max_distance`. Empty cells are black.
+// Requires `rc_build_distance_field()`.
+pub fn rc_debug_image_dist(chf: &RcCompactHeightfield, selector: RcSpanSelector) -> RNResult<RcDebugImage> {
+    let dist = chf.dist();
+    if dist.len() != chf.span_count() {
+        return Err(RNError::InvalidParam);
+    }
+    let max_distance = chf.max_distance.max(1) as u32;
+    let mut image = RcDebugImage::new(chf.width(), chf.height(), false);
+    for (x, z, spans) in chf.columns() {
+        if let Some(i) = selector.select(chf, spans) {
+            image.set_gray(x, z, (dist[i] as u32 * 255 / max_distance).min(255) as u8);
+        }
+    }
+    Ok(image)
+}
+
+// The area ids of the selected spans, see `rc_debug_area_color()`.
+pub fn rc_debug_image_areas(chf: &RcCompactHeightfield, selector: RcSpanSelector) -> RcDebugImage {
+    let areas = chf.areas();
+    let mut image = RcDebugImage::new(chf.width(), chf.height(), true);
+    for (x, z, spans) in chf.columns() {
+        if let Some(i) = selector.select(chf, spans) {
+            image.set_color(x, z, rc_debug_area_color(areas[i]));
+        }
+    }
+    image
+}
+
+// The region ids of the selected spans, see `rc_debug_id_color()`.
+// Spans without region are black, border regions (RC_BORDER_REG) are drawn at half intensity.
+pub fn rc_debug_image_regions(chf: &RcCompactHeightfield, selector: RcSpanSelector) -> RcDebugImage {
+    let spans = chf.spans();
+    let mut image = RcDebugImage::new(chf.width(), chf.height(), true);
+    for (x, z, cell) in chf.columns() {
+        let Some(i) = selector.select(chf, cell) else {
+            continue;
+        };
+        let reg = spans[i].reg;
+        let color = match reg {
+            0 => [0, 0, 0],
+            reg if reg & RC_BORDER_REG != 0 => rc_debug_id_color((reg & !RC_BORDER_REG) as u32).map(|c| c / 2),
+            reg => rc_debug_id_color(reg as u32),
+        };
+        image.set_color(x, z, color);
+    }
+    image
+}
+
+// The heights of a heightfield layer, scaled to [1, 255] by `hmin..hmax`. Empty cells are black.
+pub fn rc_debug_image_layer_heights(layer: &RcHeightfieldLayer) -> RcDebugImage {
+    let heights = layer.heights();
+    let mut image = RcDebugImage::new(layer.width(), layer.height(), false);
+    let range = (layer.hmax - layer.hmin).max(1) as u32;
+    for z in 0..layer.height() {
+        for x in 0..layer.width() {
+            let h = heights[(x + z * layer.width()) as usize];
+            if h != 0xff {
+                image.set_gray(x, z, (1 + h as u32 * 254 / range).min(255) as u8);
+            }
+        }
+    }
+    image
+}
+
+// The area ids of a heightfield layer, see `rc_debug_area_color()`.
+pub fn rc_debug_image_layer_areas(layer: &RcHeightfieldLayer) -> RcDebugImage {
+    let areas = layer.areas();
+    let mut image = RcDebugImage::new(layer.width(), layer.height(), true);
+    for z in 0..layer.height() {
+        for x in 0..layer.width() {
+            image.set_color(x, z, rc_debug_area_color(areas[(x + z * layer.width()) as usize]));
+        }
+    }
+    image
+}
+
+// The connections of a heightfield layer. Red is the portal mask (high 4 bits), green the neighbour mask
+// (low 4 bits), each scaled to [0, 255].
+pub fn rc_debug_image_layer_cons(layer: &RcHeightfieldLayer) -> RcDebugImage {
+    let cons = layer.cons();
+    let mut image = RcDebugImage::new(layer.width(), layer.height(), true);
+    for z in 0..layer.height() {
+        for x in 0..layer.width() {
+            let con = cons[(x + z * layer.width()) as usize];
+            image.set_color(x, z, [(con >> 4) * 17, (con & 0xf) * 17, 0]);
+        }
+    }
+    image
+}
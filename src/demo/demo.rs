@@ -6,6 +6,7 @@ use std::slice;
 
 use crate::detour::DtNavMesh;
 use crate::error::{RNError, RNResult};
+use crate::recast::InputGeometry;
 
 #[allow(dead_code)]
 #[cxx::bridge]
@@ -161,6 +162,11 @@ impl Drop for RcChunkyTriMesh {
     }
 }
 
+unsafe impl Send for RcChunkyTriMesh {}
+
+// The chunky mesh is immutable once created, overlap queries only read it.
+unsafe impl Sync for RcChunkyTriMesh {}
+
 impl Default for RcChunkyTriMesh {
     fn default() -> Self {
         Self::new()
@@ -201,12 +207,13 @@ impl RcChunkyTriMesh {
     }
 }
 
-pub fn rc_create_chunky_tri_mesh(
+pub fn rc_create_chunky_tri_mesh<G: InputGeometry + ?Sized>(
     cm: &mut RcChunkyTriMesh,
-    verts: &[[f32; 3]],
-    tris: &[[i32; 3]],
+    geometry: &G,
     tris_per_chunk: i32,
 ) -> RNResult<()> {
+    let verts = geometry.verts();
+    let tris = geometry.tris();
     let verts_ptr = verts.as_ptr() as *const f32;
     let tris_ptr = tris.as_ptr() as *const i32;
     let ntris = tris.len() as i32;
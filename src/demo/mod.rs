@@ -1,3 +1,7 @@
+pub(crate) mod debug_image;
 pub(crate) mod demo;
+pub(crate) mod obj;
 
+pub use debug_image::*;
 pub use demo::*;
+pub use obj::*;
@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::RcObjError;
+
+// A Wavefront OBJ mesh loaded in Rust, a replacement of `RcMeshLoaderObj` with error reporting.
+//
+// Produces the same vertices and triangles as RecastDemo's rcMeshLoaderObj. Polygons are triangulated as fans.
+// The group (`g`) and material (`usemtl`) of each triangle are kept, index 0 is the unnamed default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcObjMesh {
+    verts: Vec<[f32; 3]>,
+    tris: Vec<[i32; 3]>,
+    normals: Vec<[f32; 3]>,
+    groups: Vec<String>,
+    materials: Vec<String>,
+    tri_groups: Vec<u32>,
+    tri_materials: Vec<u32>,
+}
+
+impl RcObjMesh {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RcObjMesh, RcObjError> {
+        let text = fs::read_to_string(path)?;
+        RcObjMesh::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<RcObjMesh, RcObjError> {
+        let mut mesh = RcObjMesh {
+            verts: Vec::new(),
+            tris: Vec::new(),
+            normals: Vec::new(),
+            groups: vec![String::new()],
+            materials: vec![String::new()],
+            tri_groups: Vec::new(),
+            tri_materials: Vec::new(),
+        };
+        let mut group = 0;
+        let mut material = 0;
+        let mut face = Vec::new();
+
+        for (idx, row) in text.lines().enumerate() {
+            let line = idx + 1;
+            let row = match row.find('#') {
+                Some(pos) => &row[..pos],
+                None => row,
+            };
+            let mut tokens = row.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+
+            match keyword {
+                "v" => {
+                    let mut vert = [0.0; 3];
+                    for v in vert.iter_mut() {
+                        let token = tokens
+                            .next()
+                            .ok_or_else(|| parse_error(line, "vertex needs 3 coordinates"))?;
+                        *v = token
+                            .parse()
+                            .map_err(|_| parse_error(line, format!("invalid vertex coordinate '{}'", token)))?;
+                    }
+                    mesh.verts.push(vert);
+                }
+                "f" => {
+                    face.clear();
+                    for token in tokens {
+                        face.push(parse_index(token, mesh.verts.len(), line)?);
+                    }
+                    if face.len() < 3 {
+                        return Err(parse_error(line, "face needs at least 3 vertices"));
+                    }
+                    for i in 2..face.len() {
+                        mesh.tris.push([face[0], face[i - 1], face[i]]);
+                        mesh.tri_groups.push(group);
+                        mesh.tri_materials.push(material);
+                    }
+                }
+                "g" => group = find_or_insert(&mut mesh.groups, &join_rest(tokens)),
+                "usemtl" => material = find_or_insert(&mut mesh.materials, &join_rest(tokens)),
+                _ => {}
+            }
+        }
+
+        if mesh.tris.is_empty() {
+            return Err(RcObjError::Empty);
+        }
+        mesh.normals = mesh.tris.iter().map(|tri| tri_normal(&mesh.verts, tri)).collect();
+        Ok(mesh)
+    }
+
+    #[inline]
+    pub fn get_verts(&self) -> &[[f32; 3]] {
+        &self.verts
+    }
+
+    #[inline]
+    pub fn get_tris(&self) -> &[[i32; 3]] {
+        &self.tris
+    }
+
+    // Per triangle normals.
+    #[inline]
+    pub fn get_normals(&self) -> &[[f32; 3]] {
+        &self.normals
+    }
+
+    #[inline]
+    pub fn get_vert_count(&self) -> i32 {
+        self.verts.len() as i32
+    }
+
+    #[inline]
+    pub fn get_tri_count(&self) -> i32 {
+        self.tris.len() as i32
+    }
+
+    // Group names, `groups()[0]` is the default group ("").
+    #[inline]
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    // Material names, `materials()[0]` is the default material ("").
+    #[inline]
+    pub fn materials(&self) -> &[String] {
+        &self.materials
+    }
+
+    // The group index of each triangle.
+    #[inline]
+    pub fn tri_groups(&self) -> &[u32] {
+        &self.tri_groups
+    }
+
+    // The material index of each triangle.
+    #[inline]
+    pub fn tri_materials(&self) -> &[u32] {
+        &self.tri_materials
+    }
+
+    #[inline]
+    pub fn tri_group(&self, tri: usize) -> &str {
+        &self.groups[self.tri_groups[tri] as usize]
+    }
+
+    #[inline]
+    pub fn tri_material(&self, tri: usize) -> &str {
+        &self.materials[self.tri_materials[tri] as usize]
+    }
+
+    // Maps the material of each triangle to an area id, e.g. for `rc_rasterize_triangles_1()`.
+    pub fn tri_areas_by_material<F: FnMut(&str) -> u8>(&self, mut f: F) -> Vec<u8> {
+        let areas: Vec<u8> = self.materials.iter().map(|name| f(name)).collect();
+        self.tri_materials.iter().map(|m| areas[*m as usize]).collect()
+    }
+}
+
+fn parse_error<S: Into<String>>(line: usize, msg: S) -> RcObjError {
+    RcObjError::Parse { line, msg: msg.into() }
+}
+
+// Parses a face vertex ("v", "v/vt", "v//vn" or "v/vt/vn"), negative indices are relative to the end.
+fn parse_index(token: &str, vert_count: usize, line: usize) -> Result<i32, RcObjError> {
+    let index = token.split('/').next().unwrap_or("");
+    let index: i64 = index
+        .parse()
+        .map_err(|_| parse_error(line, format!("invalid face vertex '{}'", token)))?;
+    let index = match index {
+        i if i < 0 => vert_count as i64 + i,
+        i => i - 1,
+    };
+    if index < 0 || index >= vert_count as i64 {
+        return Err(parse_error(line, format!("face vertex '{}' out of range", token)));
+    }
+    Ok(index as i32)
+}
+
+fn join_rest<'a, I: Iterator<Item = &'a str>>(tokens: I) -> String {
+    tokens.collect::<Vec<_>>().join(" ")
+}
+
+fn find_or_insert(names: &mut Vec<String>, name: &str) -> u32 {
+    match names.iter().position(|n| n == name) {
+        Some(idx) => idx as u32,
+        None => {
+            names.push(name.to_string());
+            (names.len() - 1) as u32
+        }
+    }
+}
+
+fn tri_normal(verts: &[[f32; 3]], tri: &[i32; 3]) -> [f32; 3] {
+    let v0 = &verts[tri[0] as usize];
+    let v1 = &verts[tri[1] as usize];
+    let v2 = &verts[tri[2] as usize];
+    let e0 = [v1[0] - v0[0], v1[1] - v0[1], v1[2] - v0[2]];
+    let e1 = [v2[0] - v0[0], v2[1] - v0[1], v2[2] - v0[2]];
+    let mut n = [
+        e0[1] * e1[2] - e0[2] * e1[1],
+        e0[2] * e1[0] - e0[0] * e1[2],
+        e0[0] * e1[1] - e0[1] * e1[0],
+    ];
+    let d = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if d > 0.0 {
+        let d = 1.0 / d;
+        n = [n[0] * d, n[1] * d, n[2] * d];
+    }
+    n
+}
@@ -19,6 +19,7 @@ pub(crate) mod ffi {
 
         type c_void;
         unsafe fn dtFree(ptr: *mut c_void);
+        unsafe fn dtAllocBytes(size: usize) -> *mut u8;
     }
 }
 
@@ -52,6 +53,24 @@ impl DtStatus {
             return Err(RNError::Failed);
         }
     }
+
+    // The status returned to C++ by a Rust callback, the inverse of `to_result()`.
+    pub(crate) fn from_result(res: RNResult<()>) -> DtStatus {
+        let detail = match res {
+            Ok(()) => return DtStatus(DT_SUCCESS),
+            Err(RNError::InProgress) => return DtStatus(DT_IN_PROGRESS),
+            Err(RNError::WrongMagic) => DT_WRONG_MAGIC,
+            Err(RNError::WrongVersion) => DT_WRONG_VERSION,
+            Err(RNError::OutOfMemory) => DT_OUT_OF_MEMORY,
+            Err(RNError::InvalidParam) => DT_INVALID_PARAM,
+            Err(RNError::BufferTooSmall) => DT_BUFFER_TOO_SMALL,
+            Err(RNError::OutOfNodes) => DT_OUT_OF_NODES,
+            Err(RNError::PartialResult) => DT_PARTIAL_RESULT,
+            Err(RNError::AlreadyOccupied) => DT_ALREADY_OCCUPIED,
+            Err(_) => 0,
+        };
+        DtStatus(DT_FAILURE | detail)
+    }
 }
 
 const DT_FAILURE: u32 = 1 << 31;
@@ -88,19 +107,43 @@ impl Drop for DtBuf {
     }
 }
 
+unsafe impl Send for DtBuf {}
+
 impl DtBuf {
     #[inline]
     pub(crate) fn from_raw(data: *mut u8, size: i32) -> DtBuf {
         DtBuf { data, size }
     }
 
+    // Copies the data into a buffer allocated by dtAlloc(), e.g. tile data loaded from a file.
+    pub fn from_slice(data: &[u8]) -> RNResult<DtBuf> {
+        if data.is_empty() {
+            return Ok(DtBuf::default());
+        }
+        if data.len() > i32::MAX as usize {
+            return Err(RNError::InvalidParam);
+        }
+        let ptr = unsafe { ffi::dtAllocBytes(data.len()) };
+        if ptr.is_null() {
+            return Err(RNError::OutOfMemory);
+        }
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len()) };
+        Ok(DtBuf::from_raw(ptr, data.len() as i32))
+    }
+
     #[inline]
     pub fn as_slice(&self) -> &[u8] {
+        if self.data.is_null() {
+            return &[];
+        }
         return unsafe { std::slice::from_raw_parts(self.data, self.size as usize) };
     }
 
     #[inline]
     pub fn as_slice_mut(&mut self) -> &mut [u8] {
+        if self.data.is_null() {
+            return &mut [];
+        }
         return unsafe { std::slice::from_raw_parts_mut(self.data, self.size as usize) };
     }
 
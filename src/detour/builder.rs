@@ -170,50 +170,148 @@ impl CxxDtNavMeshCreateParams {
             build_bv_tree: params.build_bv_tree,
         }
     }
-}
 
-pub fn dt_create_nav_mesh_data(params: &mut DtNavMeshCreateParams) -> RNResult<DtBuf> {
-    let mut cp = CxxDtNavMeshCreateParams::from(params);
+    // A view of C++ params, without the poly flags and areas (passed as mutable slices beside the view).
+    pub(crate) unsafe fn as_params<'t>(&self) -> DtNavMeshCreateParams<'t> {
+        let poly_count = self.poly_count.max(0) as usize;
+        let off_mesh_con_count = self.off_mesh_con_count.max(0) as usize;
+        DtNavMeshCreateParams {
+            verts: pack_slice(self.verts as *const [u16; 3], self.vert_count.max(0) as usize),
+            polys: pack_slice(self.polys, poly_count * 2 * self.nvp.max(0) as usize),
+            poly_flags: None,
+            poly_areas: None,
+            nvp: self.nvp.max(0) as usize,
+
+            detail_meshes: pack_slice(self.detail_meshes as *const [u32; 4], poly_count),
+            detail_verts: pack_slice(
+                self.detail_verts as *const [f32; 3],
+                self.detail_verts_count.max(0) as usize,
+            ),
+            detail_tris: pack_slice(
+                self.detail_tris as *const [u8; 4],
+                self.detail_tri_count.max(0) as usize,
+            ),
+
+            off_mesh_con_verts: pack_slice(self.off_mesh_con_verts as *const DtAABB, off_mesh_con_count),
+            off_mesh_con_rad: pack_slice(self.off_mesh_con_rad, off_mesh_con_count),
+            off_mesh_con_flags: pack_slice(self.off_mesh_con_flags, off_mesh_con_count),
+            off_mesh_con_areas: pack_slice(self.off_mesh_con_areas, off_mesh_con_count),
+            off_mesh_con_dir: pack_slice(self.off_mesh_con_dir, off_mesh_con_count),
+            off_mesh_con_user_id: pack_slice(self.off_mesh_con_user_id, off_mesh_con_count),
+
+            user_id: self.user_id,
+            tile_x: self.tile_x,
+            tile_y: self.tile_y,
+            tile_layer: self.tile_layer,
+            bmin: self.bmin,
+            bmax: self.bmax,
+
+            walkable_height: self.walkable_height,
+            walkable_radius: self.walkable_radius,
+            walkable_climb: self.walkable_climb,
+            cs: self.cs,
+            ch: self.ch,
+
+            build_bv_tree: self.build_bv_tree,
+        }
+    }
 
-    if cp.vert_count < 3 || cp.vert_count >= 0xFFFF {
-        return Err(RNError::InvalidParam);
+    // Writes back a view returned by `as_params()`, keeping the poly flags and areas.
+    // The view is checked against the kept poly count first, on error the params are left unchanged.
+    pub(crate) fn update_from(&mut self, params: &DtNavMeshCreateParams<'_>) -> RNResult<()> {
+        validate_params(params, self.poly_count)?;
+        let (poly_flags, poly_areas, poly_count) = (self.poly_flags, self.poly_areas, self.poly_count);
+        *self = CxxDtNavMeshCreateParams::from(params);
+        self.poly_flags = poly_flags;
+        self.poly_areas = poly_areas;
+        self.poly_count = poly_count;
+        Ok(())
     }
+}
 
-    if cp.poly_count < 1 {
-        return Err(RNError::InvalidParam);
+#[inline]
+unsafe fn pack_slice<'t, T>(ptr: *const T, len: usize) -> Option<&'t [T]> {
+    if ptr.is_null() {
+        return None;
     }
-    if unpack_len(params.polys) != cp.poly_count * 2 * cp.nvp {
+    Some(std::slice::from_raw_parts(ptr, len))
+}
+
+// Poly vertex index of an unused slot, same as MESH_NULL_IDX in DetourNavMeshBuilder.cpp.
+const MESH_NULL_IDX: u16 = 0xffff;
+
+// Checks everything dtCreateNavMeshData() reads, except the poly flags and areas which have `poly_count` items.
+fn validate_params(params: &DtNavMeshCreateParams<'_>, poly_count: i32) -> RNResult<()> {
+    let vert_count = unpack_len(params.verts);
+    if !(3..0xFFFF).contains(&vert_count) {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.poly_flags) != cp.poly_count {
+    if poly_count < 1 {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.poly_areas) != cp.poly_count {
+    let nvp = params.nvp;
+    if !(3..=DT_VERTS_PER_POLYGON).contains(&nvp) {
         return Err(RNError::InvalidParam);
     }
-    if cp.nvp < 3 || cp.nvp > DT_VERTS_PER_POLYGON as i32 {
+    let polys = params.polys.unwrap_or_default();
+    if polys.len() != poly_count as usize * 2 * nvp {
         return Err(RNError::InvalidParam);
     }
+    let mut poly_vert_counts = Vec::with_capacity(poly_count as usize);
+    for poly in polys.chunks_exact(2 * nvp) {
+        let nv = poly[..nvp].iter().position(|v| *v == MESH_NULL_IDX).unwrap_or(nvp);
+        if nv < 3 || poly[..nv].iter().any(|v| *v as i32 >= vert_count) {
+            return Err(RNError::InvalidParam);
+        }
+        poly_vert_counts.push(nv);
+    }
 
-    if cp.detail_meshes.is_null() && unpack_len(params.detail_meshes) != cp.poly_count {
-        return Err(RNError::InvalidParam);
+    if let Some(detail_meshes) = params.detail_meshes {
+        if detail_meshes.len() != poly_count as usize {
+            return Err(RNError::InvalidParam);
+        }
+        let detail_verts = unpack_len(params.detail_verts) as usize;
+        let detail_tris = params.detail_tris.unwrap_or_default();
+        for (mesh, nv) in detail_meshes.iter().zip(poly_vert_counts) {
+            let [vbase, ndv, tbase, ntris] = mesh.map(|v| v as usize);
+            // The first verts of a detail mesh are the poly verts, they are not copied.
+            if ndv < nv || vbase + ndv > detail_verts || tbase + ntris > detail_tris.len() {
+                return Err(RNError::InvalidParam);
+            }
+            if detail_tris[tbase..tbase + ntris]
+                .iter()
+                .any(|t| t[..3].iter().any(|i| *i as usize >= ndv))
+            {
+                return Err(RNError::InvalidParam);
+            }
+        }
     }
 
-    if unpack_len(params.off_mesh_con_rad) != cp.off_mesh_con_count {
+    let off_mesh_con_count = unpack_len(params.off_mesh_con_verts);
+    if unpack_len(params.off_mesh_con_rad) != off_mesh_con_count {
+        return Err(RNError::InvalidParam);
+    }
+    if unpack_len(params.off_mesh_con_flags) != off_mesh_con_count {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.off_mesh_con_flags) != cp.off_mesh_con_count {
+    if unpack_len(params.off_mesh_con_areas) != off_mesh_con_count {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.off_mesh_con_areas) != cp.off_mesh_con_count {
+    if unpack_len(params.off_mesh_con_dir) != off_mesh_con_count {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.off_mesh_con_dir) != cp.off_mesh_con_count {
+    if unpack_len(params.off_mesh_con_user_id) != off_mesh_con_count {
         return Err(RNError::InvalidParam);
     }
-    if unpack_len(params.off_mesh_con_user_id) != cp.off_mesh_con_count {
+    Ok(())
+}
+
+pub fn dt_create_nav_mesh_data(params: &mut DtNavMeshCreateParams) -> RNResult<DtBuf> {
+    let mut cp = CxxDtNavMeshCreateParams::from(params);
+    if unpack_len(params.poly_flags) != cp.poly_count || unpack_len(params.poly_areas) != cp.poly_count {
         return Err(RNError::InvalidParam);
     }
+    validate_params(params, cp.poly_count)?;
 
     unsafe {
         let mut buf = DtBuf::default();
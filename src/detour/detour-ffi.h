@@ -31,6 +31,11 @@ static_assert(sizeof(dtMeshTile) == SIZE_32_64(60, 104), "dtNavMesh size");
 static_assert(sizeof(dtQueryFilter) == 260, "dtQueryFilter size");
 static_assert(sizeof(dtRaycastHit) == SIZE_32_64(36, 48), "dtQueryFilter size");
 
+// Memory owned by the detour data buffers (`DtBuf`), released by dtFree().
+inline uint8_t* dtAllocBytes(size_t size) {
+    return (uint8_t*)dtAlloc(size, DT_ALLOC_PERM);
+}
+
 //
 // dtPoly
 //
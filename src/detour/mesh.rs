@@ -604,6 +604,8 @@ impl Drop for DtNavMesh {
     }
 }
 
+unsafe impl Send for DtNavMesh {}
+
 impl Default for DtNavMesh {
     fn default() -> Self {
         Self::new()
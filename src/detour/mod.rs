@@ -1,9 +1,11 @@
 pub(crate) mod base;
 pub(crate) mod builder;
 pub(crate) mod mesh;
+pub(crate) mod off_mesh;
 pub(crate) mod query;
 
 pub use base::*;
 pub use builder::*;
 pub use mesh::*;
+pub use off_mesh::*;
 pub use query::*;
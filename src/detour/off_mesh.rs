@@ -0,0 +1,138 @@
+use crate::detour::base::DtAABB;
+use crate::detour::builder::DtNavMeshCreateParams;
+use crate::detour::mesh::DT_OFFMESH_CON_BIDIR;
+
+// An off-mesh connection (jump, ladder, teleport, ...) between two points of the navigation mesh.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct OffMeshConnection {
+    // The start and end points. The connection belongs to the tile containing the start point. [Units: wu]
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+
+    // The radius of the endpoints. [Limit: >= 0] [Units: wu]
+    pub radius: f32,
+
+    // If false, the connection can only be traversed from start to end.
+    pub bidirectional: bool,
+
+    pub area: u8,
+    pub flags: u16,
+    pub user_id: u32,
+}
+
+// Off-mesh connections passed to the builders, one struct per connection instead of the parallel
+// `off_mesh_con_*` slices of `DtNavMeshCreateParams`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct OffMeshConnectionSet {
+    connections: Vec<OffMeshConnection>,
+}
+
+impl OffMeshConnectionSet {
+    #[inline]
+    pub fn new() -> OffMeshConnectionSet {
+        OffMeshConnectionSet::default()
+    }
+
+    // Adds a connection and returns its index.
+    #[inline]
+    pub fn add(&mut self, connection: OffMeshConnection) -> usize {
+        self.connections.push(connection);
+        self.connections.len() - 1
+    }
+
+    #[inline]
+    pub fn remove(&mut self, idx: usize) -> OffMeshConnection {
+        self.connections.remove(idx)
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.connections.clear();
+    }
+
+    #[inline]
+    pub fn connections(&self) -> &[OffMeshConnection] {
+        &self.connections
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    // The connections whose start point is inside the xz-rectangle [bmin, bmax). The far edges reaching
+    // `world_bmax` are included too, so the connections on the far side of the world are not dropped.
+    pub(crate) fn connections_in<'a>(
+        &'a self,
+        bmin: &'a [f32; 3],
+        bmax: &'a [f32; 3],
+        world_bmax: &'a [f32; 3],
+    ) -> impl Iterator<Item = &'a OffMeshConnection> + 'a {
+        let inside =
+            move |v: f32, i: usize| v >= bmin[i] && (v < bmax[i] || (v <= bmax[i] && bmax[i] >= world_bmax[i]));
+        self.connections
+            .iter()
+            .filter(move |con| inside(con.start[0], 0) && inside(con.start[2], 2))
+    }
+
+    pub(crate) fn columns_in(&self, bmin: &[f32; 3], bmax: &[f32; 3], world_bmax: &[f32; 3]) -> OffMeshColumns {
+        let mut columns = OffMeshColumns::default();
+        self.connections_in(bmin, bmax, world_bmax)
+            .for_each(|con| columns.push(con));
+        columns
+    }
+
+    pub(crate) fn columns(&self) -> OffMeshColumns {
+        let mut columns = OffMeshColumns::default();
+        self.connections.iter().for_each(|con| columns.push(con));
+        columns
+    }
+}
+
+// Off-mesh connections in the layout of `DtNavMeshCreateParams`.
+#[derive(Debug, Default)]
+pub(crate) struct OffMeshColumns {
+    verts: Vec<DtAABB>,
+    rad: Vec<f32>,
+    flags: Vec<u16>,
+    areas: Vec<u8>,
+    dir: Vec<u8>,
+    user_id: Vec<u32>,
+}
+
+impl OffMeshColumns {
+    fn push(&mut self, con: &OffMeshConnection) {
+        self.verts.push(DtAABB {
+            a: con.start,
+            b: con.end,
+        });
+        self.rad.push(con.radius);
+        self.flags.push(con.flags);
+        self.areas.push(con.area);
+        self.dir.push(if con.bidirectional {
+            DT_OFFMESH_CON_BIDIR as u8
+        } else {
+            0
+        });
+        self.user_id.push(con.user_id);
+    }
+
+    pub(crate) fn fill_params<'t>(&'t self, params: &mut DtNavMeshCreateParams<'t>) {
+        if self.verts.is_empty() {
+            return;
+        }
+        params.off_mesh_con_verts = Some(&self.verts);
+        params.off_mesh_con_rad = Some(&self.rad);
+        params.off_mesh_con_flags = Some(&self.flags);
+        params.off_mesh_con_areas = Some(&self.areas);
+        params.off_mesh_con_dir = Some(&self.dir);
+        params.off_mesh_con_user_id = Some(&self.user_id);
+    }
+}
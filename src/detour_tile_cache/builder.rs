@@ -0,0 +1,418 @@
+use cxx::{type_id, ExternType};
+use static_assertions::const_assert_eq;
+use std::mem;
+use std::pin::Pin;
+use std::ptr;
+
+use crate::detour::DtBuf;
+use crate::detour_tile_cache::compressor::{with_cxx_compressor, DtTileCacheCompressor};
+use crate::error::{RNError, RNResult};
+use crate::recast::{RcHeightfieldLayer, RcHeightfieldLayerSet};
+
+pub const DT_TILECACHE_MAGIC: i32 = (('D' as i32) << 24) | (('T' as i32) << 16) | (('L' as i32) << 8) | ('R' as i32);
+pub const DT_TILECACHE_VERSION: i32 = 1;
+
+pub const DT_TILECACHE_NULL_AREA: u8 = 0;
+pub const DT_TILECACHE_WALKABLE_AREA: u8 = 63;
+pub const DT_TILECACHE_NULL_IDX: u16 = 0xffff;
+
+#[allow(dead_code)]
+#[cxx::bridge]
+pub(crate) mod ffi {
+    unsafe extern "C++" {
+        include!("recastnavigation-rs/src/detour_tile_cache/tile-cache-ffi.h");
+
+        type dtStatus = crate::detour::base::DtStatus;
+        type dtTileCacheCompressor = crate::detour_tile_cache::compressor::ffi::dtTileCacheCompressor;
+
+        type dtTileCacheLayerHeader = crate::detour_tile_cache::builder::DtTileCacheLayerHeader;
+        type dtTileCacheLayer = crate::detour_tile_cache::builder::CxxDtTileCacheLayer;
+
+        unsafe fn dttc_buildTileCacheLayer(
+            comp: Pin<&mut dtTileCacheCompressor>,
+            header: *const dtTileCacheLayerHeader,
+            heights: *const u8,
+            areas: *const u8,
+            cons: *const u8,
+            outData: *mut *mut u8,
+            outDataSize: *mut i32,
+        ) -> dtStatus;
+        unsafe fn dttc_decompressTileCacheLayer(
+            comp: Pin<&mut dtTileCacheCompressor>,
+            compressed: *const u8,
+            compressedSize: i32,
+            layerOut: *mut *mut dtTileCacheLayer,
+        ) -> dtStatus;
+        unsafe fn dttc_freeTileCacheLayer(layer: *mut dtTileCacheLayer);
+        fn dttc_buildTileCacheRegions(layer: Pin<&mut dtTileCacheLayer>, walkableClimb: i32) -> dtStatus;
+        unsafe fn dttc_markCylinderArea(
+            layer: Pin<&mut dtTileCacheLayer>,
+            orig: *const f32,
+            cs: f32,
+            ch: f32,
+            pos: *const f32,
+            radius: f32,
+            height: f32,
+            areaId: u8,
+        ) -> dtStatus;
+        unsafe fn dttc_markBoxArea(
+            layer: Pin<&mut dtTileCacheLayer>,
+            orig: *const f32,
+            cs: f32,
+            ch: f32,
+            bmin: *const f32,
+            bmax: *const f32,
+            areaId: u8,
+        ) -> dtStatus;
+        unsafe fn dttc_markOrientedBoxArea(
+            layer: Pin<&mut dtTileCacheLayer>,
+            orig: *const f32,
+            cs: f32,
+            ch: f32,
+            center: *const f32,
+            halfExtents: *const f32,
+            yRadians: f32,
+            areaId: u8,
+        ) -> dtStatus;
+    }
+}
+
+//
+// DtTileCacheLayerHeader
+//
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DtTileCacheLayerHeader {
+    // Data magic, DT_TILECACHE_MAGIC.
+    pub magic: i32,
+    // Data version, DT_TILECACHE_VERSION.
+    pub version: i32,
+    pub tx: i32,
+    pub ty: i32,
+    pub tlayer: i32,
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    // Height min/max range. [Units: vx]
+    pub hmin: u16,
+    pub hmax: u16,
+    // Dimension of the layer. [Units: vx]
+    pub width: u8,
+    pub height: u8,
+    // Usable sub-region. [Units: vx]
+    pub minx: u8,
+    pub maxx: u8,
+    pub miny: u8,
+    pub maxy: u8,
+}
+
+const_assert_eq!(mem::size_of::<DtTileCacheLayerHeader>(), 56);
+
+unsafe impl ExternType for DtTileCacheLayerHeader {
+    type Id = type_id!("dtTileCacheLayerHeader");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl DtTileCacheLayerHeader {
+    // The header of a layer built by `rc_build_heightfield_layers()`, same as Sample_TempObstacles.
+    // Fails if the layer does not fit the header, i.e. a dimension above 255 voxels or a height above 65535 voxels.
+    pub fn from_layer(layer: &RcHeightfieldLayer, tx: i32, ty: i32, tlayer: i32) -> RNResult<DtTileCacheLayerHeader> {
+        let to_u8 = |v: i32| u8::try_from(v).map_err(|_| RNError::InvalidParam);
+        let to_u16 = |v: i32| u16::try_from(v).map_err(|_| RNError::InvalidParam);
+        Ok(DtTileCacheLayerHeader {
+            magic: DT_TILECACHE_MAGIC,
+            version: DT_TILECACHE_VERSION,
+            tx,
+            ty,
+            tlayer,
+            bmin: layer.bmin,
+            bmax: layer.bmax,
+            hmin: to_u16(layer.hmin)?,
+            hmax: to_u16(layer.hmax)?,
+            width: to_u8(layer.width())?,
+            height: to_u8(layer.height())?,
+            minx: to_u8(layer.minx)?,
+            maxx: to_u8(layer.maxx)?,
+            miny: to_u8(layer.miny)?,
+            maxy: to_u8(layer.maxy)?,
+        })
+    }
+}
+
+//
+// DtTileCacheLayer
+//
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct CxxDtTileCacheLayer {
+    header: *mut DtTileCacheLayerHeader,
+    // Region count.
+    pub reg_count: u8,
+    heights: *mut u8,
+    areas: *mut u8,
+    cons: *mut u8,
+    regs: *mut u8,
+}
+
+#[cfg(target_pointer_width = "64")]
+const_assert_eq!(mem::size_of::<CxxDtTileCacheLayer>(), 48);
+
+#[cfg(target_pointer_width = "32")]
+const_assert_eq!(mem::size_of::<CxxDtTileCacheLayer>(), 24);
+
+unsafe impl ExternType for CxxDtTileCacheLayer {
+    type Id = type_id!("dtTileCacheLayer");
+    type Kind = cxx::kind::Trivial;
+}
+
+// A decompressed tile cache layer, see `dt_decompress_tile_cache_layer()`.
+#[derive(Debug)]
+pub struct DtTileCacheLayer(*mut CxxDtTileCacheLayer);
+
+impl Drop for DtTileCacheLayer {
+    fn drop(&mut self) {
+        unsafe { ffi::dttc_freeTileCacheLayer(self.0) };
+        self.0 = ptr::null_mut();
+    }
+}
+
+unsafe impl Send for DtTileCacheLayer {}
+
+impl DtTileCacheLayer {
+    #[inline]
+    fn inner(&self) -> &CxxDtTileCacheLayer {
+        unsafe { &*self.0 }
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> Pin<&mut CxxDtTileCacheLayer> {
+        unsafe { Pin::new_unchecked(&mut *self.0) }
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const CxxDtTileCacheLayer {
+        self.0
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut CxxDtTileCacheLayer {
+        self.0
+    }
+
+    #[inline]
+    pub fn header(&self) -> &DtTileCacheLayerHeader {
+        unsafe { &*self.inner().header }
+    }
+
+    #[inline]
+    pub fn reg_count(&self) -> u8 {
+        self.inner().reg_count
+    }
+
+    #[inline]
+    fn cell_count(&self) -> usize {
+        self.header().width as usize * self.header().height as usize
+    }
+
+    // The heights of the cells, 0xff for empty cells. [Size: width * height]
+    #[inline]
+    pub fn heights(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.inner().heights, self.cell_count()) }
+    }
+
+    #[inline]
+    pub fn heights_mut(&mut self) -> &mut [u8] {
+        let count = self.cell_count();
+        unsafe { std::slice::from_raw_parts_mut(self.inner().heights, count) }
+    }
+
+    #[inline]
+    pub fn areas(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.inner().areas, self.cell_count()) }
+    }
+
+    #[inline]
+    pub fn areas_mut(&mut self) -> &mut [u8] {
+        let count = self.cell_count();
+        unsafe { std::slice::from_raw_parts_mut(self.inner().areas, count) }
+    }
+
+    // The packed neighbour connection and portal flags of the cells.
+    #[inline]
+    pub fn cons(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.inner().cons, self.cell_count()) }
+    }
+
+    #[inline]
+    pub fn cons_mut(&mut self) -> &mut [u8] {
+        let count = self.cell_count();
+        unsafe { std::slice::from_raw_parts_mut(self.inner().cons, count) }
+    }
+
+    // The region ids of the cells, 0xff until `dt_build_tile_cache_regions()`.
+    #[inline]
+    pub fn regs(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.inner().regs, self.cell_count()) }
+    }
+}
+
+//
+// functions
+//
+
+// Builds the data of a tile cache layer, which can be added to `DtTileCache::add_tile()`.
+// `heights`, `areas` and `cons` have `header.width * header.height` cells, e.g. of a `RcHeightfieldLayer`.
+// `compressor` must be the same as the one of the tile cache, see `DtTileCache::compressor()`.
+pub fn dt_build_tile_cache_layer(
+    compressor: &dyn DtTileCacheCompressor,
+    header: &DtTileCacheLayerHeader,
+    heights: &[u8],
+    areas: &[u8],
+    cons: &[u8],
+) -> RNResult<DtBuf> {
+    let cell_count = header.width as usize * header.height as usize;
+    if heights.len() != cell_count || areas.len() != cell_count || cons.len() != cell_count {
+        return Err(RNError::InvalidParam);
+    }
+    let mut data = ptr::null_mut();
+    let mut data_size = 0;
+    with_cxx_compressor(compressor, |comp| unsafe {
+        ffi::dttc_buildTileCacheLayer(
+            comp,
+            header,
+            heights.as_ptr(),
+            areas.as_ptr(),
+            cons.as_ptr(),
+            &mut data,
+            &mut data_size,
+        )
+    })
+    .to_result()?;
+    Ok(DtBuf::from_raw(data, data_size))
+}
+
+// Builds the data of all layers of a tile, in layer order, so `tlayer` is the index of the layer in `lset`.
+// Each data can be added to `DtTileCache::add_tile()`, or stored to fill a tile cache later.
+pub fn dt_build_tile_cache_layers(
+    compressor: &dyn DtTileCacheCompressor,
+    lset: &RcHeightfieldLayerSet,
+    tx: i32,
+    ty: i32,
+) -> RNResult<Vec<DtBuf>> {
+    let layers = lset.layers();
+    let mut datas = Vec::with_capacity(layers.len());
+    for (i, layer) in layers.iter().enumerate() {
+        let header = DtTileCacheLayerHeader::from_layer(layer, tx, ty, i as i32)?;
+        let data = dt_build_tile_cache_layer(compressor, &header, layer.heights(), layer.areas(), layer.cons())?;
+        datas.push(data);
+    }
+    Ok(datas)
+}
+
+// Decompresses the data built by `dt_build_tile_cache_layer()`.
+pub fn dt_decompress_tile_cache_layer(
+    compressor: &dyn DtTileCacheCompressor,
+    data: &[u8],
+) -> RNResult<DtTileCacheLayer> {
+    if data.len() < mem::size_of::<DtTileCacheLayerHeader>() || data.len() > i32::MAX as usize {
+        return Err(RNError::InvalidParam);
+    }
+    // dtDecompressTileCacheLayer() reads the header in place, copy the data if it is not aligned.
+    let mut aligned: Vec<u32> = Vec::new();
+    let data = if data.as_ptr() as usize % mem::align_of::<DtTileCacheLayerHeader>() == 0 {
+        data
+    } else {
+        aligned.resize(data.len().div_ceil(4), 0);
+        let buf = unsafe { std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, data.len()) };
+        buf.copy_from_slice(data);
+        &*buf
+    };
+    let mut layer = ptr::null_mut();
+    with_cxx_compressor(compressor, |comp| unsafe {
+        ffi::dttc_decompressTileCacheLayer(comp, data.as_ptr(), data.len() as i32, &mut layer)
+    })
+    .to_result()?;
+    Ok(DtTileCacheLayer(layer))
+}
+
+pub fn dt_build_tile_cache_regions(layer: &mut DtTileCacheLayer, walkable_climb: i32) -> RNResult<()> {
+    ffi::dttc_buildTileCacheRegions(layer.inner_mut(), walkable_climb).to_result()
+}
+
+// Marks the area of the cells inside a vertical cylinder. `orig`, `cs` and `ch` are the ones of `DtTileCacheParams`.
+pub fn dt_mark_cylinder_area(
+    layer: &mut DtTileCacheLayer,
+    orig: &[f32; 3],
+    cs: f32,
+    ch: f32,
+    pos: &[f32; 3],
+    radius: f32,
+    height: f32,
+    area_id: u8,
+) -> RNResult<()> {
+    unsafe {
+        ffi::dttc_markCylinderArea(
+            layer.inner_mut(),
+            orig.as_ptr(),
+            cs,
+            ch,
+            pos.as_ptr(),
+            radius,
+            height,
+            area_id,
+        )
+    }
+    .to_result()
+}
+
+// Marks the area of the cells inside an axis-aligned box.
+pub fn dt_mark_box_area(
+    layer: &mut DtTileCacheLayer,
+    orig: &[f32; 3],
+    cs: f32,
+    ch: f32,
+    bmin: &[f32; 3],
+    bmax: &[f32; 3],
+    area_id: u8,
+) -> RNResult<()> {
+    unsafe {
+        ffi::dttc_markBoxArea(
+            layer.inner_mut(),
+            orig.as_ptr(),
+            cs,
+            ch,
+            bmin.as_ptr(),
+            bmax.as_ptr(),
+            area_id,
+        )
+    }
+    .to_result()
+}
+
+// Marks the area of the cells inside a box rotated by `y_radians` around the y-axis.
+// The rotation is computed in C++ the same way as `DtTileCache::add_oriented_box_obstacle()`.
+pub fn dt_mark_oriented_box_area(
+    layer: &mut DtTileCacheLayer,
+    orig: &[f32; 3],
+    cs: f32,
+    ch: f32,
+    center: &[f32; 3],
+    half_extents: &[f32; 3],
+    y_radians: f32,
+    area_id: u8,
+) -> RNResult<()> {
+    unsafe {
+        ffi::dttc_markOrientedBoxArea(
+            layer.inner_mut(),
+            orig.as_ptr(),
+            cs,
+            ch,
+            center.as_ptr(),
+            half_extents.as_ptr(),
+            y_radians,
+            area_id,
+        )
+    }
+    .to_result()
+}
@@ -0,0 +1,217 @@
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+use crate::detour::base::DtStatus;
+use crate::error::{RNError, RNResult};
+
+#[allow(dead_code)]
+#[cxx::bridge]
+pub(crate) mod ffi {
+    extern "Rust" {
+        type DtCompressorState<'a>;
+        #[cxx_name = "dtCompressorMaxCompressedSize"]
+        fn compressor_max_compressed_size(rs: &DtCompressorState, buffer_size: i32) -> i32;
+        #[cxx_name = "dtCompressorCompress"]
+        fn compressor_compress(
+            rs: &DtCompressorState,
+            buffer: &[u8],
+            compressed: &mut [u8],
+            compressed_size: &mut i32,
+        ) -> dtStatus;
+        #[cxx_name = "dtCompressorDecompress"]
+        fn compressor_decompress(
+            rs: &DtCompressorState,
+            compressed: &[u8],
+            buffer: &mut [u8],
+            buffer_size: &mut i32,
+        ) -> dtStatus;
+    }
+
+    unsafe extern "C++" {
+        include!("recastnavigation-rs/src/detour_tile_cache/tile-cache-ffi.h");
+
+        type dtStatus = crate::detour::base::DtStatus;
+
+        type dtTileCacheCompressor;
+        unsafe fn dttc_newCompressor(rs: &DtCompressorState) -> UniquePtr<dtTileCacheCompressor>;
+    }
+}
+
+// Compresses the layers of a tile cache, see `DtTileCache::with_params()` and `dt_build_tile_cache_layer()`.
+//
+// The compressed layers are stored in the tile cache and may be sent to other peers, so an implementation
+// must give the same bytes for the same input on every platform.
+pub trait DtTileCacheCompressor: Send {
+    // The maximum compressed size of `buffer_size` bytes.
+    fn max_compressed_size(&self, buffer_size: usize) -> usize;
+
+    // Compresses `buffer` into `compressed`, which has `max_compressed_size()` bytes.
+    // Returns the compressed size.
+    fn compress(&self, buffer: &[u8], compressed: &mut [u8]) -> RNResult<usize>;
+
+    // Decompresses `compressed` into `buffer`. Returns the decompressed size.
+    fn decompress(&self, compressed: &[u8], buffer: &mut [u8]) -> RNResult<usize>;
+}
+
+// Stores the layers as is, the fastest choice when memory is not a concern.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DtPassthroughCompressor;
+
+impl DtTileCacheCompressor for DtPassthroughCompressor {
+    #[inline]
+    fn max_compressed_size(&self, buffer_size: usize) -> usize {
+        buffer_size
+    }
+
+    fn compress(&self, buffer: &[u8], compressed: &mut [u8]) -> RNResult<usize> {
+        copy_into(buffer, compressed)
+    }
+
+    fn decompress(&self, compressed: &[u8], buffer: &mut [u8]) -> RNResult<usize> {
+        copy_into(compressed, buffer)
+    }
+}
+
+fn copy_into(src: &[u8], dst: &mut [u8]) -> RNResult<usize> {
+    if src.len() > dst.len() {
+        return Err(RNError::BufferTooSmall);
+    }
+    dst[..src.len()].copy_from_slice(src);
+    Ok(src.len())
+}
+
+// Compresses the layers into zlib streams with miniz_oxide.
+//
+// miniz_oxide is pure Rust and has no platform specific code paths, the output only depends on the
+// input, the level and the miniz_oxide version. Any version decompresses the layers, but peers comparing
+// the compressed bytes must use the same level and the same miniz_oxide version, e.g. from a shared
+// Cargo.lock.
+#[cfg(feature = "rkyv")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DtDeflateCompressor {
+    level: u8,
+}
+
+#[cfg(feature = "rkyv")]
+impl Default for DtDeflateCompressor {
+    #[inline]
+    fn default() -> Self {
+        DtDeflateCompressor::new(DtDeflateCompressor::DEFAULT_LEVEL)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl DtDeflateCompressor {
+    pub const DEFAULT_LEVEL: u8 = 6;
+    pub const MAX_LEVEL: u8 = 10;
+
+    // `level` is clamped to `MAX_LEVEL`, 0 stores the layers without compression.
+    #[inline]
+    pub fn new(level: u8) -> DtDeflateCompressor {
+        DtDeflateCompressor {
+            level: level.min(Self::MAX_LEVEL),
+        }
+    }
+
+    #[inline]
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl DtTileCacheCompressor for DtDeflateCompressor {
+    // Same as mz_compressBound().
+    #[inline]
+    fn max_compressed_size(&self, buffer_size: usize) -> usize {
+        usize::max(
+            128 + buffer_size * 110 / 100,
+            128 + buffer_size + (buffer_size / (31 * 1024) + 1) * 5,
+        )
+    }
+
+    fn compress(&self, buffer: &[u8], compressed: &mut [u8]) -> RNResult<usize> {
+        let data = miniz_oxide::deflate::compress_to_vec_zlib(buffer, self.level);
+        copy_into(&data, compressed)
+    }
+
+    fn decompress(&self, compressed: &[u8], buffer: &mut [u8]) -> RNResult<usize> {
+        use miniz_oxide::inflate::{decompress_slice_iter_to_slice, TINFLStatus};
+
+        match decompress_slice_iter_to_slice(buffer, std::iter::once(compressed), true, false) {
+            Ok(size) => Ok(size),
+            Err(TINFLStatus::HasMoreOutput) => Err(RNError::BufferTooSmall),
+            Err(_) => Err(RNError::InvalidParam),
+        }
+    }
+}
+
+// The Rust side of a C++ compressor, the C++ compressor keeps a pointer to it.
+// The compressor is owned elsewhere, by the caller or by a `DtTileCache`, and must outlive the state.
+pub(crate) struct DtCompressorState<'a>(pub(crate) *const (dyn DtTileCacheCompressor + 'a));
+
+impl<'a> DtCompressorState<'a> {
+    #[inline]
+    pub(crate) fn new(compressor: &'a dyn DtTileCacheCompressor) -> DtCompressorState<'a> {
+        DtCompressorState(compressor)
+    }
+
+    #[inline]
+    fn compressor(&self) -> &dyn DtTileCacheCompressor {
+        unsafe { &*self.0 }
+    }
+}
+
+fn compressor_max_compressed_size(rs: &DtCompressorState, buffer_size: i32) -> i32 {
+    let size = rs.compressor().max_compressed_size(buffer_size.max(0) as usize);
+    size.min(i32::MAX as usize) as i32
+}
+
+fn compressor_compress(
+    rs: &DtCompressorState,
+    buffer: &[u8],
+    compressed: &mut [u8],
+    compressed_size: &mut i32,
+) -> DtStatus {
+    let res = rs.compressor().compress(buffer, compressed).map(|size| {
+        *compressed_size = size as i32;
+    });
+    DtStatus::from_result(res)
+}
+
+fn compressor_decompress(
+    rs: &DtCompressorState,
+    compressed: &[u8],
+    buffer: &mut [u8],
+    buffer_size: &mut i32,
+) -> DtStatus {
+    // The tile cache decompresses into grids of the exact layer size, a shorter output would leave them uninitialised.
+    let grid_size = buffer.len();
+    let res = rs
+        .compressor()
+        .decompress(compressed, buffer)
+        .and_then(|size| match size == grid_size {
+            true => {
+                *buffer_size = size as i32;
+                Ok(())
+            }
+            false => Err(RNError::InvalidParam),
+        });
+    DtStatus::from_result(res)
+}
+
+// A C++ compressor forwarding to a Rust one. `state` must outlive the returned compressor.
+#[inline]
+pub(crate) unsafe fn new_cxx_compressor(state: &DtCompressorState) -> UniquePtr<ffi::dtTileCacheCompressor> {
+    ffi::dttc_newCompressor(state)
+}
+
+// Runs `f` with a temporary C++ compressor forwarding to `compressor`.
+pub(crate) fn with_cxx_compressor<R>(
+    compressor: &dyn DtTileCacheCompressor,
+    f: impl FnOnce(Pin<&mut ffi::dtTileCacheCompressor>) -> R,
+) -> R {
+    let state = DtCompressorState::new(compressor);
+    let mut cxx_compressor = unsafe { new_cxx_compressor(&state) };
+    f(cxx_compressor.pin_mut())
+}
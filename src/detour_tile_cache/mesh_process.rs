@@ -0,0 +1,135 @@
+use cxx::UniquePtr;
+
+use crate::detour::builder::CxxDtNavMeshCreateParams;
+use crate::detour::off_mesh::{OffMeshColumns, OffMeshConnectionSet};
+use crate::detour::DtNavMeshCreateParams;
+
+#[allow(dead_code)]
+#[cxx::bridge]
+pub(crate) mod ffi {
+    extern "Rust" {
+        type DtMeshProcessState;
+        #[cxx_name = "dtMeshProcessProcess"]
+        fn mesh_process_process(
+            rs: &mut DtMeshProcessState,
+            params: &mut dtNavMeshCreateParams,
+            poly_areas: &mut [u8],
+            poly_flags: &mut [u16],
+        );
+    }
+
+    unsafe extern "C++" {
+        include!("recastnavigation-rs/src/detour_tile_cache/tile-cache-ffi.h");
+
+        type dtNavMeshCreateParams = crate::detour::builder::CxxDtNavMeshCreateParams;
+
+        type dtTileCacheMeshProcess;
+        unsafe fn dttc_newMeshProcess(rs: &mut DtMeshProcessState) -> UniquePtr<dtTileCacheMeshProcess>;
+    }
+}
+
+// Post-processes the navigation mesh tiles built by a `DtTileCache`, before the tile data is created.
+// It is called for every tile build, including the rebuilds of `DtTileCache::update()`, so the poly flags
+// and off-mesh connections set here are kept when obstacles change the tile.
+//
+// `params` has no poly flags and areas, they are passed as `poly_areas` and `poly_flags`. The data set into
+// `params` may borrow from `self`, e.g. the off-mesh connections of the tile. If the changed `params` are
+// inconsistent, e.g. off-mesh arrays of different lengths, all changes to `params` are ignored.
+pub trait DtTileCacheMeshProcess: Send {
+    fn process<'a>(&'a mut self, params: &mut DtNavMeshCreateParams<'a>, poly_areas: &mut [u8], poly_flags: &mut [u16]);
+}
+
+impl<F> DtTileCacheMeshProcess for F
+where
+    F: for<'t> FnMut(&mut DtNavMeshCreateParams<'t>, &mut [u8], &mut [u16]) + Send,
+{
+    #[inline]
+    fn process<'a>(
+        &'a mut self,
+        params: &mut DtNavMeshCreateParams<'a>,
+        poly_areas: &mut [u8],
+        poly_flags: &mut [u16],
+    ) {
+        self(params, poly_areas, poly_flags)
+    }
+}
+
+// Gives all polys the flags 1, same as the recast builders without poly flags callback,
+// and adds the off-mesh connections starting in the tile.
+#[derive(Debug)]
+pub struct DtDefaultMeshProcess {
+    off_mesh: OffMeshConnectionSet,
+    world_bmax: [f32; 3],
+    columns: OffMeshColumns,
+}
+
+impl Default for DtDefaultMeshProcess {
+    #[inline]
+    fn default() -> Self {
+        DtDefaultMeshProcess::with_off_mesh(OffMeshConnectionSet::new())
+    }
+}
+
+impl DtDefaultMeshProcess {
+    #[inline]
+    pub fn new() -> DtDefaultMeshProcess {
+        DtDefaultMeshProcess::default()
+    }
+
+    #[inline]
+    pub fn with_off_mesh(off_mesh: OffMeshConnectionSet) -> DtDefaultMeshProcess {
+        DtDefaultMeshProcess {
+            off_mesh,
+            world_bmax: [f32::INFINITY; 3],
+            columns: OffMeshColumns::default(),
+        }
+    }
+
+    // The far corner of the tiled world, e.g. `TileMeshBuilder::bounds()`. The tiles reaching it also take
+    // the connections starting on their far edges, otherwise a tile only takes the connections in [bmin, bmax).
+    #[inline]
+    pub fn set_world_bmax(&mut self, world_bmax: [f32; 3]) {
+        self.world_bmax = world_bmax;
+    }
+
+    #[inline]
+    pub fn off_mesh(&self) -> &OffMeshConnectionSet {
+        &self.off_mesh
+    }
+}
+
+impl DtTileCacheMeshProcess for DtDefaultMeshProcess {
+    fn process<'a>(
+        &'a mut self,
+        params: &mut DtNavMeshCreateParams<'a>,
+        _poly_areas: &mut [u8],
+        poly_flags: &mut [u16],
+    ) {
+        poly_flags.fill(1);
+        // Connections are selected on xz only, dtCreateNavMeshData() drops the ones outside the layer heights.
+        self.columns = self.off_mesh.columns_in(&params.bmin, &params.bmax, &self.world_bmax);
+        self.columns.fill_params(params);
+    }
+}
+
+// The Rust side of a C++ mesh process, the C++ mesh process keeps a pointer to it.
+pub(crate) struct DtMeshProcessState(pub(crate) Box<dyn DtTileCacheMeshProcess>);
+
+fn mesh_process_process(
+    rs: &mut DtMeshProcessState,
+    params: &mut CxxDtNavMeshCreateParams,
+    poly_areas: &mut [u8],
+    poly_flags: &mut [u16],
+) {
+    let mut view = unsafe { params.as_params() };
+    rs.0.process(&mut view, poly_areas, poly_flags);
+    // The data set by the process is used by dtCreateNavMeshData() right after, before the next process call.
+    // Data that dtCreateNavMeshData() could read out of bounds is dropped, the tile is built from the original params.
+    let _ = params.update_from(&view);
+}
+
+// A C++ mesh process forwarding to a Rust one. `state` must outlive the returned mesh process.
+#[inline]
+pub(crate) unsafe fn new_cxx_mesh_process(state: &mut DtMeshProcessState) -> UniquePtr<ffi::dtTileCacheMeshProcess> {
+    ffi::dttc_newMeshProcess(state)
+}
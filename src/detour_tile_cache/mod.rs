@@ -1 +1,11 @@
+pub(crate) mod builder;
+pub(crate) mod compressor;
+pub(crate) mod mesh_process;
+pub(crate) mod obstacles;
+pub(crate) mod tile_cache;
 
+pub use builder::*;
+pub use compressor::*;
+pub use mesh_process::*;
+pub use obstacles::*;
+pub use tile_cache::*;
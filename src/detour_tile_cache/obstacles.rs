@@ -0,0 +1,363 @@
+use crate::detour::{DtNavMesh, DtTileRef};
+use crate::detour_tile_cache::tile_cache::{
+    DtCompressedTileRef, DtObstacleRef, DtObstacleState, DtTileCache, DtTileCacheObstacle, DT_MAX_TOUCHED_TILES,
+};
+use crate::error::{RNError, RNResult};
+
+// Same as MAX_REQUESTS in DetourTileCache.cpp. `DtTileCache::update()` only consumes the requests once the
+// previously touched tiles are rebuilt, so they are counted until it reports up to date.
+const MAX_REQUESTS: usize = 64;
+
+// A temporary obstacle of a `DtObstacleManager`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DtObstacle {
+    // A vertical cylinder standing on `pos`.
+    Cylinder {
+        pos: [f32; 3],
+        radius: f32,
+        height: f32,
+    },
+    // An axis-aligned box.
+    Box {
+        bmin: [f32; 3],
+        bmax: [f32; 3],
+    },
+    // A box rotated by `y_radians` around the y-axis.
+    OrientedBox {
+        center: [f32; 3],
+        half_extents: [f32; 3],
+        y_radians: f32,
+    },
+}
+
+impl DtObstacle {
+    // The same obstacle moved by `offset`.
+    pub fn translated(&self, offset: &[f32; 3]) -> DtObstacle {
+        let add = |v: &[f32; 3]| [v[0] + offset[0], v[1] + offset[1], v[2] + offset[2]];
+        match *self {
+            DtObstacle::Cylinder { pos, radius, height } => DtObstacle::Cylinder {
+                pos: add(&pos),
+                radius,
+                height,
+            },
+            DtObstacle::Box { bmin, bmax } => DtObstacle::Box {
+                bmin: add(&bmin),
+                bmax: add(&bmax),
+            },
+            DtObstacle::OrientedBox {
+                center,
+                half_extents,
+                y_radians,
+            } => DtObstacle::OrientedBox {
+                center: add(&center),
+                half_extents,
+                y_radians,
+            },
+        }
+    }
+}
+
+// An obstacle of a `DtObstacleManager`. Unlike `DtObstacleRef`, the handle stays the same when the obstacle moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DtObstacleHandle {
+    index: u32,
+    generation: u32,
+}
+
+// A navigation mesh tile rebuilt by `DtObstacleManager::update()`.
+//
+// The tile cache replaces the tile the same way as `DtNavMesh::remove_tile()` then `DtNavMesh::add_tile()`:
+// the new tile gets a new salt, so the poly refs of the old tile are no longer valid and must be looked up
+// again, e.g. by `DtNavMeshQuery::find_nearest_poly_1()`. A `DtCrowd` does this by itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DtRebuiltTile {
+    pub tx: i32,
+    pub ty: i32,
+    pub tlayer: i32,
+    // Null if there was no tile before.
+    pub old_ref: DtTileRef,
+    // Null if nothing walkable is left.
+    pub new_ref: DtTileRef,
+}
+
+// The result of `DtObstacleManager::update()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DtObstacleUpdate {
+    pub rebuilt: Vec<DtRebuiltTile>,
+    // True if all obstacle changes are applied to the navigation mesh.
+    pub up_to_date: bool,
+}
+
+#[derive(Debug, Default)]
+struct ObstacleSlot {
+    generation: u32,
+    obstacle: Option<(DtObstacle, DtObstacleRef)>,
+}
+
+// Temporary obstacles carving a navigation mesh at runtime, e.g. doors, crates or vehicles.
+//
+// Obstacle changes are queued and applied by `update()`, which rebuilds at most `max_rebuilds()` tiles per call,
+// so the cost of a frame stays bounded.
+#[derive(Debug)]
+pub struct DtObstacleManager {
+    tile_cache: DtTileCache,
+    slots: Vec<ObstacleSlot>,
+    free_slots: Vec<u32>,
+    len: usize,
+    requests: usize,
+    // The tile cache obstacles added or removed, until their tiles are rebuilt.
+    changed: Vec<DtObstacleRef>,
+    max_rebuilds: usize,
+}
+
+impl DtObstacleManager {
+    // `tile_cache` must hold the layers of the navigation mesh passed to `update()`.
+    pub fn new(tile_cache: DtTileCache) -> DtObstacleManager {
+        DtObstacleManager {
+            tile_cache,
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            len: 0,
+            requests: 0,
+            changed: Vec::new(),
+            max_rebuilds: 1,
+        }
+    }
+
+    #[inline]
+    pub fn tile_cache(&self) -> &DtTileCache {
+        &self.tile_cache
+    }
+
+    #[inline]
+    pub fn into_tile_cache(self) -> DtTileCache {
+        self.tile_cache
+    }
+
+    // The maximum number of tiles rebuilt by an `update()`. Default is 1.
+    #[inline]
+    pub fn max_rebuilds(&self) -> usize {
+        self.max_rebuilds
+    }
+
+    #[inline]
+    pub fn set_max_rebuilds(&mut self, max_rebuilds: usize) {
+        self.max_rebuilds = max_rebuilds.max(1);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn get(&self, handle: DtObstacleHandle) -> Option<&DtObstacle> {
+        self.slot(handle).map(|(obstacle, _)| obstacle)
+    }
+
+    #[inline]
+    pub fn contains(&self, handle: DtObstacleHandle) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    // The current tile cache obstacle, which changes when the obstacle moves.
+    #[inline]
+    pub fn obstacle_ref(&self, handle: DtObstacleHandle) -> Option<DtObstacleRef> {
+        self.slot(handle).map(|(_, re)| *re)
+    }
+
+    // `DT_OBSTACLE_PROCESSING` until the obstacle is carved into the navigation mesh, then `DT_OBSTACLE_PROCESSED`.
+    pub fn state(&self, handle: DtObstacleHandle) -> Option<DtObstacleState> {
+        let re = self.obstacle_ref(handle)?;
+        self.tile_cache_obstacle(re).map(|ob| ob.state())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (DtObstacleHandle, &DtObstacle)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let (obstacle, _) = slot.obstacle.as_ref()?;
+            let handle = DtObstacleHandle {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            Some((handle, obstacle))
+        })
+    }
+
+    // Adds an obstacle, carved into the navigation mesh by the next `update()`s.
+    // Fails with `BufferTooSmall` if 64 changes are queued since `update()` was last up to date.
+    pub fn add(&mut self, obstacle: DtObstacle) -> RNResult<DtObstacleHandle> {
+        self.reserve_requests(1)?;
+        let re = self.add_to_tile_cache(&obstacle)?;
+        let index = match self.free_slots.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(ObstacleSlot::default());
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.obstacle = Some((obstacle, re));
+        self.len += 1;
+        Ok(DtObstacleHandle {
+            index,
+            generation: slot.generation,
+        })
+    }
+
+    // Removes an obstacle, the navigation mesh is restored by the next `update()`s.
+    pub fn remove(&mut self, handle: DtObstacleHandle) -> RNResult<DtObstacle> {
+        let (_, re) = *self.slot(handle).ok_or(RNError::InvalidParam)?;
+        self.reserve_requests(1)?;
+        self.tile_cache.remove_obstacle(re)?;
+        self.requests += 1;
+        self.changed.push(re);
+
+        let slot = &mut self.slots[handle.index as usize];
+        let (obstacle, _) = slot.obstacle.take().unwrap();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+        self.len -= 1;
+        Ok(obstacle)
+    }
+
+    // Moves or reshapes an obstacle, the handle stays valid. On error, the obstacle is unchanged.
+    pub fn move_obstacle(&mut self, handle: DtObstacleHandle, obstacle: DtObstacle) -> RNResult<()> {
+        let (_, old_re) = *self.slot(handle).ok_or(RNError::InvalidParam)?;
+        // The tile cache has no move, the obstacle is replaced. Both requests must fit in this update.
+        self.reserve_requests(2)?;
+        let re = self.add_to_tile_cache(&obstacle)?;
+        if let Err(err) = self.tile_cache.remove_obstacle(old_re) {
+            // Drop the new obstacle again, nothing else refers to it.
+            if self.tile_cache.remove_obstacle(re).is_ok() {
+                self.requests += 1;
+            }
+            return Err(err);
+        }
+        self.requests += 1;
+        self.changed.push(old_re);
+        self.slots[handle.index as usize].obstacle = Some((obstacle, re));
+        Ok(())
+    }
+
+    // Applies the queued obstacle changes, and rebuilds at most `max_rebuilds()` tiles into `nav_mesh`.
+    pub fn update(&mut self, dt: f32, nav_mesh: &mut DtNavMesh) -> RNResult<DtObstacleUpdate> {
+        let mut result = DtObstacleUpdate::default();
+        // Only the tiles of the changed obstacles can be rebuilt.
+        let mut tile_refs = self.changed_tile_refs(nav_mesh)?;
+        for _ in 0..self.max_rebuilds {
+            result.up_to_date = self.tile_cache.update(dt, nav_mesh)?;
+
+            // The tile cache does not tell which tile is rebuilt, it is the one whose ref changed.
+            for (tx, ty, tlayer, old_ref) in tile_refs.iter_mut() {
+                let new_ref = nav_mesh.get_tile_ref_at(*tx, *ty, *tlayer);
+                if *old_ref != new_ref {
+                    result.rebuilt.push(DtRebuiltTile {
+                        tx: *tx,
+                        ty: *ty,
+                        tlayer: *tlayer,
+                        old_ref: *old_ref,
+                        new_ref,
+                    });
+                    *old_ref = new_ref;
+                }
+            }
+
+            if result.up_to_date {
+                break;
+            }
+        }
+
+        if result.up_to_date {
+            self.requests = 0;
+            self.changed.clear();
+        } else {
+            // A processed obstacle may still have a queued removal, only the freed ones are done.
+            let tile_cache = &self.tile_cache;
+            self.changed.retain(|re| Self::live_obstacle(tile_cache, *re).is_some());
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    fn slot(&self, handle: DtObstacleHandle) -> Option<&(DtObstacle, DtObstacleRef)> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.obstacle.as_ref()
+    }
+
+    fn reserve_requests(&self, count: usize) -> RNResult<()> {
+        if self.requests + count > MAX_REQUESTS {
+            return Err(RNError::BufferTooSmall);
+        }
+        Ok(())
+    }
+
+    fn add_to_tile_cache(&mut self, obstacle: &DtObstacle) -> RNResult<DtObstacleRef> {
+        let re = match obstacle {
+            DtObstacle::Cylinder { pos, radius, height } => self.tile_cache.add_obstacle(pos, *radius, *height)?,
+            DtObstacle::Box { bmin, bmax } => self.tile_cache.add_box_obstacle(bmin, bmax)?,
+            DtObstacle::OrientedBox {
+                center,
+                half_extents,
+                y_radians,
+            } => self
+                .tile_cache
+                .add_oriented_box_obstacle(center, half_extents, *y_radians)?,
+        };
+        self.requests += 1;
+        self.changed.push(re);
+        Ok(re)
+    }
+
+    #[inline]
+    fn tile_cache_obstacle(&self, re: DtObstacleRef) -> Option<&DtTileCacheObstacle> {
+        Self::live_obstacle(&self.tile_cache, re)
+    }
+
+    // Same as `DtTileCache::get_obstacle_by_ref()`, without borrowing the tile cache mutably.
+    fn live_obstacle(tile_cache: &DtTileCache, re: DtObstacleRef) -> Option<&DtTileCacheObstacle> {
+        let ob = tile_cache.get_obstacle((re.0 & 0xffff) as i32)?;
+        if tile_cache.get_obstacle_ref(ob) != re {
+            return None;
+        }
+        Some(ob)
+    }
+
+    // The navigation mesh tile refs of the layers touched by the changed obstacles.
+    fn changed_tile_refs(&self, nav_mesh: &DtNavMesh) -> RNResult<Vec<(i32, i32, i32, DtTileRef)>> {
+        let mut tiles: Vec<DtCompressedTileRef> = Vec::new();
+        for re in &self.changed {
+            let Some(ob) = self.tile_cache_obstacle(*re) else {
+                continue;
+            };
+            // A new obstacle has no touched tiles until its request is processed, the tile cache queries
+            // them by the obstacle bounds.
+            let mut queried = [DtCompressedTileRef::default(); DT_MAX_TOUCHED_TILES];
+            let touched = match ob.touched() {
+                [] => {
+                    let (bmin, bmax) = self.tile_cache.get_obstacle_bounds(ob);
+                    let count = self.tile_cache.query_tiles(&bmin, &bmax, &mut queried)?;
+                    &queried[..count]
+                }
+                touched => touched,
+            };
+            for tile in touched {
+                if !tiles.contains(tile) {
+                    tiles.push(*tile);
+                }
+            }
+        }
+        Ok(tiles
+            .into_iter()
+            .filter_map(|re| self.tile_cache.get_tile_by_ref(re)?.header())
+            .map(|h| (h.tx, h.ty, h.tlayer, nav_mesh.get_tile_ref_at(h.tx, h.ty, h.tlayer)))
+            .collect())
+    }
+}
@@ -0,0 +1,37 @@
+#include "recastnavigation-rs/src/detour_tile_cache/compressor.rs.h"
+#include "recastnavigation-rs/src/detour_tile_cache/mesh_process.rs.h"
+
+int dtRustCompressor::maxCompressedSize(const int bufferSize)
+{
+	return dtCompressorMaxCompressedSize(*m_rs, bufferSize);
+}
+
+dtStatus dtRustCompressor::compress(const unsigned char* buffer, const int bufferSize,
+									unsigned char* compressed, const int maxCompressedSize, int* compressedSize)
+{
+	if (bufferSize < 0 || maxCompressedSize < 0)
+		return DT_FAILURE | DT_INVALID_PARAM;
+	return dtCompressorCompress(*m_rs,
+								rust::Slice<const uint8_t>(buffer, (size_t)bufferSize),
+								rust::Slice<uint8_t>(compressed, (size_t)maxCompressedSize),
+								*compressedSize);
+}
+
+dtStatus dtRustCompressor::decompress(const unsigned char* compressed, const int compressedSize,
+									  unsigned char* buffer, const int maxBufferSize, int* bufferSize)
+{
+	if (compressedSize < 0 || maxBufferSize < 0)
+		return DT_FAILURE | DT_INVALID_PARAM;
+	return dtCompressorDecompress(*m_rs,
+								  rust::Slice<const uint8_t>(compressed, (size_t)compressedSize),
+								  rust::Slice<uint8_t>(buffer, (size_t)maxBufferSize),
+								  *bufferSize);
+}
+
+void dtRustMeshProcess::process(dtNavMeshCreateParams* params, unsigned char* polyAreas, unsigned short* polyFlags)
+{
+	const size_t polyCount = params->polyCount > 0 ? (size_t)params->polyCount : 0;
+	dtMeshProcessProcess(*m_rs, *params,
+						 rust::Slice<uint8_t>(polyAreas, polyCount),
+						 rust::Slice<uint16_t>(polyFlags, polyCount));
+}
@@ -0,0 +1,134 @@
+#pragma once
+
+#include <memory>
+
+#include "DetourAlloc.h"
+#include "DetourMath.h"
+#include "DetourNavMesh.h"
+#include "DetourNavMeshBuilder.h"
+#include "DetourStatus.h"
+#include "DetourTileCache.h"
+#include "DetourTileCacheBuilder.h"
+
+#include "rust/cxx.h"
+#include "recastnavigation-rs/src/utils.h"
+
+static_assert(sizeof(dtObstacleRef) == sizeof(unsigned int), "dtObstacleRef size");
+static_assert(sizeof(dtCompressedTileRef) == sizeof(unsigned int), "dtCompressedTileRef size");
+static_assert(sizeof(dtTileCacheLayerHeader) == 56, "dtTileCacheLayerHeader size");
+static_assert(sizeof(dtTileCacheLayer) == SIZE_32_64(24, 48), "dtTileCacheLayer size");
+static_assert(sizeof(dtTileCacheParams) == 52, "dtTileCacheParams size");
+static_assert(sizeof(dtCompressedTile) == SIZE_32_64(32, 56), "dtCompressedTile size");
+static_assert(sizeof(dtTileCacheObstacle) == SIZE_32_64(108, 112), "dtTileCacheObstacle size");
+
+//
+// allocator, compressor & mesh process
+//
+
+struct DtCompressorState;
+
+// dtTileCacheCompressor forwarding to a Rust DtTileCacheCompressor.
+class dtRustCompressor : public dtTileCacheCompressor {
+public:
+    explicit dtRustCompressor(const DtCompressorState& rs) : m_rs(&rs) {}
+
+    int maxCompressedSize(const int bufferSize) override;
+    dtStatus compress(const unsigned char* buffer, const int bufferSize,
+                      unsigned char* compressed, const int maxCompressedSize, int* compressedSize) override;
+    dtStatus decompress(const unsigned char* compressed, const int compressedSize,
+                        unsigned char* buffer, const int maxBufferSize, int* bufferSize) override;
+
+private:
+    const DtCompressorState* m_rs;
+};
+
+inline std::unique_ptr<dtTileCacheCompressor> dttc_newCompressor(const DtCompressorState& rs) {
+    return std::make_unique<dtRustCompressor>(rs);
+}
+
+struct DtMeshProcessState;
+
+// dtTileCacheMeshProcess forwarding to a Rust DtTileCacheMeshProcess.
+class dtRustMeshProcess : public dtTileCacheMeshProcess {
+public:
+    explicit dtRustMeshProcess(DtMeshProcessState& rs) : m_rs(&rs) {}
+
+    void process(dtNavMeshCreateParams* params, unsigned char* polyAreas, unsigned short* polyFlags) override;
+
+private:
+    DtMeshProcessState* m_rs;
+};
+
+inline std::unique_ptr<dtTileCacheMeshProcess> dttc_newMeshProcess(DtMeshProcessState& rs) {
+    return std::make_unique<dtRustMeshProcess>(rs);
+}
+
+// The default allocator is stateless (dtAlloc/dtFree), one instance is shared by all tile caches.
+inline dtTileCacheAlloc* dttc_defaultAlloc() {
+    static dtTileCacheAlloc alloc;
+    return &alloc;
+}
+
+//
+// dtTileCacheBuilder
+//
+
+// dtBuildTileCacheLayer() copies the header, dtDecompressTileCacheLayer() only reads the data.
+inline dtStatus dttc_buildTileCacheLayer(dtTileCacheCompressor& comp, const dtTileCacheLayerHeader* header,
+                                         const uint8_t* heights, const uint8_t* areas, const uint8_t* cons,
+                                         uint8_t** outData, int* outDataSize) {
+    return dtBuildTileCacheLayer(&comp, const_cast<dtTileCacheLayerHeader*>(header),
+                                 heights, areas, cons, outData, outDataSize);
+}
+
+inline dtStatus dttc_decompressTileCacheLayer(dtTileCacheCompressor& comp, const uint8_t* compressed,
+                                             int compressedSize, dtTileCacheLayer** layerOut) {
+    return dtDecompressTileCacheLayer(dttc_defaultAlloc(), &comp,
+                                      const_cast<uint8_t*>(compressed), compressedSize, layerOut);
+}
+
+inline void dttc_freeTileCacheLayer(dtTileCacheLayer* layer) {
+    dtFreeTileCacheLayer(dttc_defaultAlloc(), layer);
+}
+
+inline dtStatus dttc_buildTileCacheRegions(dtTileCacheLayer& layer, int walkableClimb) {
+    return dtBuildTileCacheRegions(dttc_defaultAlloc(), layer, walkableClimb);
+}
+
+inline dtStatus dttc_markCylinderArea(dtTileCacheLayer& layer, const float* orig, float cs, float ch,
+                                      const float* pos, float radius, float height, uint8_t areaId) {
+    return dtMarkCylinderArea(layer, orig, cs, ch, pos, radius, height, areaId);
+}
+
+inline dtStatus dttc_markBoxArea(dtTileCacheLayer& layer, const float* orig, float cs, float ch,
+                                 const float* bmin, const float* bmax, uint8_t areaId) {
+    return dtMarkBoxArea(layer, orig, cs, ch, bmin, bmax, areaId);
+}
+
+// Same rotation as dtTileCache::addBoxObstacle(), with the deterministic dtMath trigonometry.
+inline dtStatus dttc_markOrientedBoxArea(dtTileCacheLayer& layer, const float* orig, float cs, float ch,
+                                         const float* center, const float* halfExtents, float yRadians,
+                                         uint8_t areaId) {
+    const float coshalf = dtMathCosf(0.5f * yRadians);
+    const float sinhalf = dtMathSinf(-0.5f * yRadians);
+    const float rotAux[2] = {coshalf * sinhalf, coshalf * coshalf - 0.5f};
+    return dtMarkBoxArea(layer, orig, cs, ch, center, halfExtents, rotAux, areaId);
+}
+
+//
+// dtTileCache
+//
+
+inline dtStatus dttc_init(dtTileCache& tc, const dtTileCacheParams* params, dtTileCacheCompressor* comp,
+                          dtTileCacheMeshProcess* proc) {
+    return tc.init(params, dttc_defaultAlloc(), comp, proc);
+}
+
+inline dtStatus dttc_addBoxObstacle(dtTileCache& tc, const float* bmin, const float* bmax, dtObstacleRef* result) {
+    return tc.addBoxObstacle(bmin, bmax, result);
+}
+
+inline dtStatus dttc_addOrientedBoxObstacle(dtTileCache& tc, const float* center, const float* halfExtents,
+                                            float yRadians, dtObstacleRef* result) {
+    return tc.addBoxObstacle(center, halfExtents, yRadians, result);
+}
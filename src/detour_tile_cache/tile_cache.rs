@@ -0,0 +1,641 @@
+use cxx::{type_id, ExternType, UniquePtr};
+use static_assertions::const_assert_eq;
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::{mem, ptr};
+
+use crate::detour::{DtBuf, DtNavMesh};
+use crate::detour_tile_cache::builder::DtTileCacheLayerHeader;
+use crate::detour_tile_cache::compressor::{self, DtCompressorState, DtTileCacheCompressor};
+use crate::detour_tile_cache::mesh_process::{self, DtMeshProcessState, DtTileCacheMeshProcess};
+use crate::error::{RNError, RNResult};
+
+pub const DT_MAX_TOUCHED_TILES: usize = 8;
+
+const DT_COMPRESSEDTILE_FREE_DATA: u8 = 1;
+
+#[allow(dead_code)]
+#[cxx::bridge]
+pub(crate) mod ffi {
+    #[repr(u32)]
+    enum ObstacleState {
+        DT_OBSTACLE_EMPTY,
+        DT_OBSTACLE_PROCESSING,
+        DT_OBSTACLE_PROCESSED,
+        DT_OBSTACLE_REMOVING,
+    }
+
+    #[repr(u32)]
+    enum ObstacleType {
+        DT_OBSTACLE_CYLINDER,
+        DT_OBSTACLE_BOX,
+        DT_OBSTACLE_ORIENTED_BOX,
+    }
+
+    unsafe extern "C++" {
+        include!("recastnavigation-rs/src/detour_tile_cache/tile-cache-ffi.h");
+
+        type dtStatus = crate::detour::base::DtStatus;
+        type dtNavMesh = crate::detour::mesh::ffi::dtNavMesh;
+        type dtTileCacheCompressor = crate::detour_tile_cache::compressor::ffi::dtTileCacheCompressor;
+        type dtTileCacheMeshProcess = crate::detour_tile_cache::mesh_process::ffi::dtTileCacheMeshProcess;
+
+        type ObstacleState;
+        type ObstacleType;
+
+        type dtObstacleRef = crate::detour_tile_cache::tile_cache::DtObstacleRef;
+        type dtCompressedTileRef = crate::detour_tile_cache::tile_cache::DtCompressedTileRef;
+        type dtTileCacheLayerHeader = crate::detour_tile_cache::builder::DtTileCacheLayerHeader;
+        type dtTileCacheParams = crate::detour_tile_cache::tile_cache::DtTileCacheParams;
+        type dtCompressedTile = crate::detour_tile_cache::tile_cache::DtCompressedTile;
+        type dtTileCacheObstacle = crate::detour_tile_cache::tile_cache::DtTileCacheObstacle;
+
+        type dtTileCache;
+        fn dtAllocTileCache() -> *mut dtTileCache;
+        unsafe fn dtFreeTileCache(tc: *mut dtTileCache);
+        unsafe fn dttc_init(
+            tc: Pin<&mut dtTileCache>,
+            params: *const dtTileCacheParams,
+            comp: *mut dtTileCacheCompressor,
+            proc: *mut dtTileCacheMeshProcess,
+        ) -> dtStatus;
+        fn getParams(self: &dtTileCache) -> *const dtTileCacheParams;
+        fn getTileCount(self: &dtTileCache) -> i32;
+        fn getTile(self: &dtTileCache, i: i32) -> *const dtCompressedTile;
+        fn getObstacleCount(self: &dtTileCache) -> i32;
+        fn getObstacle(self: &dtTileCache, i: i32) -> *const dtTileCacheObstacle;
+        fn getObstacleByRef(self: Pin<&mut dtTileCache>, re: dtObstacleRef) -> *const dtTileCacheObstacle;
+        unsafe fn getObstacleRef(self: &dtTileCache, obmin: *const dtTileCacheObstacle) -> dtObstacleRef;
+        unsafe fn getTilesAt(
+            self: &dtTileCache,
+            tx: i32,
+            ty: i32,
+            tiles: *mut dtCompressedTileRef,
+            maxTiles: i32,
+        ) -> i32;
+        fn getTileAt(self: Pin<&mut dtTileCache>, tx: i32, ty: i32, tlayer: i32) -> *mut dtCompressedTile;
+        unsafe fn getTileRef(self: &dtTileCache, tile: *const dtCompressedTile) -> dtCompressedTileRef;
+        fn getTileByRef(self: &dtTileCache, re: dtCompressedTileRef) -> *const dtCompressedTile;
+        unsafe fn addTile(
+            self: Pin<&mut dtTileCache>,
+            data: *mut u8,
+            dataSize: i32,
+            flags: u8,
+            result: *mut dtCompressedTileRef,
+        ) -> dtStatus;
+        unsafe fn removeTile(
+            self: Pin<&mut dtTileCache>,
+            re: dtCompressedTileRef,
+            data: *mut *mut u8,
+            dataSize: *mut i32,
+        ) -> dtStatus;
+        unsafe fn addObstacle(
+            self: Pin<&mut dtTileCache>,
+            pos: *const f32,
+            radius: f32,
+            height: f32,
+            result: *mut dtObstacleRef,
+        ) -> dtStatus;
+        unsafe fn dttc_addBoxObstacle(
+            tc: Pin<&mut dtTileCache>,
+            bmin: *const f32,
+            bmax: *const f32,
+            result: *mut dtObstacleRef,
+        ) -> dtStatus;
+        unsafe fn dttc_addOrientedBoxObstacle(
+            tc: Pin<&mut dtTileCache>,
+            center: *const f32,
+            halfExtents: *const f32,
+            yRadians: f32,
+            result: *mut dtObstacleRef,
+        ) -> dtStatus;
+        fn removeObstacle(self: Pin<&mut dtTileCache>, re: dtObstacleRef) -> dtStatus;
+        unsafe fn queryTiles(
+            self: &dtTileCache,
+            bmin: *const f32,
+            bmax: *const f32,
+            results: *mut dtCompressedTileRef,
+            resultCount: *mut i32,
+            maxResults: i32,
+        ) -> dtStatus;
+        unsafe fn update(
+            self: Pin<&mut dtTileCache>,
+            dt: f32,
+            navmesh: *mut dtNavMesh,
+            upToDate: *mut bool,
+        ) -> dtStatus;
+        unsafe fn buildNavMeshTilesAt(
+            self: Pin<&mut dtTileCache>,
+            tx: i32,
+            ty: i32,
+            navmesh: *mut dtNavMesh,
+        ) -> dtStatus;
+        unsafe fn buildNavMeshTile(
+            self: Pin<&mut dtTileCache>,
+            re: dtCompressedTileRef,
+            navmesh: *mut dtNavMesh,
+        ) -> dtStatus;
+        unsafe fn calcTightTileBounds(
+            self: &dtTileCache,
+            header: *const dtTileCacheLayerHeader,
+            bmin: *mut f32,
+            bmax: *mut f32,
+        );
+        unsafe fn getObstacleBounds(self: &dtTileCache, ob: *const dtTileCacheObstacle, bmin: *mut f32, bmax: *mut f32);
+    }
+}
+
+pub type DtObstacleState = ffi::ObstacleState;
+pub type DtObstacleType = ffi::ObstacleType;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DtObstacleRef(pub u32);
+
+unsafe impl ExternType for DtObstacleRef {
+    type Id = type_id!("dtObstacleRef");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl DtObstacleRef {
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DtCompressedTileRef(pub u32);
+
+unsafe impl ExternType for DtCompressedTileRef {
+    type Id = type_id!("dtCompressedTileRef");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl DtCompressedTileRef {
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+//
+// DtTileCacheParams
+//
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DtTileCacheParams {
+    pub orig: [f32; 3],
+    pub cs: f32,
+    pub ch: f32,
+    // The size of the tiles. [Units: vx]
+    pub width: i32,
+    pub height: i32,
+    pub walkable_height: f32,
+    pub walkable_radius: f32,
+    pub walkable_climb: f32,
+    pub max_simplification_error: f32,
+    pub max_tiles: i32,
+    pub max_obstacles: i32,
+}
+
+const_assert_eq!(mem::size_of::<DtTileCacheParams>(), 52);
+
+unsafe impl ExternType for DtTileCacheParams {
+    type Id = type_id!("dtTileCacheParams");
+    type Kind = cxx::kind::Trivial;
+}
+
+//
+// DtCompressedTile
+//
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct DtCompressedTile {
+    // Counter describing modifications to the tile.
+    pub salt: u32,
+    header: *mut DtTileCacheLayerHeader,
+    compressed: *mut u8,
+    compressed_size: i32,
+    data: *mut u8,
+    data_size: i32,
+    pub flags: u32,
+    next: *mut DtCompressedTile,
+}
+
+#[cfg(target_pointer_width = "64")]
+const_assert_eq!(mem::size_of::<DtCompressedTile>(), 56);
+
+#[cfg(target_pointer_width = "32")]
+const_assert_eq!(mem::size_of::<DtCompressedTile>(), 32);
+
+unsafe impl ExternType for DtCompressedTile {
+    type Id = type_id!("dtCompressedTile");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl DtCompressedTile {
+    // None if the tile slot is free.
+    #[inline]
+    pub fn header(&self) -> Option<&DtTileCacheLayerHeader> {
+        unsafe { self.header.as_ref() }
+    }
+
+    // The compressed grid of the layer, after the header.
+    #[inline]
+    pub fn compressed(&self) -> &[u8] {
+        if self.compressed.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.compressed, self.compressed_size as usize) }
+    }
+
+    // The whole data of the tile, as passed to `DtTileCache::add_tile()`.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        if self.data.is_null() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.data, self.data_size as usize) }
+    }
+}
+
+//
+// DtTileCacheObstacle
+//
+
+// The shape of an obstacle, see `DtTileCacheObstacle::shape()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DtObstacleShape {
+    Cylinder {
+        pos: [f32; 3],
+        radius: f32,
+        height: f32,
+    },
+    Box {
+        bmin: [f32; 3],
+        bmax: [f32; 3],
+    },
+    OrientedBox {
+        center: [f32; 3],
+        half_extents: [f32; 3],
+        rot_aux: [f32; 2],
+    },
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct DtTileCacheObstacle {
+    // The union of dtObstacleCylinder, dtObstacleBox and dtObstacleOrientedBox.
+    shape: [f32; 8],
+    touched: [DtCompressedTileRef; DT_MAX_TOUCHED_TILES],
+    pending: [DtCompressedTileRef; DT_MAX_TOUCHED_TILES],
+    pub salt: u16,
+    ty: u8,
+    state: u8,
+    ntouched: u8,
+    npending: u8,
+    next: *mut DtTileCacheObstacle,
+}
+
+#[cfg(target_pointer_width = "64")]
+const_assert_eq!(mem::size_of::<DtTileCacheObstacle>(), 112);
+
+#[cfg(target_pointer_width = "32")]
+const_assert_eq!(mem::size_of::<DtTileCacheObstacle>(), 108);
+
+unsafe impl ExternType for DtTileCacheObstacle {
+    type Id = type_id!("dtTileCacheObstacle");
+    type Kind = cxx::kind::Trivial;
+}
+
+impl DtTileCacheObstacle {
+    #[inline]
+    pub fn obstacle_type(&self) -> DtObstacleType {
+        DtObstacleType { repr: self.ty as u32 }
+    }
+
+    #[inline]
+    pub fn state(&self) -> DtObstacleState {
+        DtObstacleState {
+            repr: self.state as u32,
+        }
+    }
+
+    pub fn shape(&self) -> DtObstacleShape {
+        let s = &self.shape;
+        match self.obstacle_type() {
+            DtObstacleType::DT_OBSTACLE_BOX => DtObstacleShape::Box {
+                bmin: [s[0], s[1], s[2]],
+                bmax: [s[3], s[4], s[5]],
+            },
+            DtObstacleType::DT_OBSTACLE_ORIENTED_BOX => DtObstacleShape::OrientedBox {
+                center: [s[0], s[1], s[2]],
+                half_extents: [s[3], s[4], s[5]],
+                rot_aux: [s[6], s[7]],
+            },
+            _ => DtObstacleShape::Cylinder {
+                pos: [s[0], s[1], s[2]],
+                radius: s[3],
+                height: s[4],
+            },
+        }
+    }
+
+    // The tiles overlapped by the obstacle.
+    #[inline]
+    pub fn touched(&self) -> &[DtCompressedTileRef] {
+        &self.touched[..(self.ntouched as usize).min(DT_MAX_TOUCHED_TILES)]
+    }
+
+    // The tiles waiting to be rebuilt for the obstacle.
+    #[inline]
+    pub fn pending(&self) -> &[DtCompressedTileRef] {
+        &self.pending[..(self.npending as usize).min(DT_MAX_TOUCHED_TILES)]
+    }
+}
+
+//
+// DtTileCache
+//
+
+// Stores the compressed layers of a tiled navigation mesh, and rebuilds the navigation mesh tiles
+// when temporary obstacles are added or removed.
+pub struct DtTileCache {
+    ptr: *mut ffi::dtTileCache,
+    cxx_compressor: UniquePtr<ffi::dtTileCacheCompressor>,
+    // Points to `compressor`, only used by the C++ compressor.
+    #[allow(dead_code)]
+    compressor_state: Box<DtCompressorState<'static>>,
+    compressor: Box<dyn DtTileCacheCompressor>,
+    cxx_mesh_process: UniquePtr<ffi::dtTileCacheMeshProcess>,
+    // Only used by the C++ mesh process.
+    #[allow(dead_code)]
+    mesh_process: Box<DtMeshProcessState>,
+}
+
+impl Debug for DtTileCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return f.debug_tuple("DtTileCache").field(&self.ptr).finish();
+    }
+}
+
+impl Drop for DtTileCache {
+    fn drop(&mut self) {
+        // The C++ objects must go first, they point to the Rust compressor and mesh process.
+        unsafe { ffi::dtFreeTileCache(self.ptr) };
+        self.ptr = ptr::null_mut();
+        self.cxx_compressor = UniquePtr::null();
+        self.cxx_mesh_process = UniquePtr::null();
+    }
+}
+
+unsafe impl Send for DtTileCache {}
+
+impl DtTileCache {
+    #[inline]
+    fn inner(&self) -> &ffi::dtTileCache {
+        unsafe { &*self.ptr }
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> Pin<&mut ffi::dtTileCache> {
+        unsafe { Pin::new_unchecked(&mut *self.ptr) }
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const ffi::dtTileCache {
+        self.ptr
+    }
+
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut ffi::dtTileCache {
+        self.ptr
+    }
+
+    // Creates a tile cache, its layers must be built with the same compressor, see `compressor()`.
+    // `mesh_process` sets the poly flags of the built tiles, e.g. `DtDefaultMeshProcess`.
+    pub fn with_params(
+        params: &DtTileCacheParams,
+        compressor: Box<dyn DtTileCacheCompressor>,
+        mesh_process: Box<dyn DtTileCacheMeshProcess>,
+    ) -> RNResult<DtTileCache> {
+        // The state points to the heap allocation of `compressor`, it does not move with the box.
+        let compressor_state = Box::new(DtCompressorState(&*compressor as *const _));
+        let mut mesh_process = Box::new(DtMeshProcessState(mesh_process));
+        let mut tc = DtTileCache {
+            ptr: ffi::dtAllocTileCache(),
+            cxx_compressor: unsafe { compressor::new_cxx_compressor(&compressor_state) },
+            compressor_state,
+            compressor,
+            cxx_mesh_process: unsafe { mesh_process::new_cxx_mesh_process(&mut mesh_process) },
+            mesh_process,
+        };
+        if tc.ptr.is_null() {
+            return Err(RNError::OutOfMemory);
+        }
+        let comp = tc.cxx_compressor.as_mut_ptr();
+        let proc = tc.cxx_mesh_process.as_mut_ptr();
+        unsafe { ffi::dttc_init(tc.inner_mut(), params, comp, proc) }.to_result()?;
+        Ok(tc)
+    }
+
+    #[inline]
+    pub fn compressor(&self) -> &dyn DtTileCacheCompressor {
+        &*self.compressor
+    }
+
+    #[inline]
+    pub fn params(&self) -> &DtTileCacheParams {
+        unsafe { &*self.inner().getParams() }
+    }
+
+    // The number of tile slots, see `get_tile()`.
+    #[inline]
+    pub fn get_tile_count(&self) -> i32 {
+        self.inner().getTileCount()
+    }
+
+    #[inline]
+    pub fn get_tile(&self, i: i32) -> Option<&DtCompressedTile> {
+        if i < 0 || i >= self.get_tile_count() {
+            return None;
+        }
+        unsafe { self.inner().getTile(i).as_ref() }
+    }
+
+    // The number of obstacle slots, see `get_obstacle()`.
+    #[inline]
+    pub fn get_obstacle_count(&self) -> i32 {
+        self.inner().getObstacleCount()
+    }
+
+    #[inline]
+    pub fn get_obstacle(&self, i: i32) -> Option<&DtTileCacheObstacle> {
+        if i < 0 || i >= self.get_obstacle_count() {
+            return None;
+        }
+        unsafe { self.inner().getObstacle(i).as_ref() }
+    }
+
+    #[inline]
+    pub fn get_obstacle_by_ref(&mut self, re: DtObstacleRef) -> Option<&DtTileCacheObstacle> {
+        unsafe { self.inner_mut().getObstacleByRef(re).as_ref() }
+    }
+
+    #[inline]
+    pub fn get_obstacle_ref(&self, ob: &DtTileCacheObstacle) -> DtObstacleRef {
+        unsafe { self.inner().getObstacleRef(ob) }
+    }
+
+    // Gets the refs of the layers at a tile location. Returns the number of refs written to `tiles`.
+    #[inline]
+    pub fn get_tiles_at(&self, tx: i32, ty: i32, tiles: &mut [DtCompressedTileRef]) -> usize {
+        let count = unsafe { self.inner().getTilesAt(tx, ty, tiles.as_mut_ptr(), tiles.len() as i32) };
+        count as usize
+    }
+
+    #[inline]
+    pub fn get_tile_at(&mut self, tx: i32, ty: i32, tlayer: i32) -> Option<&DtCompressedTile> {
+        unsafe { self.inner_mut().getTileAt(tx, ty, tlayer).as_ref() }
+    }
+
+    #[inline]
+    pub fn get_tile_ref(&self, tile: &DtCompressedTile) -> DtCompressedTileRef {
+        unsafe { self.inner().getTileRef(tile) }
+    }
+
+    #[inline]
+    pub fn get_tile_by_ref(&self, re: DtCompressedTileRef) -> Option<&DtCompressedTile> {
+        unsafe { self.inner().getTileByRef(re).as_ref() }
+    }
+
+    // Adds a layer built by `dt_build_tile_cache_layer()`. The tile cache takes the ownership of the data.
+    pub fn add_tile(&mut self, buf: DtBuf) -> RNResult<DtCompressedTileRef> {
+        // dtTileCache::addTile() reads the header before checking the size.
+        if buf.len() < mem::size_of::<DtTileCacheLayerHeader>() || buf.len() > i32::MAX as usize {
+            return Err(RNError::InvalidParam);
+        }
+        let mut re = DtCompressedTileRef::default();
+        unsafe {
+            self.inner_mut()
+                .addTile(buf.data, buf.len() as i32, DT_COMPRESSEDTILE_FREE_DATA, &mut re)
+        }
+        .to_result()?;
+        mem::forget(buf);
+        Ok(re)
+    }
+
+    // Removes a layer and frees its data. The navigation mesh tile built from it is not removed.
+    #[inline]
+    pub fn remove_tile(&mut self, re: DtCompressedTileRef) -> RNResult<()> {
+        unsafe { self.inner_mut().removeTile(re, ptr::null_mut(), ptr::null_mut()) }.to_result()
+    }
+
+    // Adds a vertical cylinder obstacle. The obstacle takes effect on the next `update()`.
+    #[inline]
+    pub fn add_obstacle(&mut self, pos: &[f32; 3], radius: f32, height: f32) -> RNResult<DtObstacleRef> {
+        let mut re = DtObstacleRef::default();
+        unsafe { self.inner_mut().addObstacle(pos.as_ptr(), radius, height, &mut re) }.to_result()?;
+        Ok(re)
+    }
+
+    // Adds an axis-aligned box obstacle.
+    #[inline]
+    pub fn add_box_obstacle(&mut self, bmin: &[f32; 3], bmax: &[f32; 3]) -> RNResult<DtObstacleRef> {
+        let mut re = DtObstacleRef::default();
+        unsafe { ffi::dttc_addBoxObstacle(self.inner_mut(), bmin.as_ptr(), bmax.as_ptr(), &mut re) }.to_result()?;
+        Ok(re)
+    }
+
+    // Adds a box obstacle rotated by `y_radians` around the y-axis.
+    #[inline]
+    pub fn add_oriented_box_obstacle(
+        &mut self,
+        center: &[f32; 3],
+        half_extents: &[f32; 3],
+        y_radians: f32,
+    ) -> RNResult<DtObstacleRef> {
+        let mut re = DtObstacleRef::default();
+        unsafe {
+            ffi::dttc_addOrientedBoxObstacle(
+                self.inner_mut(),
+                center.as_ptr(),
+                half_extents.as_ptr(),
+                y_radians,
+                &mut re,
+            )
+        }
+        .to_result()?;
+        Ok(re)
+    }
+
+    // Removes an obstacle. The obstacle is removed from the navigation mesh on the next `update()`.
+    #[inline]
+    pub fn remove_obstacle(&mut self, re: DtObstacleRef) -> RNResult<()> {
+        self.inner_mut().removeObstacle(re).to_result()
+    }
+
+    // Gets the refs of the layers overlapping the bounds. Returns the number of refs written to `results`.
+    #[inline]
+    pub fn query_tiles(
+        &self,
+        bmin: &[f32; 3],
+        bmax: &[f32; 3],
+        results: &mut [DtCompressedTileRef],
+    ) -> RNResult<usize> {
+        let mut count = 0;
+        unsafe {
+            self.inner().queryTiles(
+                bmin.as_ptr(),
+                bmax.as_ptr(),
+                results.as_mut_ptr(),
+                &mut count,
+                results.len() as i32,
+            )
+        }
+        .to_result()?;
+        Ok(count as usize)
+    }
+
+    // Processes the pending obstacle requests and rebuilds at most one touched tile into `nav_mesh`.
+    // Returns true if all requests are processed and all tiles are up to date.
+    #[inline]
+    pub fn update(&mut self, dt: f32, nav_mesh: &mut DtNavMesh) -> RNResult<bool> {
+        let mut up_to_date = false;
+        unsafe { self.inner_mut().update(dt, nav_mesh.as_mut_ptr(), &mut up_to_date) }.to_result()?;
+        Ok(up_to_date)
+    }
+
+    // Builds the navigation mesh tiles of all layers at a tile location.
+    #[inline]
+    pub fn build_nav_mesh_tiles_at(&mut self, tx: i32, ty: i32, nav_mesh: &mut DtNavMesh) -> RNResult<()> {
+        unsafe { self.inner_mut().buildNavMeshTilesAt(tx, ty, nav_mesh.as_mut_ptr()) }.to_result()
+    }
+
+    #[inline]
+    pub fn build_nav_mesh_tile(&mut self, re: DtCompressedTileRef, nav_mesh: &mut DtNavMesh) -> RNResult<()> {
+        unsafe { self.inner_mut().buildNavMeshTile(re, nav_mesh.as_mut_ptr()) }.to_result()
+    }
+
+    // The bounds of the usable sub-region of a layer.
+    #[inline]
+    pub fn calc_tight_tile_bounds(&self, header: &DtTileCacheLayerHeader) -> ([f32; 3], [f32; 3]) {
+        let mut bmin = [0.0; 3];
+        let mut bmax = [0.0; 3];
+        unsafe {
+            self.inner()
+                .calcTightTileBounds(header, bmin.as_mut_ptr(), bmax.as_mut_ptr())
+        };
+        (bmin, bmax)
+    }
+
+    #[inline]
+    pub fn get_obstacle_bounds(&self, ob: &DtTileCacheObstacle) -> ([f32; 3], [f32; 3]) {
+        let mut bmin = [0.0; 3];
+        let mut bmax = [0.0; 3];
+        unsafe { self.inner().getObstacleBounds(ob, bmin.as_mut_ptr(), bmax.as_mut_ptr()) };
+        (bmin, bmax)
+    }
+}
@@ -22,6 +22,40 @@ pub enum RNError {
     PartialResult,
     #[error("A tile has already been assigned to the given x,y coordinate")]
     AlreadyOccupied,
+    #[error("Navigation mesh build failed at stage {0:?}.")]
+    BuildFailed(RcBuildStage),
+    #[error("Invalid build settings: {0}.")]
+    InvalidSettings(&'static str),
 }
 
 pub type RNResult<T> = Result<T, RNError>;
+
+// Errors of the Rust OBJ loader `RcObjMesh`.
+#[derive(Error, Debug)]
+pub enum RcObjError {
+    #[error("Failed to read OBJ file: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("OBJ parse error at line {line}: {msg}.")]
+    Parse { line: usize, msg: String },
+    #[error("OBJ file has no triangles.")]
+    Empty,
+}
+
+// The stages of the recast/detour build pipeline, used to report where a build failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RcBuildStage {
+    Input,
+    Heightfield,
+    Rasterize,
+    CompactHeightfield,
+    ErodeArea,
+    HeightfieldLayers,
+    DistanceField,
+    Regions,
+    Contours,
+    PolyMesh,
+    PolyMeshDetail,
+    NavMeshData,
+    NavMesh,
+    TileCacheLayer,
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::detour::{
+    DtBVNode, DtBuf, DtLink, DtMeshHeader, DtOffMeshConnection, DtPoly, DtPolyDetail, DT_NAVMESH_MAGIC,
+    DT_NAVMESH_VERSION,
+};
+
+const CACHE_MAGIC: [u8; 4] = *b"RNTB";
+const CACHE_VERSION: u32 = 2;
+const CACHE_EXT: &str = "tile";
+// Magic, version, hash, payload length and payload checksum.
+const HEADER_SIZE: usize = 4 + 4 + 8 + 4 + 8;
+
+// A directory of tile data keyed by the content hash of their inputs, see `TileMeshBuilder::tile_hash()`.
+//
+// The builds are deterministic, so a tile whose inputs have the same hash as in a previous run can be
+// loaded instead of rebuilt. Each entry is a file named by the hash, written to a temporary file and
+// renamed, so an interrupted run never leaves a truncated entry behind.
+#[derive(Debug, Clone)]
+pub struct TileBuildCache {
+    dir: PathBuf,
+}
+
+impl TileBuildCache {
+    // Opens the cache directory, creating it if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<TileBuildCache> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(TileBuildCache {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // The file of an entry.
+    pub fn path(&self, hash: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.{}", hash, CACHE_EXT))
+    }
+
+    // Loads the tile data of an entry. An empty buffer is a tile with nothing walkable.
+    // Returns `None` if there is no entry, or if the entry is not a valid cache file: the payload checksum
+    // must match, and the payload must be a tile whose size matches the counts of its `DtMeshHeader`,
+    // because `DtNavMesh::add_tile()` trusts them.
+    pub fn load(&self, hash: u64) -> io::Result<Option<DtBuf>> {
+        let bytes = match fs::read(self.path(hash)) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if bytes.len() < HEADER_SIZE
+            || bytes[0..4] != CACHE_MAGIC
+            || read_u32(&bytes[4..8]) != CACHE_VERSION
+            || read_u64(&bytes[8..16]) != hash
+            || read_u32(&bytes[16..20]) as usize != bytes.len() - HEADER_SIZE
+            || read_u64(&bytes[20..28]) != checksum(&bytes[HEADER_SIZE..])
+            || !is_tile_data(&bytes[HEADER_SIZE..])
+        {
+            return Ok(None);
+        }
+        let data = DtBuf::from_slice(&bytes[HEADER_SIZE..]).map_err(io::Error::other)?;
+        Ok(Some(data))
+    }
+
+    // Stores the tile data of an entry, replacing the previous one. Use an empty slice for a tile with
+    // nothing walkable.
+    pub fn store(&self, hash: u64, data: &[u8]) -> io::Result<()> {
+        if data.len() > u32::MAX as usize {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "tile data too large"));
+        }
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + data.len());
+        bytes.extend_from_slice(&CACHE_MAGIC);
+        bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&hash.to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&checksum(data).to_le_bytes());
+        bytes.extend_from_slice(data);
+
+        let path = self.path(hash);
+        let tmp_path = path.with_extension(format!("{}.{}", CACHE_EXT, std::process::id()));
+        fs::write(&tmp_path, &bytes)?;
+        fs::rename(&tmp_path, &path).map_err(|err| {
+            let _ = fs::remove_file(&tmp_path);
+            err
+        })
+    }
+
+    #[inline]
+    pub fn contains(&self, hash: u64) -> bool {
+        self.path(hash).is_file()
+    }
+
+    // Removes the entries not in `keep`, e.g. the hashes of the last build. Returns the number of removed entries.
+    pub fn prune(&self, keep: &[u64]) -> io::Result<usize> {
+        let keep: HashSet<u64> = keep.iter().copied().collect();
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(CACHE_EXT) {
+                continue;
+            }
+            let hash = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| u64::from_str_radix(stem, 16).ok());
+            if let Some(hash) = hash {
+                if !keep.contains(&hash) {
+                    fs::remove_file(&path)?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+// The cache usage of `TileMeshBuilder::build_cached()`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TileCacheStats {
+    // Tiles loaded from the cache.
+    pub hits: usize,
+    // Tiles built and stored into the cache.
+    pub misses: usize,
+    // Tiles built but not stored, because writing the entry failed.
+    pub store_failures: usize,
+    // The hashes of all tiles, in build order. Pass them to `TileBuildCache::prune()` to drop stale entries.
+    pub hashes: Vec<u64>,
+}
+
+// 64-bit FNV-1a. Unlike `DefaultHasher`, the result is stable across runs, platforms and Rust versions.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new() -> StableHasher {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.bytes(&[v]);
+    }
+
+    #[inline]
+    pub(crate) fn u16(&mut self, v: u16) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    #[inline]
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    #[inline]
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    #[inline]
+    pub(crate) fn i32(&mut self, v: i32) {
+        self.bytes(&v.to_le_bytes());
+    }
+
+    #[inline]
+    pub(crate) fn f32(&mut self, v: f32) {
+        self.u32(v.to_bits());
+    }
+
+    #[inline]
+    pub(crate) fn vec3(&mut self, v: &[f32; 3]) {
+        v.iter().for_each(|c| self.f32(*c));
+    }
+
+    #[inline]
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn checksum(data: &[u8]) -> u64 {
+    let mut h = StableHasher::new();
+    h.bytes(data);
+    h.finish()
+}
+
+// Empty, or a tile laid out as by `dt_create_nav_mesh_data()`.
+fn is_tile_data(data: &[u8]) -> bool {
+    if data.is_empty() {
+        return true;
+    }
+    if data.len() < mem::size_of::<DtMeshHeader>() {
+        return false;
+    }
+    let header = unsafe { (data.as_ptr() as *const DtMeshHeader).read_unaligned() };
+    if header.magic != DT_NAVMESH_MAGIC as i32 || header.version != DT_NAVMESH_VERSION as i32 {
+        return false;
+    }
+    // All element sizes are multiples of 4, so there is no padding between the arrays.
+    let sizes = [
+        (header.vert_count, mem::size_of::<[f32; 3]>()),
+        (header.poly_count, mem::size_of::<DtPoly>()),
+        (header.max_link_count, mem::size_of::<DtLink>()),
+        (header.detail_mesh_count, mem::size_of::<DtPolyDetail>()),
+        (header.detail_vert_count, mem::size_of::<[f32; 3]>()),
+        (header.detail_tri_count, mem::size_of::<[u8; 4]>()),
+        (header.bv_node_count, mem::size_of::<DtBVNode>()),
+        (header.off_mesh_con_count, mem::size_of::<DtOffMeshConnection>()),
+    ];
+    let mut size = mem::size_of::<DtMeshHeader>();
+    for (count, elem_size) in sizes {
+        let Ok(count) = usize::try_from(count) else {
+            return false;
+        };
+        match count.checked_mul(elem_size).and_then(|n| size.checked_add(n)) {
+            Some(n) => size = n,
+            None => return false,
+        }
+    }
+    size == data.len()
+}
+
+#[inline]
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes.try_into().unwrap())
+}
+
+#[inline]
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes.try_into().unwrap())
+}
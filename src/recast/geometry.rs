@@ -0,0 +1,94 @@
+use crate::demo::{RcMeshLoaderObj, RcObjMesh};
+use crate::recast::recast::rc_calc_bounds;
+
+// Triangle soup consumed by the navigation mesh builders and `rc_create_chunky_tri_mesh()`.
+//
+// The builders borrow the slices, so geometry owned by the caller (scene graph, physics colliders,
+// procedural generators, ...) can be used without copying.
+pub trait InputGeometry {
+    fn verts(&self) -> &[[f32; 3]];
+
+    fn tris(&self) -> &[[i32; 3]];
+
+    // Per-triangle area ids. If None, triangles are marked walkable by the slope.
+    fn tri_areas(&self) -> Option<&[u8]> {
+        None
+    }
+
+    // The AABB of the vertices, override it if the bounds are already known.
+    fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        rc_calc_bounds(self.verts())
+    }
+}
+
+// Borrowed vertices, triangles and optional per-triangle area ids.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RcInputMesh<'t> {
+    pub verts: &'t [[f32; 3]],
+    pub tris: &'t [[i32; 3]],
+    pub tri_areas: Option<&'t [u8]>,
+}
+
+impl<'t> RcInputMesh<'t> {
+    #[inline]
+    pub fn new(verts: &'t [[f32; 3]], tris: &'t [[i32; 3]]) -> RcInputMesh<'t> {
+        RcInputMesh {
+            verts,
+            tris,
+            tri_areas: None,
+        }
+    }
+}
+
+impl InputGeometry for RcInputMesh<'_> {
+    #[inline]
+    fn verts(&self) -> &[[f32; 3]] {
+        self.verts
+    }
+
+    #[inline]
+    fn tris(&self) -> &[[i32; 3]] {
+        self.tris
+    }
+
+    #[inline]
+    fn tri_areas(&self) -> Option<&[u8]> {
+        self.tri_areas
+    }
+}
+
+impl InputGeometry for (&[[f32; 3]], &[[i32; 3]]) {
+    #[inline]
+    fn verts(&self) -> &[[f32; 3]] {
+        self.0
+    }
+
+    #[inline]
+    fn tris(&self) -> &[[i32; 3]] {
+        self.1
+    }
+}
+
+impl InputGeometry for RcMeshLoaderObj {
+    #[inline]
+    fn verts(&self) -> &[[f32; 3]] {
+        self.get_verts()
+    }
+
+    #[inline]
+    fn tris(&self) -> &[[i32; 3]] {
+        self.get_tris()
+    }
+}
+
+impl InputGeometry for RcObjMesh {
+    #[inline]
+    fn verts(&self) -> &[[f32; 3]] {
+        self.get_verts()
+    }
+
+    #[inline]
+    fn tris(&self) -> &[[i32; 3]] {
+        self.get_tris()
+    }
+}
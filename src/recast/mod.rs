@@ -1,3 +1,25 @@
+pub(crate) mod build_cache;
+pub(crate) mod geometry;
+pub(crate) mod pipeline;
+pub(crate) mod rasterize;
 pub(crate) mod recast;
+pub(crate) mod regions;
+pub(crate) mod settings;
+pub(crate) mod snapshot;
+pub(crate) mod solo_mesh;
+pub(crate) mod tile_mesh;
+pub(crate) mod validate;
+pub(crate) mod volumes;
 
+pub use build_cache::*;
+pub use geometry::*;
+pub use pipeline::RcPolyFlagsFn;
+pub use rasterize::*;
 pub use recast::*;
+pub use regions::*;
+pub use settings::*;
+pub use snapshot::*;
+pub use solo_mesh::*;
+pub use tile_mesh::*;
+pub use validate::*;
+pub use volumes::*;
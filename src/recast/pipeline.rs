@@ -0,0 +1,240 @@
+use crate::detour::off_mesh::OffMeshColumns;
+use crate::detour::{dt_create_nav_mesh_data, DtBuf, DtNavMeshCreateParams};
+use crate::error::{RNError, RNResult, RcBuildStage};
+use crate::recast::geometry::InputGeometry;
+use crate::recast::recast::*;
+use crate::recast::volumes::ConvexVolumeSet;
+
+// Updates poly areas and flags of a freshly built poly mesh, before it is converted to detour data.
+// Receives `RcPolyMesh::areas_mut()` and `RcPolyMesh::flags_mut()`.
+// Must be `Sync`, since tiles can be built on several threads.
+pub type RcPolyFlagsFn = dyn Fn(&mut [u8], &mut [u16]) + Sync;
+
+#[inline]
+pub(crate) fn at_stage<T>(res: RNResult<T>, stage: RcBuildStage) -> RNResult<T> {
+    res.map_err(|_| RNError::BuildFailed(stage))
+}
+
+// Checks the input geometry of a builder. The C++ side reads the vertices of the triangles unchecked.
+pub(crate) fn check_geometry<G: InputGeometry + ?Sized>(geometry: &G) -> RNResult<()> {
+    let verts = geometry.verts();
+    let tris = geometry.tris();
+    if verts.is_empty() || tris.is_empty() {
+        return Err(RNError::BuildFailed(RcBuildStage::Input));
+    }
+    if tris.iter().flatten().any(|v| *v < 0 || *v as usize >= verts.len()) {
+        return Err(RNError::BuildFailed(RcBuildStage::Input));
+    }
+    if matches!(geometry.tri_areas(), Some(areas) if areas.len() < tris.len()) {
+        return Err(RNError::BuildFailed(RcBuildStage::Input));
+    }
+    Ok(())
+}
+
+// Marks walkable triangles (unless areas are given) and rasterizes them into the heightfield.
+pub(crate) fn rasterize_triangles(
+    ctx: &mut RcContext,
+    cfg: &RcConfig,
+    solid: &mut RcHeightfield,
+    verts: &[[f32; 3]],
+    tris: &[[i32; 3]],
+    tri_areas: Option<&[u8]>,
+    scratch: &mut Vec<u8>,
+) -> RNResult<()> {
+    let tri_areas = match tri_areas {
+        Some(tri_areas) => {
+            if tri_areas.len() < tris.len() {
+                return Err(RNError::BuildFailed(RcBuildStage::Input));
+            }
+            tri_areas
+        }
+        None => {
+            scratch.clear();
+            scratch.resize(tris.len(), RC_NULL_AREA);
+            at_stage(
+                rc_mark_walkable_triangles(ctx, cfg.walkable_slope_angle, verts, tris, scratch),
+                RcBuildStage::Rasterize,
+            )?;
+            scratch
+        }
+    };
+
+    let ok = at_stage(
+        rc_rasterize_triangles_1(ctx, verts, tris, tri_areas, solid, cfg.walkable_climb),
+        RcBuildStage::Rasterize,
+    )?;
+    if !ok {
+        return Err(RNError::BuildFailed(RcBuildStage::Rasterize));
+    }
+    Ok(())
+}
+
+// Runs the pipeline from a rasterized heightfield to the poly mesh and detail mesh.
+// Returns `None` if no contour was generated, i.e. there is nothing walkable.
+pub(crate) fn build_poly_mesh(
+    ctx: &mut RcContext,
+    cfg: &RcConfig,
+    partition: RcPartitionType,
+    convex_volumes: Option<&ConvexVolumeSet>,
+    solid: &mut RcHeightfield,
+) -> RNResult<Option<(RcPolyMesh, RcPolyMeshDetail)>> {
+    // Partition walkable surface to simple regions.
+    let mut chf = build_compact_heightfield(ctx, cfg, convex_volumes, solid)?;
+    if partition == RcPartitionType::Watershed {
+        at_stage(rc_build_distance_field(ctx, &mut chf), RcBuildStage::DistanceField)?;
+    }
+    let res = match partition {
+        RcPartitionType::Watershed => rc_build_regions(
+            ctx,
+            &mut chf,
+            cfg.border_size,
+            cfg.min_region_area,
+            cfg.merge_region_area,
+        ),
+        RcPartitionType::Monotone => rc_build_regions_monotone(
+            ctx,
+            &mut chf,
+            cfg.border_size,
+            cfg.min_region_area,
+            cfg.merge_region_area,
+        ),
+        RcPartitionType::Layers => rc_build_layer_regions(ctx, &mut chf, cfg.border_size, cfg.min_region_area),
+    };
+    at_stage(res, RcBuildStage::Regions)?;
+
+    // Trace and simplify region contours.
+    let mut cset = RcContourSet::new();
+    at_stage(
+        rc_build_contours(
+            ctx,
+            &chf,
+            cfg.max_simplification_error,
+            cfg.max_edge_len,
+            &mut cset,
+            RcBuildContoursFlags::RC_CONTOUR_TESS_WALL_EDGES,
+        ),
+        RcBuildStage::Contours,
+    )?;
+    if cset.conts().is_empty() {
+        return Ok(None);
+    }
+
+    // Build polygons mesh from contours.
+    let mut pmesh = RcPolyMesh::new();
+    at_stage(
+        rc_build_poly_mesh(ctx, &cset, cfg.max_verts_per_poly, &mut pmesh),
+        RcBuildStage::PolyMesh,
+    )?;
+
+    // Create detail mesh which allows to access approximate height on each polygon.
+    let mut dmesh = RcPolyMeshDetail::new();
+    at_stage(
+        rc_build_poly_mesh_detail(
+            ctx,
+            &pmesh,
+            &chf,
+            cfg.detail_sample_dist,
+            cfg.detail_sample_max_error,
+            &mut dmesh,
+        ),
+        RcBuildStage::PolyMeshDetail,
+    )?;
+
+    Ok(Some((pmesh, dmesh)))
+}
+
+// Filters the walkable surfaces of a rasterized heightfield, then builds the eroded compact heightfield
+// and marks the convex volumes into it.
+pub(crate) fn build_compact_heightfield(
+    ctx: &mut RcContext,
+    cfg: &RcConfig,
+    convex_volumes: Option<&ConvexVolumeSet>,
+    solid: &mut RcHeightfield,
+) -> RNResult<RcCompactHeightfield> {
+    // Filter walkable surfaces.
+    rc_filter_low_hanging_walkable_obstacles(ctx, cfg.walkable_climb, solid);
+    rc_filter_ledge_spans(ctx, cfg.walkable_height, cfg.walkable_climb, solid);
+    rc_filter_walkable_low_height_spans(ctx, cfg.walkable_height, solid);
+
+    let mut chf = RcCompactHeightfield::new();
+    at_stage(
+        rc_build_compact_heightfield(ctx, cfg.walkable_height, cfg.walkable_climb, solid, &mut chf),
+        RcBuildStage::CompactHeightfield,
+    )?;
+    at_stage(
+        rc_erode_walkable_area(ctx, cfg.walkable_radius, &mut chf),
+        RcBuildStage::ErodeArea,
+    )?;
+
+    // Mark areas.
+    if let Some(convex_volumes) = convex_volumes {
+        convex_volumes.mark_areas(ctx, &mut chf);
+    }
+    Ok(chf)
+}
+
+// Runs the pipeline from a rasterized heightfield to the heightfield layers of a tile cache.
+pub(crate) fn build_heightfield_layers(
+    ctx: &mut RcContext,
+    cfg: &RcConfig,
+    convex_volumes: Option<&ConvexVolumeSet>,
+    solid: &mut RcHeightfield,
+) -> RNResult<RcHeightfieldLayerSet> {
+    let chf = build_compact_heightfield(ctx, cfg, convex_volumes, solid)?;
+    let mut lset = RcHeightfieldLayerSet::new();
+    at_stage(
+        rc_build_heightfield_layers(ctx, &chf, cfg.border_size, cfg.walkable_height, &mut lset),
+        RcBuildStage::HeightfieldLayers,
+    )?;
+    Ok(lset)
+}
+
+// Creates detour data from recast poly mesh.
+pub(crate) fn create_nav_mesh_data(
+    cfg: &RcConfig,
+    pmesh: &mut RcPolyMesh,
+    dmesh: &RcPolyMeshDetail,
+    walkable_height: f32,
+    walkable_radius: f32,
+    walkable_climb: f32,
+    tile_x: i32,
+    tile_y: i32,
+    build_bv_tree: bool,
+    poly_flags: Option<&RcPolyFlagsFn>,
+    off_mesh: Option<&OffMeshColumns>,
+) -> RNResult<DtBuf> {
+    match poly_flags {
+        Some(poly_flags) => {
+            let (areas, flags) = pmesh.areas_and_flags_mut();
+            poly_flags(areas, flags);
+        }
+        None => pmesh.flags_mut().fill(1),
+    }
+
+    let mut params = DtNavMeshCreateParams {
+        verts: Some(pmesh.verts()),
+        polys: Some(pmesh.polys()),
+        poly_areas: Some(pmesh.areas()),
+        poly_flags: Some(pmesh.flags()),
+        nvp: pmesh.nvp(),
+        detail_meshes: Some(dmesh.meshes()),
+        detail_verts: Some(dmesh.verts()),
+        detail_tris: Some(dmesh.tris()),
+        walkable_height,
+        walkable_radius,
+        walkable_climb,
+        tile_x,
+        tile_y,
+        tile_layer: 0,
+        bmin: pmesh.bmin,
+        bmax: pmesh.bmax,
+        cs: cfg.cs,
+        ch: cfg.ch,
+        build_bv_tree,
+        ..DtNavMeshCreateParams::default()
+    };
+    if let Some(off_mesh) = off_mesh {
+        off_mesh.fill_params(&mut params);
+    }
+    at_stage(dt_create_nav_mesh_data(&mut params), RcBuildStage::NavMeshData)
+}
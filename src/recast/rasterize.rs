@@ -0,0 +1,573 @@
+use crate::error::{RNError, RNResult};
+use crate::recast::geometry::InputGeometry;
+use crate::recast::recast::*;
+
+// A regular grid of height samples, e.g. a terrain heightmap.
+//
+// Sample (x, z) is at `origin + [x * spacing[0], heights[z * width + x], z * spacing[1]]`.
+// Each cell is split into the triangles (x, z), (x, z + 1), (x + 1, z + 1) and (x, z), (x + 1, z + 1), (x + 1, z),
+// see `triangulate()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RcHeightGrid<'t> {
+    pub origin: [f32; 3],
+
+    // The distance between samples along the x and z axes. [Limit: > 0] [Units: wu]
+    pub spacing: [f32; 2],
+
+    // The number of samples along the x and z axes. [Limit: >= 2]
+    pub width: usize,
+    pub height: usize,
+
+    // Sample heights, relative to `origin[1]`. [Size: width * height]
+    pub heights: &'t [f32],
+
+    // Per-cell area ids. If None, cells are marked walkable by the slope. [Size: (width - 1) * (height - 1)]
+    pub areas: Option<&'t [u8]>,
+
+    // Per-cell hole mask, true cells are not rasterized. [Size: (width - 1) * (height - 1)]
+    pub holes: Option<&'t [bool]>,
+}
+
+impl<'t> RcHeightGrid<'t> {
+    #[inline]
+    pub fn new(
+        origin: [f32; 3],
+        spacing: [f32; 2],
+        width: usize,
+        height: usize,
+        heights: &'t [f32],
+    ) -> RcHeightGrid<'t> {
+        RcHeightGrid {
+            origin,
+            spacing,
+            width,
+            height,
+            heights,
+            areas: None,
+            holes: None,
+        }
+    }
+
+    // The number of cells along the x and z axes.
+    #[inline]
+    pub fn cells(&self) -> (usize, usize) {
+        (self.width.saturating_sub(1), self.height.saturating_sub(1))
+    }
+
+    #[inline]
+    pub fn vertex(&self, x: usize, z: usize) -> [f32; 3] {
+        [
+            self.origin[0] + x as f32 * self.spacing[0],
+            self.origin[1] + self.heights[z * self.width + x],
+            self.origin[2] + z as f32 * self.spacing[1],
+        ]
+    }
+
+    #[inline]
+    pub fn is_hole(&self, cx: usize, cz: usize) -> bool {
+        self.holes.is_some_and(|holes| holes[cz * (self.width - 1) + cx])
+    }
+
+    // The AABB of the samples.
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let (ymin, ymax) = self
+            .heights
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), h| (lo.min(*h), hi.max(*h)));
+        let (cx, cz) = self.cells();
+        (
+            [self.origin[0], self.origin[1] + ymin, self.origin[2]],
+            [
+                self.origin[0] + cx as f32 * self.spacing[0],
+                self.origin[1] + ymax,
+                self.origin[2] + cz as f32 * self.spacing[1],
+            ],
+        )
+    }
+
+    pub fn validate(&self) -> RNResult<()> {
+        let (cx, cz) = self.cells();
+        if cx == 0 || cz == 0 || self.spacing[0] <= 0.0 || self.spacing[1] <= 0.0 {
+            return Err(RNError::InvalidParam);
+        }
+        if self.heights.len() != self.width * self.height
+            || self.areas.is_some_and(|areas| areas.len() != cx * cz)
+            || self.holes.is_some_and(|holes| holes.len() != cx * cz)
+        {
+            return Err(RNError::InvalidParam);
+        }
+        Ok(())
+    }
+
+    // The triangle soup rasterized by `rc_rasterize_height_grid()`, with the per-triangle area ids
+    // if `areas` is set. Holes are skipped.
+    pub fn triangulate(&self) -> RcHeightGridMesh {
+        let (cx, cz) = self.cells();
+        let mut verts = Vec::with_capacity(self.width * self.height);
+        for z in 0..self.height {
+            for x in 0..self.width {
+                verts.push(self.vertex(x, z));
+            }
+        }
+        let mut tris = Vec::with_capacity(cx * cz * 2);
+        let mut areas = self.areas.map(|_| Vec::with_capacity(cx * cz * 2));
+        for z in 0..cz {
+            for x in 0..cx {
+                if self.is_hole(x, z) {
+                    continue;
+                }
+                let i00 = (z * self.width + x) as i32;
+                let i10 = i00 + 1;
+                let i01 = i00 + self.width as i32;
+                let i11 = i01 + 1;
+                tris.push([i00, i01, i11]);
+                tris.push([i00, i11, i10]);
+                if let (Some(areas), Some(cell_areas)) = (areas.as_mut(), self.areas) {
+                    let area = cell_areas[z * cx + x];
+                    areas.extend([area, area]);
+                }
+            }
+        }
+        RcHeightGridMesh {
+            verts,
+            tris,
+            tri_areas: areas,
+        }
+    }
+}
+
+// The triangulated height grid, usable as the input of the navigation mesh builders.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RcHeightGridMesh {
+    pub verts: Vec<[f32; 3]>,
+    pub tris: Vec<[i32; 3]>,
+    pub tri_areas: Option<Vec<u8>>,
+}
+
+impl InputGeometry for RcHeightGridMesh {
+    #[inline]
+    fn verts(&self) -> &[[f32; 3]] {
+        &self.verts
+    }
+
+    #[inline]
+    fn tris(&self) -> &[[i32; 3]] {
+        &self.tris
+    }
+
+    #[inline]
+    fn tri_areas(&self) -> Option<&[u8]> {
+        self.tri_areas.as_deref()
+    }
+}
+
+// Rasterizes a height grid into the heightfield, one row of cells at a time.
+//
+// Produces the same spans as marking (with `walkable_slope_angle`, unless the grid has areas) and
+// rasterizing the soup of `grid.triangulate()`, without allocating it.
+pub fn rc_rasterize_height_grid(
+    context: &mut RcContext,
+    grid: &RcHeightGrid,
+    walkable_slope_angle: f32,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    grid.validate()?;
+
+    let (cx, cz) = grid.cells();
+    let mut verts = Vec::with_capacity(grid.width * 2);
+    let mut tris = Vec::with_capacity(cx * 2);
+    let mut areas = Vec::with_capacity(cx * 2);
+    for z in 0..cz {
+        // Row z is verts[0..width], row z + 1 is verts[width..].
+        verts.clear();
+        for dz in 0..2 {
+            for x in 0..grid.width {
+                verts.push(grid.vertex(x, z + dz));
+            }
+        }
+
+        tris.clear();
+        areas.clear();
+        for x in 0..cx {
+            if grid.is_hole(x, z) {
+                continue;
+            }
+            let i00 = x as i32;
+            let i01 = (grid.width + x) as i32;
+            tris.push([i00, i01, i01 + 1]);
+            tris.push([i00, i01 + 1, i00 + 1]);
+            if let Some(cell_areas) = grid.areas {
+                let area = cell_areas[z * cx + x];
+                areas.extend([area, area]);
+            }
+        }
+        if tris.is_empty() {
+            continue;
+        }
+
+        if grid.areas.is_none() {
+            areas.resize(tris.len(), RC_NULL_AREA);
+            rc_mark_walkable_triangles(context, walkable_slope_angle, &verts, &tris, &mut areas)?;
+        }
+        if !rc_rasterize_triangles_1(context, &verts, &tris, &areas, heightfield, flag_merge_threshold)? {
+            return Err(RNError::Failed);
+        }
+    }
+    Ok(())
+}
+
+// The number of search iterations of `rc_rasterize_capsule()` per column.
+const CAPSULE_SEARCH_ITERS: usize = 32;
+
+// The corners of a box are `center + sum(±half_extents[i] * axes[i])`, corner `i` uses the sign bits x, y, z of `i`.
+const BOX_TRIS: [[i32; 3]; 12] = [
+    [0, 2, 6],
+    [0, 6, 4],
+    [1, 3, 7],
+    [1, 7, 5],
+    [0, 1, 5],
+    [0, 5, 4],
+    [2, 3, 7],
+    [2, 7, 6],
+    [0, 1, 3],
+    [0, 3, 2],
+    [4, 5, 7],
+    [4, 7, 6],
+];
+
+// The axes of an axis-aligned box for `rc_rasterize_box()`.
+pub const RC_BOX_AXES_IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+// Rasterizes a solid box into the heightfield.
+// `axes` are the unit x, y and z axes of the box in world space, e.g. the columns of its rotation matrix.
+pub fn rc_rasterize_box(
+    context: &mut RcContext,
+    center: &[f32; 3],
+    half_extents: &[f32; 3],
+    axes: &[[f32; 3]; 3],
+    area_id: u8,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    if half_extents.iter().any(|e| *e < 0.0) {
+        return Err(RNError::InvalidParam);
+    }
+    let mut corners = [*center; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        for (axis, (dir, extent)) in axes.iter().zip(half_extents).enumerate() {
+            let extent = if i & (1 << axis) != 0 { *extent } else { -*extent };
+            for k in 0..3 {
+                corner[k] += dir[k] * extent;
+            }
+        }
+    }
+    rc_rasterize_convex_hull(context, &corners, &BOX_TRIS, area_id, heightfield, flag_merge_threshold)
+}
+
+// Rasterizes a solid vertical cylinder into the heightfield. `base` is the center of the bottom cap.
+pub fn rc_rasterize_cylinder(
+    context: &mut RcContext,
+    base: &[f32; 3],
+    radius: f32,
+    height: f32,
+    area_id: u8,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    if radius <= 0.0 || height < 0.0 {
+        return Err(RNError::InvalidParam);
+    }
+    let bmin = [base[0] - radius, base[1], base[2] - radius];
+    let bmax = [base[0] + radius, base[1] + height, base[2] + radius];
+    let r2 = radius * radius;
+    rasterize_columns(
+        context,
+        heightfield,
+        &bmin,
+        &bmax,
+        area_id,
+        flag_merge_threshold,
+        |cmin, cmax| {
+            if rect_dist_sqr(base, cmin, cmax) >= r2 {
+                return None;
+            }
+            Some((base[1], base[1] + height))
+        },
+    )
+}
+
+// Rasterizes a solid capsule, the segment from `a` to `b` swept by a sphere of `radius`.
+pub fn rc_rasterize_capsule(
+    context: &mut RcContext,
+    a: &[f32; 3],
+    b: &[f32; 3],
+    radius: f32,
+    area_id: u8,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    if radius <= 0.0 {
+        return Err(RNError::InvalidParam);
+    }
+    let bmin = [
+        a[0].min(b[0]) - radius,
+        a[1].min(b[1]) - radius,
+        a[2].min(b[2]) - radius,
+    ];
+    let bmax = [
+        a[0].max(b[0]) + radius,
+        a[1].max(b[1]) + radius,
+        a[2].max(b[2]) + radius,
+    ];
+    let r2 = radius * radius;
+    let point = |t: f32| {
+        [
+            a[0] * (1.0 - t) + b[0] * t,
+            a[1] * (1.0 - t) + b[1] * t,
+            a[2] * (1.0 - t) + b[2] * t,
+        ]
+    };
+    rasterize_columns(
+        context,
+        heightfield,
+        &bmin,
+        &bmax,
+        area_id,
+        flag_merge_threshold,
+        |cmin, cmax| {
+            // The capsule is the union of the spheres centered on the segment. The squared half height of the
+            // sphere at `t` over the column is concave in t, so the extent of the union is found by ternary search.
+            let h2 = |t: f32| r2 - rect_dist_sqr(&point(t), cmin, cmax);
+            let tm = ternary_search(0.0, 1.0, &h2);
+            if h2(tm) <= 0.0 {
+                return None;
+            }
+            let t0 = if h2(0.0) > 0.0 { 0.0 } else { bisect(0.0, tm, &h2) };
+            let t1 = if h2(1.0) > 0.0 { 1.0 } else { bisect(1.0, tm, &h2) };
+
+            let top = |t: f32| point(t)[1] + h2(t).max(0.0).sqrt();
+            let bottom = |t: f32| point(t)[1] - h2(t).max(0.0).sqrt();
+            let t_top = ternary_search(t0, t1, &top);
+            let t_bottom = ternary_search(t0, t1, &|t| -bottom(t));
+            let ymax = top(t0).max(top(t1)).max(top(t_top));
+            let ymin = bottom(t0).min(bottom(t1)).min(bottom(t_bottom));
+            Some((ymin, ymax))
+        },
+    )
+}
+
+// Rasterizes a solid sphere into the heightfield.
+pub fn rc_rasterize_sphere(
+    context: &mut RcContext,
+    center: &[f32; 3],
+    radius: f32,
+    area_id: u8,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    if radius <= 0.0 {
+        return Err(RNError::InvalidParam);
+    }
+    let bmin = [center[0] - radius, center[1] - radius, center[2] - radius];
+    let bmax = [center[0] + radius, center[1] + radius, center[2] + radius];
+    let r2 = radius * radius;
+    rasterize_columns(
+        context,
+        heightfield,
+        &bmin,
+        &bmax,
+        area_id,
+        flag_merge_threshold,
+        |cmin, cmax| {
+            let d2 = rect_dist_sqr(center, cmin, cmax);
+            if d2 >= r2 {
+                return None;
+            }
+            let h = (r2 - d2).sqrt();
+            Some((center[1] - h, center[1] + h))
+        },
+    )
+}
+
+// Rasterizes a solid convex hull into the heightfield.
+// `tris` must be the closed surface of the hull, the winding of the triangles does not matter.
+//
+// Each column gets a single span, from the lowest to the highest point of the surface clipped to the column,
+// the same way `rc_rasterize_triangle()` computes the span of a triangle.
+pub fn rc_rasterize_convex_hull(
+    context: &mut RcContext,
+    verts: &[[f32; 3]],
+    tris: &[[i32; 3]],
+    area_id: u8,
+    heightfield: &mut RcHeightfield,
+    flag_merge_threshold: i32,
+) -> RNResult<()> {
+    if tris.is_empty() || tris.iter().flatten().any(|i| *i < 0 || *i as usize >= verts.len()) {
+        return Err(RNError::InvalidParam);
+    }
+    let (bmin, bmax) = rc_calc_bounds(verts);
+    let tri_bounds: Vec<([f32; 3], [f32; 3])> = tris
+        .iter()
+        .map(|tri| rc_calc_bounds(&tri.map(|i| verts[i as usize])))
+        .collect();
+    let mut poly = Vec::with_capacity(7);
+    let mut tmp = Vec::with_capacity(7);
+    rasterize_columns(
+        context,
+        heightfield,
+        &bmin,
+        &bmax,
+        area_id,
+        flag_merge_threshold,
+        |cmin, cmax| {
+            let mut extent: Option<(f32, f32)> = None;
+            for (tri, (tmin, tmax)) in tris.iter().zip(&tri_bounds) {
+                if tmin[0] > cmax[0] || tmax[0] < cmin[0] || tmin[2] > cmax[1] || tmax[2] < cmin[1] {
+                    continue;
+                }
+                poly.clear();
+                poly.extend(tri.map(|i| verts[i as usize]));
+                clip_poly(&poly, &mut tmp, 0, cmin[0], true);
+                clip_poly(&tmp, &mut poly, 0, cmax[0], false);
+                clip_poly(&poly, &mut tmp, 2, cmin[1], true);
+                clip_poly(&tmp, &mut poly, 2, cmax[1], false);
+                if poly.len() < 3 {
+                    continue;
+                }
+                for v in &poly {
+                    extent = Some(match extent {
+                        Some((lo, hi)) => (lo.min(v[1]), hi.max(v[1])),
+                        None => (v[1], v[1]),
+                    });
+                }
+            }
+            extent
+        },
+    )
+}
+
+// Adds a span to each column overlapping the xz-bounds of a shape.
+// `extent` returns the y-range of the shape inside the column with the given xz-bounds, or None.
+fn rasterize_columns<F>(
+    context: &mut RcContext,
+    heightfield: &mut RcHeightfield,
+    bmin: &[f32; 3],
+    bmax: &[f32; 3],
+    area_id: u8,
+    flag_merge_threshold: i32,
+    mut extent: F,
+) -> RNResult<()>
+where
+    F: FnMut(&[f32; 2], &[f32; 2]) -> Option<(f32, f32)>,
+{
+    let (hf_bmin, hf_bmax) = (heightfield.bmin, heightfield.bmax);
+    if bmin[0] > hf_bmax[0] || bmax[0] < hf_bmin[0] || bmin[1] > hf_bmax[1] || bmax[1] < hf_bmin[1] {
+        return Ok(());
+    }
+    if bmin[2] > hf_bmax[2] || bmax[2] < hf_bmin[2] {
+        return Ok(());
+    }
+
+    let (w, h) = (heightfield.width(), heightfield.height());
+    let cs = heightfield.cs;
+    let ics = 1.0 / cs;
+    let ich = 1.0 / heightfield.ch;
+    let by = hf_bmax[1] - hf_bmin[1];
+    let x0 = (((bmin[0] - hf_bmin[0]) * ics) as i32).clamp(0, w - 1);
+    let x1 = (((bmax[0] - hf_bmin[0]) * ics) as i32).clamp(0, w - 1);
+    let z0 = (((bmin[2] - hf_bmin[2]) * ics) as i32).clamp(0, h - 1);
+    let z1 = (((bmax[2] - hf_bmin[2]) * ics) as i32).clamp(0, h - 1);
+
+    for z in z0..=z1 {
+        for x in x0..=x1 {
+            let cmin = [hf_bmin[0] + x as f32 * cs, hf_bmin[2] + z as f32 * cs];
+            let cmax = [cmin[0] + cs, cmin[1] + cs];
+            let Some((ymin, ymax)) = extent(&cmin, &cmax) else {
+                continue;
+            };
+
+            // Same quantization as rasterizeTri().
+            let span_min = ymin - hf_bmin[1];
+            let span_max = ymax - hf_bmin[1];
+            if span_max < 0.0 || span_min > by {
+                continue;
+            }
+            let span_min = span_min.max(0.0);
+            let span_max = span_max.min(by);
+            // Keeps room for smax when the heightfield is taller than the span height range.
+            let smin = ((span_min * ich).floor() as i32).clamp(0, RC_SPAN_MAX_HEIGHT as i32 - 1);
+            let smax = ((span_max * ich).ceil() as i32).clamp(smin + 1, RC_SPAN_MAX_HEIGHT as i32);
+            rc_add_span(
+                context,
+                heightfield,
+                x,
+                z,
+                smin as u16,
+                smax as u16,
+                area_id,
+                flag_merge_threshold,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// The squared xz-distance from a point to a column.
+#[inline]
+fn rect_dist_sqr(p: &[f32; 3], cmin: &[f32; 2], cmax: &[f32; 2]) -> f32 {
+    let dx = (cmin[0] - p[0]).max(p[0] - cmax[0]).max(0.0);
+    let dz = (cmin[1] - p[2]).max(p[2] - cmax[1]).max(0.0);
+    dx * dx + dz * dz
+}
+
+// The argument maximizing a concave function on [lo, hi].
+fn ternary_search<F: Fn(f32) -> f32>(mut lo: f32, mut hi: f32, f: &F) -> f32 {
+    for _ in 0..CAPSULE_SEARCH_ITERS {
+        let m1 = lo + (hi - lo) / 3.0;
+        let m2 = hi - (hi - lo) / 3.0;
+        if f(m1) < f(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    (lo + hi) * 0.5
+}
+
+// The point between `outside` (f <= 0) and `inside` (f > 0) where f crosses 0.
+fn bisect<F: Fn(f32) -> f32>(mut outside: f32, mut inside: f32, f: &F) -> f32 {
+    for _ in 0..CAPSULE_SEARCH_ITERS {
+        let mid = (outside + inside) * 0.5;
+        if f(mid) > 0.0 {
+            inside = mid;
+        } else {
+            outside = mid;
+        }
+    }
+    inside
+}
+
+// Keeps the part of the polygon above (or below) `offset` along `axis`.
+fn clip_poly(src: &[[f32; 3]], dst: &mut Vec<[f32; 3]>, axis: usize, offset: f32, keep_above: bool) {
+    dst.clear();
+    if src.is_empty() {
+        return;
+    }
+    let dist = |v: &[f32; 3]| if keep_above { v[axis] - offset } else { offset - v[axis] };
+    for (i, b) in src.iter().enumerate() {
+        let a = &src[(i + src.len() - 1) % src.len()];
+        let (da, db) = (dist(a), dist(b));
+        if (da >= 0.0) != (db >= 0.0) {
+            let s = da / (da - db);
+            dst.push([
+                a[0] + (b[0] - a[0]) * s,
+                a[1] + (b[1] - a[1]) * s,
+                a[2] + (b[2] - a[2]) * s,
+            ]);
+        }
+        if db >= 0.0 {
+            dst.push(*b);
+        }
+    }
+}
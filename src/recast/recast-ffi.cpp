@@ -0,0 +1,64 @@
+#include "recastnavigation-rs/src/recast/recast.rs.h"
+
+void rcRustContext::doLog(const rcLogCategory category, const char* msg, const int len)
+{
+	if (len <= 0)
+		return;
+	rcContextDoLog(*m_rs, category, rust::Slice<const uint8_t>((const uint8_t*)msg, (size_t)len));
+}
+
+void rcRustContext::doResetTimers()
+{
+	for (int i = 0; i < RC_MAX_TIMERS; ++i)
+		m_accTime[i] = -1;
+}
+
+void rcRustContext::doStartTimer(const rcTimerLabel label)
+{
+	m_startTime[label] = std::chrono::steady_clock::now();
+}
+
+void rcRustContext::doStopTimer(const rcTimerLabel label)
+{
+	const auto endTime = std::chrono::steady_clock::now();
+	const int deltaTime = (int)std::chrono::duration_cast<std::chrono::microseconds>(endTime - m_startTime[label]).count();
+	if (m_accTime[label] == -1)
+		m_accTime[label] = deltaTime;
+	else
+		m_accTime[label] += deltaTime;
+}
+
+int rcRustContext::doGetAccumulatedTime(const rcTimerLabel label) const
+{
+	return m_accTime[label];
+}
+
+// Same as allocSpan() of RecastRasterization.cpp, which is not exported.
+rcSpan* rcHeightfieldAllocSpan(rcHeightfield& hf)
+{
+	if (hf.freelist == NULL || hf.freelist->next == NULL)
+	{
+		rcSpanPool* pool = (rcSpanPool*)rcAlloc(sizeof(rcSpanPool), RC_ALLOC_PERM);
+		if (pool == NULL)
+			return NULL;
+
+		pool->next = hf.pools;
+		hf.pools = pool;
+
+		rcSpan* freeList = hf.freelist;
+		rcSpan* head = &pool->items[0];
+		rcSpan* it = &pool->items[RC_SPANS_PER_POOL];
+		do
+		{
+			--it;
+			it->next = freeList;
+			freeList = it;
+		}
+		while (it != head);
+		hf.freelist = it;
+	}
+
+	rcSpan* newSpan = hf.freelist;
+	hf.freelist = hf.freelist->next;
+	return newSpan;
+}
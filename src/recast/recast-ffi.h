@@ -1,5 +1,6 @@
 #pragma once
 
+#include <chrono>
 #include <memory>
 #include "Recast.h"
 #include "RecastAlloc.h"
@@ -8,7 +9,40 @@
 #include "rust/cxx.h"
 #include "recastnavigation-rs/src/utils.h"
 
-inline std::unique_ptr<rcContext> rcNewContext(bool state) { return std::make_unique<rcContext>(state); }
+struct RcContextState;
+
+// rcContext forwarding log messages to the Rust side, with timers in microseconds like RecastDemo's BuildContext.
+class rcRustContext : public rcContext {
+public:
+    rcRustContext(bool state, RcContextState& rs) : rcContext(state), m_rs(&rs) { doResetTimers(); }
+
+protected:
+    void doLog(const rcLogCategory category, const char* msg, const int len) override;
+    void doResetTimers() override;
+    void doStartTimer(const rcTimerLabel label) override;
+    void doStopTimer(const rcTimerLabel label) override;
+    int doGetAccumulatedTime(const rcTimerLabel label) const override;
+
+private:
+    RcContextState* m_rs;
+    std::chrono::steady_clock::time_point m_startTime[RC_MAX_TIMERS];
+    int m_accTime[RC_MAX_TIMERS];
+};
+
+inline std::unique_ptr<rcContext> rcNewContext(bool state, RcContextState& rs) {
+    return std::make_unique<rcRustContext>(state, rs);
+}
+
+rcSpan* rcHeightfieldAllocSpan(rcHeightfield& hf);
+
+// Memory owned by the recast structures, released by rcFree() in rcFreeXxx().
+inline uint8_t* rcAllocBytes(size_t size) {
+    return (uint8_t*)rcAlloc(size, RC_ALLOC_PERM);
+}
+
+inline void rcContextLog(rcContext& ctx, rcLogCategory category, rust::Str msg) {
+    ctx.log(category, "%.*s", (int)msg.size(), msg.data());
+}
 
 static_assert(sizeof(rcConfig) == 92, "rcConfig size");
 static_assert(sizeof(rcSpan) == SIZE_32_64(8, 16), "rcSpan size");
@@ -1,10 +1,12 @@
 use cxx::{type_id, ExternType, UniquePtr};
 use static_assertions::const_assert_eq;
 use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
 use std::mem;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::pin::Pin;
-use std::slice;
+use std::time::Duration;
+use std::{ptr, slice};
 
 use crate::error::{RNError, RNResult};
 
@@ -26,6 +28,7 @@ pub const RC_NOT_CONNECTED: i32 = 0x3f;
 #[cxx::bridge]
 pub(crate) mod ffi {
     // Recast log categories.
+    #[derive(Debug)]
     #[repr(u32)]
     enum rcLogCategory {
         RC_LOG_PROGRESS = 1,
@@ -34,6 +37,7 @@ pub(crate) mod ffi {
     }
 
     // Recast performance timer categories.
+    #[derive(Debug)]
     #[repr(u32)]
     enum rcTimerLabel {
         RC_TIMER_TOTAL,
@@ -73,6 +77,12 @@ pub(crate) mod ffi {
         RC_CONTOUR_TESS_AREA_EDGES = 0x02,
     }
 
+    extern "Rust" {
+        type RcContextState;
+        #[cxx_name = "rcContextDoLog"]
+        fn rc_context_do_log(rs: &mut RcContextState, category: rcLogCategory, msg: &[u8]);
+    }
+
     unsafe extern "C++" {
         include!("recastnavigation-rs/src/recast/recast-ffi.h");
 
@@ -89,10 +99,10 @@ pub(crate) mod ffi {
         //
 
         type rcContext;
-        fn rcNewContext(state: bool) -> UniquePtr<rcContext>;
+        unsafe fn rcNewContext(state: bool, rs: &mut RcContextState) -> UniquePtr<rcContext>;
         fn enableLog(self: Pin<&mut rcContext>, state: bool);
         fn resetLog(self: Pin<&mut rcContext>);
-        // fn log(self: Pin<&mut rcContext>, category: rcLogCategory, format: &str);
+        fn rcContextLog(ctx: Pin<&mut rcContext>, category: rcLogCategory, msg: &str);
         fn enableTimer(self: Pin<&mut rcContext>, state: bool);
         fn resetTimers(self: Pin<&mut rcContext>);
         fn startTimer(self: Pin<&mut rcContext>, label: rcTimerLabel);
@@ -107,6 +117,8 @@ pub(crate) mod ffi {
         type rcHeightfield = crate::recast::recast::CxxRcHeightfield;
         unsafe fn rcAllocHeightfield() -> *mut rcHeightfield;
         unsafe fn rcFreeHeightField(heightfield: *mut rcHeightfield);
+        unsafe fn rcAllocBytes(size: usize) -> *mut u8;
+        unsafe fn rcHeightfieldAllocSpan(heightfield: Pin<&mut rcHeightfield>) -> *mut rcSpan;
 
         type rcCompactCell = crate::recast::recast::RcCompactCell;
         type rcCompactSpan = crate::recast::recast::RcCompactSpan;
@@ -362,7 +374,55 @@ pub type RcBuildContoursFlags = ffi::rcBuildContoursFlags;
 // RcContext
 //
 
-pub struct RcContext(UniquePtr<ffi::rcContext>);
+// A log message emitted by Recast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcLogMessage {
+    pub category: RcLogCategory,
+    pub text: String,
+}
+
+// Receives the log messages of an `RcContext`, see `RcContext::set_log_sink()`.
+pub trait RcLogSink: Send {
+    fn log(&mut self, category: RcLogCategory, text: &str);
+}
+
+impl<F: FnMut(RcLogCategory, &str) + Send> RcLogSink for F {
+    #[inline]
+    fn log(&mut self, category: RcLogCategory, text: &str) {
+        self(category, text)
+    }
+}
+
+// The maximum number of accumulated log messages, same as RecastDemo's BuildContext.
+// Later messages are only forwarded to the log sink.
+pub const RC_MAX_LOG_MESSAGES: usize = 1000;
+
+// The Rust side state of an rcContext, the C++ context keeps a pointer to it.
+#[derive(Default)]
+pub(crate) struct RcContextState {
+    messages: Vec<RcLogMessage>,
+    sink: Option<Box<dyn RcLogSink>>,
+}
+
+fn rc_context_do_log(rs: &mut RcContextState, category: RcLogCategory, msg: &[u8]) {
+    let text = String::from_utf8_lossy(msg);
+    if let Some(sink) = &mut rs.sink {
+        sink.log(category, &text);
+    }
+    if rs.messages.len() < RC_MAX_LOG_MESSAGES {
+        rs.messages.push(RcLogMessage {
+            category,
+            text: text.into_owned(),
+        });
+    }
+}
+
+pub struct RcContext(UniquePtr<ffi::rcContext>, *mut RcContextState);
+
+// Recast wrappers own their C++ allocations exclusively and have no thread affinity,
+// so they can be moved to another thread (e.g. built on a worker and sent back).
+// They are not `Sync`, a C++ object must not be mutated from two threads at once.
+unsafe impl Send for RcContext {}
 
 impl Debug for RcContext {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -373,10 +433,29 @@ impl Debug for RcContext {
     }
 }
 
+impl Drop for RcContext {
+    fn drop(&mut self) {
+        // The C++ context must go first, it points to the state.
+        self.0 = UniquePtr::null();
+        unsafe { drop(Box::from_raw(self.1)) };
+    }
+}
+
 impl RcContext {
     #[inline]
     pub fn new(state: bool) -> RcContext {
-        RcContext(ffi::rcNewContext(state))
+        let rs = Box::into_raw(Box::<RcContextState>::default());
+        RcContext(unsafe { ffi::rcNewContext(state, &mut *rs) }, rs)
+    }
+
+    #[inline]
+    fn state(&self) -> &RcContextState {
+        unsafe { &*self.1 }
+    }
+
+    #[inline]
+    fn state_mut(&mut self) -> &mut RcContextState {
+        unsafe { &mut *self.1 }
     }
 
     #[inline]
@@ -384,9 +463,35 @@ impl RcContext {
         self.0.pin_mut().enableLog(state);
     }
 
+    // Clears the accumulated log messages.
     #[inline]
     pub fn reset_log(&mut self) {
         self.0.pin_mut().resetLog();
+        self.state_mut().messages.clear();
+    }
+
+    #[inline]
+    pub fn log(&mut self, category: RcLogCategory, msg: &str) {
+        ffi::rcContextLog(self.0.pin_mut(), category, msg);
+    }
+
+    // Forwards every log message to `sink` as it is emitted. Messages are still accumulated, up to
+    // `RC_MAX_LOG_MESSAGES`.
+    #[inline]
+    pub fn set_log_sink(&mut self, sink: Option<Box<dyn RcLogSink>>) {
+        self.state_mut().sink = sink;
+    }
+
+    // The log messages accumulated since the last `reset_log()` or `drain_log()`, at most `RC_MAX_LOG_MESSAGES`.
+    #[inline]
+    pub fn log_messages(&self) -> &[RcLogMessage] {
+        &self.state().messages
+    }
+
+    // Takes the accumulated log messages, e.g. to diagnose a failed build.
+    #[inline]
+    pub fn drain_log(&mut self) -> Vec<RcLogMessage> {
+        mem::take(&mut self.state_mut().messages)
     }
 
     #[inline]
@@ -413,6 +518,91 @@ impl RcContext {
     pub fn get_accumulated_time(&self, label: RcTimerLabel) -> i32 {
         self.0.getAccumulatedTime(label)
     }
+
+    // The accumulated time of all timers since the last `reset_timers()`.
+    pub fn timings(&self) -> RcTimings {
+        let mut timings = RcTimings::default();
+        for (idx, time) in timings.times.iter_mut().enumerate() {
+            let t = self.get_accumulated_time(RcTimerLabel { repr: idx as u32 });
+            if t >= 0 {
+                *time = Some(Duration::from_micros(t as u64));
+            }
+        }
+        timings
+    }
+}
+
+pub const RC_MAX_TIMERS: usize = RcTimerLabel::RC_MAX_TIMERS.repr as usize;
+
+// The accumulated time of each `RcTimerLabel`, `None` for timers that did not run.
+// `Display` prints the same report as RecastDemo's duLogBuildTimes().
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RcTimings {
+    pub times: [Option<Duration>; RC_MAX_TIMERS],
+}
+
+impl RcTimings {
+    #[inline]
+    pub fn get(&self, label: RcTimerLabel) -> Option<Duration> {
+        self.times.get(label.repr as usize).copied().flatten()
+    }
+
+    #[inline]
+    pub fn total(&self) -> Option<Duration> {
+        self.get(RcTimerLabel::RC_TIMER_TOTAL)
+    }
+
+    // The timers that ran, with their labels.
+    pub fn iter(&self) -> impl Iterator<Item = (RcTimerLabel, Duration)> + '_ {
+        (self.times.iter().enumerate()).filter_map(|(idx, time)| time.map(|t| (RcTimerLabel { repr: idx as u32 }, t)))
+    }
+}
+
+impl fmt::Display for RcTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const LINES: [(RcTimerLabel, &str); 25] = [
+            (RcTimerLabel::RC_TIMER_RASTERIZE_TRIANGLES, "- Rasterize"),
+            (RcTimerLabel::RC_TIMER_BUILD_COMPACTHEIGHTFIELD, "- Build Compact"),
+            (RcTimerLabel::RC_TIMER_FILTER_BORDER, "- Filter Border"),
+            (RcTimerLabel::RC_TIMER_FILTER_WALKABLE, "- Filter Walkable"),
+            (RcTimerLabel::RC_TIMER_ERODE_AREA, "- Erode Area"),
+            (RcTimerLabel::RC_TIMER_MEDIAN_AREA, "- Median Area"),
+            (RcTimerLabel::RC_TIMER_MARK_BOX_AREA, "- Mark Box Area"),
+            (RcTimerLabel::RC_TIMER_MARK_CONVEXPOLY_AREA, "- Mark Convex Area"),
+            (RcTimerLabel::RC_TIMER_MARK_CYLINDER_AREA, "- Mark Cylinder Area"),
+            (RcTimerLabel::RC_TIMER_BUILD_DISTANCEFIELD, "- Build Distance Field"),
+            (RcTimerLabel::RC_TIMER_BUILD_DISTANCEFIELD_DIST, "    - Distance"),
+            (RcTimerLabel::RC_TIMER_BUILD_DISTANCEFIELD_BLUR, "    - Blur"),
+            (RcTimerLabel::RC_TIMER_BUILD_REGIONS, "- Build Regions"),
+            (RcTimerLabel::RC_TIMER_BUILD_REGIONS_WATERSHED, "    - Watershed"),
+            (RcTimerLabel::RC_TIMER_BUILD_REGIONS_EXPAND, "      - Expand"),
+            (RcTimerLabel::RC_TIMER_BUILD_REGIONS_FLOOD, "      - Find Basins"),
+            (RcTimerLabel::RC_TIMER_BUILD_REGIONS_FILTER, "    - Filter"),
+            (RcTimerLabel::RC_TIMER_BUILD_LAYERS, "- Build Layers"),
+            (RcTimerLabel::RC_TIMER_BUILD_CONTOURS, "- Build Contours"),
+            (RcTimerLabel::RC_TIMER_BUILD_CONTOURS_TRACE, "    - Trace"),
+            (RcTimerLabel::RC_TIMER_BUILD_CONTOURS_SIMPLIFY, "    - Simplify"),
+            (RcTimerLabel::RC_TIMER_BUILD_POLYMESH, "- Build Polymesh"),
+            (RcTimerLabel::RC_TIMER_BUILD_POLYMESHDETAIL, "- Build Polymesh Detail"),
+            (RcTimerLabel::RC_TIMER_MERGE_POLYMESH, "- Merge Polymeshes"),
+            (RcTimerLabel::RC_TIMER_MERGE_POLYMESHDETAIL, "- Merge Polymesh Details"),
+        ];
+
+        let total_ms = self.total().unwrap_or_default().as_secs_f32() * 1000.0;
+        writeln!(f, "Build Times")?;
+        for (label, name) in LINES {
+            if let Some(time) = self.get(label) {
+                let ms = time.as_secs_f32() * 1000.0;
+                // No percentage without a total, e.g. a build faster than the timer resolution.
+                if total_ms > 0.0 {
+                    writeln!(f, "{}:\t{:.2}ms\t({:.1}%)", name, ms, ms * 100.0 / total_ms)?;
+                } else {
+                    writeln!(f, "{}:\t{:.2}ms", name, ms)?;
+                }
+            }
+        }
+        write!(f, "=== TOTAL:\t{:.2}ms", total_ms)
+    }
 }
 
 //
@@ -494,6 +684,23 @@ unsafe impl ExternType for RcConfig {
     type Kind = cxx::kind::Trivial;
 }
 
+//
+// RcPartitionType
+//
+
+// The algorithm used to partition the walkable surface into regions.
+// See the comments of rcBuildRegions/rcBuildRegionsMonotone/rcBuildLayerRegions in Recast.h.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RcPartitionType {
+    // Best quality, slowest. Needs the distance field, see `rc_build_regions()`.
+    #[default]
+    Watershed,
+    // Fastest, may produce long thin polygons. See `rc_build_regions_monotone()`.
+    Monotone,
+    // No overlapping regions, good for tiles with small size. See `rc_build_layer_regions()`.
+    Layers,
+}
+
 //
 // RcSpan
 //
@@ -540,7 +747,7 @@ impl RcSpan {
 
     #[inline]
     pub fn set_smax(&mut self, smax: u32) {
-        self.bits = (self.bits & !RC_SPAN_SMAX_MASK) | ((smax << RC_SPAN_SMAX_OFF) | RC_SPAN_SMAX_MASK);
+        self.bits = (self.bits & !RC_SPAN_SMAX_MASK) | ((smax << RC_SPAN_SMAX_OFF) & RC_SPAN_SMAX_MASK);
     }
 
     #[inline]
@@ -604,13 +811,13 @@ unsafe impl ExternType for RcSpanPool {
 #[repr(C)]
 #[derive(Debug)]
 pub struct CxxRcHeightfield {
-    width: i32,
-    height: i32,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
     pub bmin: [f32; 3],
     pub bmax: [f32; 3],
     pub cs: f32,
     pub ch: f32,
-    spans: *mut *mut RcSpan,
+    pub(crate) spans: *mut *mut RcSpan,
     pools: *mut RcSpanPool,
     freelist: *mut RcSpan,
 }
@@ -650,6 +857,8 @@ impl Drop for RcHeightfield {
     }
 }
 
+unsafe impl Send for RcHeightfield {}
+
 impl Default for RcHeightfield {
     fn default() -> Self {
         Self::new()
@@ -715,6 +924,240 @@ impl RcHeightfield {
         }
         Some(unsafe { &mut *span })
     }
+
+    #[inline]
+    fn column_index(&self, x: i32, z: i32) -> Option<usize> {
+        if x < 0 || z < 0 || x >= self.width() || z >= self.height() {
+            return None;
+        }
+        Some((x + z * self.width()) as usize)
+    }
+
+    // The spans of a column, from bottom to top.
+    #[inline]
+    pub fn column(&self, x: i32, z: i32) -> RcSpanIter<'_> {
+        RcSpanIter {
+            span: self.column_index(x, z).and_then(|idx| self.get_span(idx)),
+        }
+    }
+
+    #[inline]
+    pub fn column_mut(&mut self, x: i32, z: i32) -> RcSpanIterMut<'_> {
+        let span = match self.column_index(x, z) {
+            Some(idx) => unsafe { *self.spans.add(idx) },
+            None => ptr::null_mut(),
+        };
+        RcSpanIterMut {
+            span,
+            _marker: PhantomData,
+        }
+    }
+
+    // All columns as `(x, z, spans)`, row by row.
+    #[inline]
+    pub fn columns(&self) -> RcColumnIter<'_> {
+        RcColumnIter { hf: self, idx: 0 }
+    }
+
+    #[inline]
+    pub fn columns_mut(&mut self) -> RcColumnIterMut<'_> {
+        RcColumnIterMut {
+            spans: self.spans,
+            width: self.width(),
+            count: (self.width() * self.height()) as usize,
+            idx: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    // All spans as `(x, z, span)`.
+    pub fn spans(&self) -> impl Iterator<Item = (i32, i32, &RcSpan)> + '_ {
+        self.columns()
+            .flat_map(|(x, z, spans)| spans.map(move |span| (x, z, span)))
+    }
+
+    pub fn spans_mut(&mut self) -> impl Iterator<Item = (i32, i32, &mut RcSpan)> + '_ {
+        self.columns_mut()
+            .flat_map(|(x, z, spans)| spans.map(move |span| (x, z, span)))
+    }
+
+    // Inserts a span into a column, keeping the column sorted. The span is taken from the span pool.
+    // Unlike `rc_add_span()`, spans are never merged, the span must not overlap the spans of the column.
+    pub fn insert_span(&mut self, x: i32, z: i32, smin: u32, smax: u32, area: u32) -> RNResult<()> {
+        let idx = self.column_index(x, z).ok_or(RNError::InvalidParam)?;
+        if smin >= smax || smax > RC_SPAN_MAX_HEIGHT || area > (RC_SPAN_AREA_MASK >> RC_SPAN_AREA_OFF) {
+            return Err(RNError::InvalidParam);
+        }
+
+        let mut prev: *mut RcSpan = ptr::null_mut();
+        let mut cur = unsafe { *self.spans.add(idx) };
+        while !cur.is_null() {
+            let span = unsafe { &*cur };
+            if span.smin() >= smax {
+                break;
+            }
+            if span.smax() > smin {
+                return Err(RNError::InvalidParam);
+            }
+            prev = cur;
+            cur = span.next as *mut RcSpan;
+        }
+
+        let span = unsafe { ffi::rcHeightfieldAllocSpan(self.inner_mut()) };
+        if span.is_null() {
+            return Err(RNError::OutOfMemory);
+        }
+        unsafe {
+            (*span).bits = 0;
+            (*span).set_smin(smin);
+            (*span).set_smax(smax);
+            (*span).set_area(area);
+            (*span).next = cur;
+            if prev.is_null() {
+                *self.spans.add(idx) = span;
+            } else {
+                (*prev).next = span;
+            }
+        }
+        Ok(())
+    }
+
+    // Removes the `n`-th span (from the bottom) of a column and returns it to the span pool.
+    pub fn remove_span(&mut self, x: i32, z: i32, n: usize) -> bool {
+        let mut i = 0;
+        self.retain_column(x, z, |_| {
+            i += 1;
+            i - 1 != n
+        }) > 0
+    }
+
+    // Removes all spans of a column.
+    pub fn clear_column(&mut self, x: i32, z: i32) {
+        self.retain_column(x, z, |_| false);
+    }
+
+    // Keeps the spans for which `f` returns true, e.g. to write a custom filter.
+    // Removed spans are returned to the span pool.
+    pub fn retain_spans<F: FnMut(i32, i32, &mut RcSpan) -> bool>(&mut self, mut f: F) {
+        for z in 0..self.height() {
+            for x in 0..self.width() {
+                self.retain_column(x, z, |span| f(x, z, span));
+            }
+        }
+    }
+
+    // Returns the number of removed spans.
+    fn retain_column<F: FnMut(&mut RcSpan) -> bool>(&mut self, x: i32, z: i32, mut f: F) -> usize {
+        let Some(idx) = self.column_index(x, z) else {
+            return 0;
+        };
+        let mut removed = 0;
+        let mut link: *mut *mut RcSpan = unsafe { self.spans.add(idx) };
+        unsafe {
+            while !(*link).is_null() {
+                let span = *link;
+                if f(&mut *span) {
+                    link = &mut (*span).next as *mut *const RcSpan as *mut *mut RcSpan;
+                } else {
+                    *link = (*span).next as *mut RcSpan;
+                    (*span).next = self.freelist;
+                    self.freelist = span;
+                    removed += 1;
+                }
+            }
+        }
+        removed
+    }
+}
+
+// Iterates the spans of a heightfield column, from bottom to top.
+#[derive(Debug, Clone)]
+pub struct RcSpanIter<'a> {
+    span: Option<&'a RcSpan>,
+}
+
+impl<'a> Iterator for RcSpanIter<'a> {
+    type Item = &'a RcSpan;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a RcSpan> {
+        let span = self.span?;
+        self.span = span.next();
+        Some(span)
+    }
+}
+
+#[derive(Debug)]
+pub struct RcSpanIterMut<'a> {
+    span: *mut RcSpan,
+    _marker: PhantomData<&'a mut RcSpan>,
+}
+
+impl<'a> Iterator for RcSpanIterMut<'a> {
+    type Item = &'a mut RcSpan;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut RcSpan> {
+        if self.span.is_null() {
+            return None;
+        }
+        let span = unsafe { &mut *self.span };
+        self.span = span.next as *mut RcSpan;
+        Some(span)
+    }
+}
+
+// Iterates the columns of a heightfield as `(x, z, spans)`.
+#[derive(Debug, Clone)]
+pub struct RcColumnIter<'a> {
+    hf: &'a RcHeightfield,
+    idx: usize,
+}
+
+impl<'a> Iterator for RcColumnIter<'a> {
+    type Item = (i32, i32, RcSpanIter<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx >= (self.hf.width() * self.hf.height()) as usize {
+            return None;
+        }
+        let idx = self.idx;
+        self.idx += 1;
+        let width = self.hf.width() as usize;
+        let spans = RcSpanIter {
+            span: self.hf.get_span(idx),
+        };
+        Some(((idx % width) as i32, (idx / width) as i32, spans))
+    }
+}
+
+#[derive(Debug)]
+pub struct RcColumnIterMut<'a> {
+    spans: *mut *mut RcSpan,
+    width: i32,
+    count: usize,
+    idx: usize,
+    _marker: PhantomData<&'a mut RcHeightfield>,
+}
+
+impl<'a> Iterator for RcColumnIterMut<'a> {
+    type Item = (i32, i32, RcSpanIterMut<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx >= self.count {
+            return None;
+        }
+        let idx = self.idx;
+        self.idx += 1;
+        let width = self.width as usize;
+        let spans = RcSpanIterMut {
+            span: unsafe { *self.spans.add(idx) },
+            _marker: PhantomData,
+        };
+        Some(((idx % width) as i32, (idx / width) as i32, spans))
+    }
 }
 
 impl Debug for RcHeightfield {
@@ -740,9 +1183,9 @@ unsafe impl ExternType for RcCompactCell {
     type Kind = cxx::kind::Trivial;
 }
 
-const RC_COMPACT_CELL_INDEX_MASK: u32 = 0xFFFFFF;
+pub(crate) const RC_COMPACT_CELL_INDEX_MASK: u32 = 0xFFFFFF;
 const RC_COMPACT_CELL_COUNT_OFF: u32 = 24;
-const RC_COMPACT_CELL_COUNT_MASK: u32 = 0xFF;
+pub(crate) const RC_COMPACT_CELL_COUNT_MASK: u32 = 0xFF;
 
 impl RcCompactCell {
     #[inline]
@@ -762,7 +1205,8 @@ impl RcCompactCell {
 
     #[inline]
     pub fn set_count(&mut self, count: u32) {
-        self.bits = (self.bits & !RC_COMPACT_CELL_COUNT_MASK) | (count << RC_COMPACT_CELL_COUNT_OFF);
+        self.bits = (self.bits & !(RC_COMPACT_CELL_COUNT_MASK << RC_COMPACT_CELL_COUNT_OFF))
+            | ((count & RC_COMPACT_CELL_COUNT_MASK) << RC_COMPACT_CELL_COUNT_OFF);
     }
 }
 
@@ -795,9 +1239,9 @@ unsafe impl ExternType for RcCompactSpan {
     type Kind = cxx::kind::Trivial;
 }
 
-const RC_COMPACT_SPAN_CON_MASK: u32 = 0xFFFFFF;
+pub(crate) const RC_COMPACT_SPAN_CON_MASK: u32 = 0xFFFFFF;
 const RC_COMPACT_SPAN_H_OFF: u32 = 24;
-const RC_COMPACT_SPAN_H_MASK: u32 = 0xFF;
+pub(crate) const RC_COMPACT_SPAN_H_MASK: u32 = 0xFF;
 
 impl RcCompactSpan {
     #[inline]
@@ -817,7 +1261,56 @@ impl RcCompactSpan {
 
     #[inline]
     pub fn set_h(&mut self, h: u32) {
-        self.bits = (self.bits & !RC_COMPACT_SPAN_H_MASK) | (h << RC_COMPACT_SPAN_H_OFF);
+        self.bits = (self.bits & !(RC_COMPACT_SPAN_H_MASK << RC_COMPACT_SPAN_H_OFF))
+            | ((h & RC_COMPACT_SPAN_H_MASK) << RC_COMPACT_SPAN_H_OFF);
+    }
+
+    // The neighbour span index in the cell of direction `dir`, relative to the first span of that cell.
+    #[inline]
+    pub fn get_con(&self, dir: i32) -> Option<u32> {
+        match rc_get_con(self, dir) {
+            RC_NOT_CONNECTED => None,
+            con => Some(con as u32),
+        }
+    }
+}
+
+// Same as rcGetCon(), returns RC_NOT_CONNECTED if the span has no neighbour in the direction.
+#[inline]
+pub fn rc_get_con(span: &RcCompactSpan, dir: i32) -> i32 {
+    let shift = dir * 6;
+    ((span.con() >> shift) & 0x3f) as i32
+}
+
+// Same as rcSetCon(), `i` is the neighbour index relative to the first span of the cell, or RC_NOT_CONNECTED.
+#[inline]
+pub fn rc_set_con(span: &mut RcCompactSpan, dir: i32, i: i32) {
+    let shift = dir * 6;
+    let con = span.con();
+    span.set_con((con & !(0x3f << shift)) | (((i as u32) & 0x3f) << shift));
+}
+
+// Same as rcGetDirOffsetX(), directions are (-1, 0), (0, 1), (1, 0), (0, -1).
+#[inline]
+pub fn rc_get_dir_offset_x(dir: i32) -> i32 {
+    const OFFSET: [i32; 4] = [-1, 0, 1, 0];
+    OFFSET[(dir & 0x03) as usize]
+}
+
+// Same as rcGetDirOffsetY(), the offset along the z-axis.
+#[inline]
+pub fn rc_get_dir_offset_y(dir: i32) -> i32 {
+    const OFFSET: [i32; 4] = [0, 1, 0, -1];
+    OFFSET[(dir & 0x03) as usize]
+}
+
+// Same as rcGetDirForOffset(), returns -1 if the offset is not one of the 4 directions.
+#[inline]
+pub fn rc_get_dir_for_offset(offset_x: i32, offset_z: i32) -> i32 {
+    const DIRS: [i32; 5] = [3, 0, -1, 2, 1];
+    match ((offset_z + 1) << 1) + offset_x {
+        idx @ 0..=4 if offset_x.abs() + offset_z.abs() == 1 => DIRS[idx as usize],
+        _ => -1,
     }
 }
 
@@ -840,9 +1333,9 @@ impl Debug for RcCompactSpan {
 #[repr(C)]
 #[derive(Debug)]
 pub struct CxxRcCompactHeightfield {
-    width: i32,
-    height: i32,
-    span_count: i32,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) span_count: i32,
     pub walkable_height: i32,
     pub walkable_climb: i32,
     pub border_size: i32,
@@ -852,10 +1345,10 @@ pub struct CxxRcCompactHeightfield {
     pub bmax: [f32; 3],
     pub cs: f32,
     pub ch: f32,
-    cells: *mut RcCompactCell,
-    spans: *mut RcCompactSpan,
-    dist: *mut u16,
-    areas: *mut u8,
+    pub(crate) cells: *mut RcCompactCell,
+    pub(crate) spans: *mut RcCompactSpan,
+    pub(crate) dist: *mut u16,
+    pub(crate) areas: *mut u8,
 }
 
 #[cfg(target_pointer_width = "64")]
@@ -893,6 +1386,8 @@ impl Drop for RcCompactHeightfield {
     }
 }
 
+unsafe impl Send for RcCompactHeightfield {}
+
 impl Default for RcCompactHeightfield {
     fn default() -> Self {
         Self::new()
@@ -989,6 +1484,75 @@ impl RcCompactHeightfield {
     pub fn areas_mut(&mut self) -> &mut [u8] {
         return unsafe { slice::from_raw_parts_mut(self.areas, self.span_count()) };
     }
+
+    #[inline]
+    pub fn cell_index(&self, x: i32, z: i32) -> Option<usize> {
+        if x < 0 || z < 0 || x >= self.width() || z >= self.height() {
+            return None;
+        }
+        Some((x + z * self.width()) as usize)
+    }
+
+    // The span indices of a cell, from bottom to top.
+    #[inline]
+    pub fn cell_spans(&self, x: i32, z: i32) -> Range<usize> {
+        match self.cell_index(x, z) {
+            Some(idx) => {
+                let cell = &self.cells()[idx];
+                cell.index() as usize..(cell.index() + cell.count()) as usize
+            }
+            None => 0..0,
+        }
+    }
+
+    // The cell `(x, z)` containing a span.
+    // Scans the cells in O(width * height), the empty ones keep an index of 0 and break the order.
+    // Use `span_indices()` in loops, which gives the cell of each span for free.
+    pub fn span_cell(&self, span_index: usize) -> Option<(i32, i32)> {
+        if span_index >= self.span_count() {
+            return None;
+        }
+        let idx = self.cells().iter().position(|c| {
+            let index = c.index() as usize;
+            c.count() > 0 && index <= span_index && span_index < index + c.count() as usize
+        })?;
+        let width = self.width() as usize;
+        Some(((idx % width) as i32, (idx / width) as i32))
+    }
+
+    // All cells as `(x, z, span indices)`, row by row.
+    pub fn columns(&self) -> impl Iterator<Item = (i32, i32, Range<usize>)> + '_ {
+        let width = self.width() as usize;
+        self.cells().iter().enumerate().map(move |(idx, cell)| {
+            let spans = cell.index() as usize..(cell.index() + cell.count()) as usize;
+            ((idx % width) as i32, (idx / width) as i32, spans)
+        })
+    }
+
+    // All spans as `(x, z, span index)`.
+    pub fn span_indices(&self) -> impl Iterator<Item = (i32, i32, usize)> + '_ {
+        self.columns().flat_map(|(x, z, spans)| spans.map(move |i| (x, z, i)))
+    }
+
+    // The neighbour of the span `span_index` of cell `(x, z)` in direction `dir`, in O(1).
+    // The cell of the neighbour is `(x + rc_get_dir_offset_x(dir), z + rc_get_dir_offset_y(dir))`.
+    #[inline]
+    pub fn neighbour_at(&self, x: i32, z: i32, span_index: usize, dir: i32) -> Option<usize> {
+        let con = self.spans().get(span_index)?.get_con(dir)?;
+        let cell = self.cell_index(x + rc_get_dir_offset_x(dir), z + rc_get_dir_offset_y(dir))?;
+        Some((self.cells()[cell].index() + con) as usize)
+    }
+
+    // The world position of the floor of a span, at the minimum corner of cell `(x, z)`.
+    #[inline]
+    pub fn span_pos(&self, x: i32, z: i32, span_index: usize) -> [f32; 3] {
+        let y = self.spans()[span_index].y;
+        [
+            self.bmin[0] + x as f32 * self.cs,
+            self.bmin[1] + y as f32 * self.ch,
+            self.bmin[2] + z as f32 * self.cs,
+        ]
+    }
 }
 
 impl Debug for RcCompactHeightfield {
@@ -1055,22 +1619,22 @@ impl RcHeightfieldLayer {
 
     #[inline]
     pub fn areas(&self) -> &[u8] {
-        return unsafe { slice::from_raw_parts(self.areas, self.height as usize) };
+        return unsafe { slice::from_raw_parts(self.areas, (self.width * self.height) as usize) };
     }
 
     #[inline]
     pub fn areas_mut(&mut self) -> &mut [u8] {
-        return unsafe { slice::from_raw_parts_mut(self.areas, self.height as usize) };
+        return unsafe { slice::from_raw_parts_mut(self.areas, (self.width * self.height) as usize) };
     }
 
     #[inline]
     pub fn cons(&self) -> &[u8] {
-        return unsafe { slice::from_raw_parts(self.cons, self.height as usize) };
+        return unsafe { slice::from_raw_parts(self.cons, (self.width * self.height) as usize) };
     }
 
     #[inline]
     pub fn cons_mut(&mut self) -> &mut [u8] {
-        return unsafe { slice::from_raw_parts_mut(self.cons, self.height as usize) };
+        return unsafe { slice::from_raw_parts_mut(self.cons, (self.width * self.height) as usize) };
     }
 }
 
@@ -1120,6 +1684,8 @@ impl Drop for RcHeightfieldLayerSet {
     }
 }
 
+unsafe impl Send for RcHeightfieldLayerSet {}
+
 impl Default for RcHeightfieldLayerSet {
     fn default() -> Self {
         Self::new()
@@ -1181,10 +1747,10 @@ impl Debug for RcHeightfieldLayerSet {
 #[repr(C)]
 #[derive(Debug)]
 pub struct RcContour {
-    verts: *mut [i32; 4],
-    nverts: i32,
-    rverts: *mut [i32; 4],
-    nrverts: i32,
+    pub(crate) verts: *mut [i32; 4],
+    pub(crate) nverts: i32,
+    pub(crate) rverts: *mut [i32; 4],
+    pub(crate) nrverts: i32,
     pub reg: u16,
     pub area: u8,
 }
@@ -1239,8 +1805,8 @@ impl RcContour {
 #[repr(C)]
 #[derive(Debug)]
 pub struct CxxRcContourSet {
-    conts: *mut RcContour,
-    nconts: i32,
+    pub(crate) conts: *mut RcContour,
+    pub(crate) nconts: i32,
     pub bmin: [f32; 3],
     pub bmax: [f32; 3],
     pub cs: f32,
@@ -1286,6 +1852,8 @@ impl Drop for RcContourSet {
     }
 }
 
+unsafe impl Send for RcContourSet {}
+
 impl Default for RcContourSet {
     fn default() -> Self {
         Self::new()
@@ -1347,15 +1915,15 @@ impl Debug for RcContourSet {
 #[repr(C)]
 #[derive(Debug)]
 pub struct CxxRcPolyMesh {
-    verts: *mut [u16; 3],
-    polys: *mut u16,
-    regs: *mut u16,
-    flags: *mut u16,
-    areas: *mut u8,
-    nverts: i32,
-    npolys: i32,
-    maxpolys: i32,
-    nvp: i32,
+    pub(crate) verts: *mut [u16; 3],
+    pub(crate) polys: *mut u16,
+    pub(crate) regs: *mut u16,
+    pub(crate) flags: *mut u16,
+    pub(crate) areas: *mut u8,
+    pub(crate) nverts: i32,
+    pub(crate) npolys: i32,
+    pub(crate) maxpolys: i32,
+    pub(crate) nvp: i32,
     pub bmin: [f32; 3],
     pub bmax: [f32; 3],
     pub cs: f32,
@@ -1399,6 +1967,8 @@ impl Drop for RcPolyMesh {
     }
 }
 
+unsafe impl Send for RcPolyMesh {}
+
 impl Default for RcPolyMesh {
     fn default() -> Self {
         Self::new()
@@ -1481,6 +2051,16 @@ impl RcPolyMesh {
         return unsafe { slice::from_raw_parts_mut(self.areas, self.npolys()) };
     }
 
+    #[inline]
+    pub fn areas_and_flags_mut(&mut self) -> (&mut [u8], &mut [u16]) {
+        return unsafe {
+            (
+                slice::from_raw_parts_mut(self.areas, self.npolys()),
+                slice::from_raw_parts_mut(self.flags, self.npolys()),
+            )
+        };
+    }
+
     #[inline]
     pub fn nverts(&self) -> usize {
         self.nverts as usize
@@ -1515,12 +2095,12 @@ impl Debug for RcPolyMesh {
 #[repr(C)]
 #[derive(Debug)]
 pub struct CxxRcPolyMeshDetail {
-    meshes: *mut [u32; 4],
-    verts: *mut [f32; 3],
-    tris: *mut [u8; 4],
-    nmeshes: i32,
-    nverts: i32,
-    ntris: i32,
+    pub(crate) meshes: *mut [u32; 4],
+    pub(crate) verts: *mut [f32; 3],
+    pub(crate) tris: *mut [u8; 4],
+    pub(crate) nmeshes: i32,
+    pub(crate) nverts: i32,
+    pub(crate) ntris: i32,
 }
 
 #[cfg(target_pointer_width = "64")]
@@ -1558,6 +2138,8 @@ impl Drop for RcPolyMeshDetail {
     }
 }
 
+unsafe impl Send for RcPolyMeshDetail {}
+
 impl Default for RcPolyMeshDetail {
     fn default() -> Self {
         Self::new()
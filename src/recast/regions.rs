@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::recast::recast::{RcCompactHeightfield, RC_BORDER_REG};
+
+// The statistics of a region of a `RcCompactHeightfield`, see `rc_region_stats()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcRegionStats {
+    // The region id, including the `RC_BORDER_REG` flag.
+    pub id: u16,
+    // True for the regions built in the border of a tile (`RC_BORDER_REG`), they are dropped by the contours.
+    pub border: bool,
+    // The number of spans, the same measure as `min_region_area` and `merge_region_area`.
+    pub span_count: usize,
+    // The cell bounds on the xz-plane, inclusive. [(x, z)] [Units: vx]
+    pub cmin: [i32; 2],
+    pub cmax: [i32; 2],
+    // The bounds of the span floors, `bmax` includes the last cell. [Units: wu]
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    // The area id of the most spans, the lowest one on ties.
+    pub area: u8,
+    pub area_span_count: usize,
+    // The ids of the regions connected to this one, sorted.
+    pub neighbours: Vec<u16>,
+}
+
+#[derive(Default)]
+struct RegionAccum {
+    span_count: usize,
+    cmin: [i32; 2],
+    cmax: [i32; 2],
+    ymin: u16,
+    ymax: u16,
+    areas: BTreeMap<u8, usize>,
+    neighbours: BTreeSet<u16>,
+}
+
+// Walks the spans of a compact heightfield with regions, e.g. after `rc_build_regions()`, and reports
+// each region sorted by id. The spans without region (id 0) are skipped.
+pub fn rc_region_stats(chf: &RcCompactHeightfield) -> Vec<RcRegionStats> {
+    let spans = chf.spans();
+    let areas = chf.areas();
+    let mut regions: BTreeMap<u16, RegionAccum> = BTreeMap::new();
+    for (x, z, i) in chf.span_indices() {
+        let span = &spans[i];
+        if span.reg == 0 {
+            continue;
+        }
+        let region = regions.entry(span.reg).or_insert_with(|| RegionAccum {
+            cmin: [x, z],
+            cmax: [x, z],
+            ymin: span.y,
+            ymax: span.y,
+            ..Default::default()
+        });
+        region.span_count += 1;
+        region.cmin = [region.cmin[0].min(x), region.cmin[1].min(z)];
+        region.cmax = [region.cmax[0].max(x), region.cmax[1].max(z)];
+        region.ymin = region.ymin.min(span.y);
+        region.ymax = region.ymax.max(span.y);
+        *region.areas.entry(areas[i]).or_default() += 1;
+
+        for dir in 0..4 {
+            if let Some(n) = chf.neighbour_at(x, z, i, dir) {
+                let reg = spans[n].reg;
+                if reg != 0 && reg != span.reg {
+                    region.neighbours.insert(reg);
+                }
+            }
+        }
+    }
+
+    regions
+        .into_iter()
+        .map(|(id, region)| {
+            // max_by_key() keeps the last maximum, so the areas are iterated from the highest id.
+            let (area, area_span_count) = region
+                .areas
+                .iter()
+                .rev()
+                .max_by_key(|(_, count)| **count)
+                .map(|(area, count)| (*area, *count))
+                .unwrap_or_default();
+            RcRegionStats {
+                id,
+                border: id & RC_BORDER_REG != 0,
+                span_count: region.span_count,
+                cmin: region.cmin,
+                cmax: region.cmax,
+                bmin: [
+                    chf.bmin[0] + region.cmin[0] as f32 * chf.cs,
+                    chf.bmin[1] + region.ymin as f32 * chf.ch,
+                    chf.bmin[2] + region.cmin[1] as f32 * chf.cs,
+                ],
+                bmax: [
+                    chf.bmin[0] + (region.cmax[0] + 1) as f32 * chf.cs,
+                    chf.bmin[1] + region.ymax as f32 * chf.ch,
+                    chf.bmin[2] + (region.cmax[1] + 1) as f32 * chf.cs,
+                ],
+                area,
+                area_span_count,
+                neighbours: region.neighbours.into_iter().collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,147 @@
+use crate::error::{RNError, RNResult};
+use crate::recast::recast::RcConfig;
+
+// The maximum number of vertices per polygon supported by detour (DT_VERTS_PER_POLYGON).
+const MAX_VERTS_PER_POLY: i32 = 6;
+
+// Build settings in world units, with the same meaning as the RecastDemo sliders.
+// Use `to_config()` to get the voxel based `RcConfig` used by recast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcBuildSettings {
+    // The xz-plane cell size. [Limit: > 0] [Units: wu]
+    pub cell_size: f32,
+
+    // The y-axis cell size. [Limit: > 0] [Units: wu]
+    pub cell_height: f32,
+
+    // The height of the agent. [Limit: >= 3 * cell_height] [Units: wu]
+    pub agent_height: f32,
+
+    // The radius of the agent. [Limit: >= 0] [Units: wu]
+    pub agent_radius: f32,
+
+    // The maximum ledge height the agent can climb. [Limit: >= 0] [Units: wu]
+    pub agent_max_climb: f32,
+
+    // The maximum slope the agent can walk on. [Limits: 0 <= value < 90] [Units: Degrees]
+    pub agent_max_slope: f32,
+
+    // The side length of the smallest allowed isolated region. [Limit: >= 0] [Units: vx]
+    pub region_min_size: f32,
+
+    // The side length under which regions are merged into larger ones. [Limit: >= 0] [Units: vx]
+    pub region_merge_size: f32,
+
+    // The maximum length of contour edges along the border of the mesh. [Limit: >= 0] [Units: wu]
+    pub edge_max_len: f32,
+
+    // The maximum distance a simplified contour's border edges should deviate from the raw contour.
+    // [Limit: >= 0] [Units: vx]
+    pub edge_max_error: f32,
+
+    // The maximum number of vertices per polygon. [Limits: 3 <= value <= 6]
+    pub verts_per_poly: i32,
+
+    // The detail mesh sampling distance, values below 0.9 disable sampling. [Limit: >= 0] [Units: cell_size]
+    pub detail_sample_dist: f32,
+
+    // The maximum distance the detail mesh surface should deviate from the heightfield.
+    // [Limit: >= 0] [Units: cell_height]
+    pub detail_sample_max_error: f32,
+
+    // The width/height of tiles on the xz-plane, 0 for a single tile mesh. [Limit: >= 0] [Units: vx]
+    pub tile_size: i32,
+}
+
+impl Default for RcBuildSettings {
+    // The RecastDemo defaults.
+    fn default() -> RcBuildSettings {
+        RcBuildSettings {
+            cell_size: 0.3,
+            cell_height: 0.2,
+            agent_height: 2.0,
+            agent_radius: 0.6,
+            agent_max_climb: 0.9,
+            agent_max_slope: 45.0,
+            region_min_size: 8.0,
+            region_merge_size: 20.0,
+            edge_max_len: 12.0,
+            edge_max_error: 1.3,
+            verts_per_poly: 6,
+            detail_sample_dist: 6.0,
+            detail_sample_max_error: 1.0,
+            tile_size: 0,
+        }
+    }
+}
+
+impl RcBuildSettings {
+    // Converts to an `RcConfig`, the same way as RecastDemo.
+    // Bounds, grid size and border size are left to the builders.
+    pub fn to_config(&self) -> RNResult<RcConfig> {
+        self.validate()?;
+
+        let mut cfg = RcConfig {
+            cs: self.cell_size,
+            ch: self.cell_height,
+            walkable_slope_angle: self.agent_max_slope,
+            walkable_height: (self.agent_height / self.cell_height).ceil() as i32,
+            walkable_climb: (self.agent_max_climb / self.cell_height).floor() as i32,
+            walkable_radius: (self.agent_radius / self.cell_size).floor() as i32,
+            max_edge_len: (self.edge_max_len / self.cell_size) as i32,
+            max_simplification_error: self.edge_max_error,
+            min_region_area: (self.region_min_size * self.region_min_size) as i32,
+            merge_region_area: (self.region_merge_size * self.region_merge_size) as i32,
+            max_verts_per_poly: self.verts_per_poly,
+            tile_size: self.tile_size,
+            detail_sample_dist: 0.0,
+            detail_sample_max_error: self.cell_height * self.detail_sample_max_error,
+            ..RcConfig::default()
+        };
+        if self.detail_sample_dist >= 0.9 {
+            cfg.detail_sample_dist = self.cell_size * self.detail_sample_dist;
+        }
+        if cfg.walkable_height < 3 {
+            return Err(RNError::InvalidSettings(
+                "agent_height must be at least 3 * cell_height",
+            ));
+        }
+        Ok(cfg)
+    }
+
+    // Same as `to_config()` for a tiled mesh, `tile_size` must be > 0.
+    pub fn to_tiled_config(&self) -> RNResult<RcConfig> {
+        if self.tile_size <= 0 {
+            return Err(RNError::InvalidSettings("tile_size must be > 0 for a tiled mesh"));
+        }
+        self.to_config()
+    }
+
+    fn validate(&self) -> RNResult<()> {
+        let check = |ok: bool, msg: &'static str| if ok { Ok(()) } else { Err(RNError::InvalidSettings(msg)) };
+        check(self.cell_size > 0.0, "cell_size must be > 0")?;
+        check(self.cell_height > 0.0, "cell_height must be > 0")?;
+        check(self.agent_height > 0.0, "agent_height must be > 0")?;
+        check(self.agent_radius >= 0.0, "agent_radius must be >= 0")?;
+        check(self.agent_max_climb >= 0.0, "agent_max_climb must be >= 0")?;
+        check(
+            (0.0..90.0).contains(&self.agent_max_slope),
+            "agent_max_slope must be in [0, 90) degrees",
+        )?;
+        check(self.region_min_size >= 0.0, "region_min_size must be >= 0")?;
+        check(self.region_merge_size >= 0.0, "region_merge_size must be >= 0")?;
+        check(self.edge_max_len >= 0.0, "edge_max_len must be >= 0")?;
+        check(self.edge_max_error >= 0.0, "edge_max_error must be >= 0")?;
+        check(
+            (3..=MAX_VERTS_PER_POLY).contains(&self.verts_per_poly),
+            "verts_per_poly must be in [3, 6]",
+        )?;
+        check(self.detail_sample_dist >= 0.0, "detail_sample_dist must be >= 0")?;
+        check(
+            self.detail_sample_max_error >= 0.0,
+            "detail_sample_max_error must be >= 0",
+        )?;
+        check(self.tile_size >= 0, "tile_size must be >= 0")?;
+        Ok(())
+    }
+}
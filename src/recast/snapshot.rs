@@ -0,0 +1,443 @@
+use std::mem;
+use std::ptr;
+
+use crate::error::{RNError, RNResult};
+use crate::recast::recast::*;
+
+// Owned copies of the intermediate recast structures, serializable with rkyv (feature "rkyv").
+//
+// `from_xxx()` copies a structure, `to_xxx()` rebuilds it with the C++ allocator, so a pipeline can be resumed
+// from a cached stage, e.g. rebuild regions, contours and meshes from a cached rasterized heightfield.
+
+// Copies a slice into memory owned by recast (freed by rcFree()), padded with `fill` up to `len` items.
+fn alloc_copy<T: Copy>(data: &[T], len: usize, fill: T) -> RNResult<*mut T> {
+    debug_assert!(data.len() <= len);
+    if len == 0 {
+        return Ok(ptr::null_mut());
+    }
+    let buf = unsafe { ffi::rcAllocBytes(len * mem::size_of::<T>()) } as *mut T;
+    if buf.is_null() {
+        return Err(RNError::OutOfMemory);
+    }
+    unsafe {
+        ptr::copy_nonoverlapping(data.as_ptr(), buf, data.len());
+        for i in data.len()..len {
+            buf.add(i).write(fill);
+        }
+    }
+    Ok(buf)
+}
+
+// The number of columns of a `width * height` grid.
+fn grid_size(width: i32, height: i32) -> RNResult<usize> {
+    if width < 0 || height < 0 {
+        return Err(RNError::InvalidParam);
+    }
+    (width as usize)
+        .checked_mul(height as usize)
+        .ok_or(RNError::InvalidParam)
+}
+
+// True if `start + len` is in `0..=size`.
+fn in_range(start: usize, len: usize, size: usize) -> bool {
+    start.checked_add(len).is_some_and(|end| end <= size)
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcHeightfieldData {
+    pub width: i32,
+    pub height: i32,
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    pub cs: f32,
+    pub ch: f32,
+    // The number of spans of each column. [Size: width * height]
+    pub counts: Vec<u16>,
+    // The spans `(smin, smax, area)`, column by column, from bottom to top.
+    pub spans: Vec<[u32; 3]>,
+}
+
+impl RcHeightfieldData {
+    pub fn from_heightfield(hf: &RcHeightfield) -> RcHeightfieldData {
+        let mut data = RcHeightfieldData {
+            width: hf.width(),
+            height: hf.height(),
+            bmin: hf.bmin,
+            bmax: hf.bmax,
+            cs: hf.cs,
+            ch: hf.ch,
+            counts: Vec::with_capacity((hf.width() * hf.height()) as usize),
+            spans: Vec::new(),
+        };
+        for (_, _, spans) in hf.columns() {
+            let count = data.spans.len();
+            data.spans
+                .extend(spans.map(|span| [span.smin(), span.smax(), span.area()]));
+            data.counts.push((data.spans.len() - count) as u16);
+        }
+        data
+    }
+
+    pub fn to_heightfield(&self) -> RNResult<RcHeightfield> {
+        let columns = grid_size(self.width, self.height)?;
+        let span_count: usize = self.counts.iter().map(|c| *c as usize).sum();
+        if self.counts.len() != columns || self.spans.len() != span_count {
+            return Err(RNError::InvalidParam);
+        }
+
+        let mut hf = RcHeightfield::new();
+        hf.spans = alloc_copy(&[], columns, ptr::null_mut())?;
+        hf.width = self.width;
+        hf.height = self.height;
+        hf.bmin = self.bmin;
+        hf.bmax = self.bmax;
+        hf.cs = self.cs;
+        hf.ch = self.ch;
+
+        let mut spans = self.spans.iter();
+        for (idx, count) in self.counts.iter().enumerate() {
+            let (x, z) = (idx as i32 % self.width, idx as i32 / self.width);
+            for [smin, smax, area] in spans.by_ref().take(*count as usize) {
+                hf.insert_span(x, z, *smin, *smax, *area)?;
+            }
+        }
+        Ok(hf)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcCompactHeightfieldData {
+    pub width: i32,
+    pub height: i32,
+    pub walkable_height: i32,
+    pub walkable_climb: i32,
+    pub border_size: i32,
+    pub max_distance: u16,
+    pub max_regions: u16,
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    pub cs: f32,
+    pub ch: f32,
+    // The cells `(index, count)`. [Size: width * height]
+    pub cells: Vec<[u32; 2]>,
+    // The spans `(y, reg, con, h)`.
+    pub spans: Vec<[u32; 4]>,
+    // Empty if the distance field is not built, otherwise same size as `spans`.
+    pub dist: Vec<u16>,
+    pub areas: Vec<u8>,
+}
+
+impl RcCompactHeightfieldData {
+    pub fn from_compact_heightfield(chf: &RcCompactHeightfield) -> RcCompactHeightfieldData {
+        RcCompactHeightfieldData {
+            width: chf.width(),
+            height: chf.height(),
+            walkable_height: chf.walkable_height,
+            walkable_climb: chf.walkable_climb,
+            border_size: chf.border_size,
+            max_distance: chf.max_distance,
+            max_regions: chf.max_regions,
+            bmin: chf.bmin,
+            bmax: chf.bmax,
+            cs: chf.cs,
+            ch: chf.ch,
+            cells: chf.cells().iter().map(|c| [c.index(), c.count()]).collect(),
+            spans: chf
+                .spans()
+                .iter()
+                .map(|s| [s.y as u32, s.reg as u32, s.con(), s.h()])
+                .collect(),
+            dist: chf.dist().to_vec(),
+            areas: chf.areas().to_vec(),
+        }
+    }
+
+    // Checks the cell ranges and the span connections, the C++ functions index the spans with them unchecked.
+    pub fn to_compact_heightfield(&self) -> RNResult<RcCompactHeightfield> {
+        let columns = grid_size(self.width, self.height)?;
+        let span_count = self.spans.len();
+        if self.cells.len() != columns
+            || span_count > RC_COMPACT_CELL_INDEX_MASK as usize
+            || self.areas.len() != span_count
+            || !(self.dist.is_empty() || self.dist.len() == span_count)
+        {
+            return Err(RNError::InvalidParam);
+        }
+        for (idx, [index, count]) in self.cells.iter().enumerate() {
+            if *count > RC_COMPACT_CELL_COUNT_MASK || !in_range(*index as usize, *count as usize, span_count) {
+                return Err(RNError::InvalidParam);
+            }
+            let (x, z) = ((idx % self.width as usize) as i32, (idx / self.width as usize) as i32);
+            for [y, reg, con, h] in &self.spans[*index as usize..(*index + *count) as usize] {
+                if *y > u16::MAX as u32
+                    || *reg > u16::MAX as u32
+                    || *con > RC_COMPACT_SPAN_CON_MASK
+                    || *h > RC_COMPACT_SPAN_H_MASK
+                {
+                    return Err(RNError::InvalidParam);
+                }
+                for dir in 0..4 {
+                    let con = (con >> (dir * 6)) & 0x3f;
+                    if con == RC_NOT_CONNECTED as u32 {
+                        continue;
+                    }
+                    let (nx, nz) = (x + rc_get_dir_offset_x(dir), z + rc_get_dir_offset_y(dir));
+                    if nx < 0 || nz < 0 || nx >= self.width || nz >= self.height {
+                        return Err(RNError::InvalidParam);
+                    }
+                    if con >= self.cells[(nx + nz * self.width) as usize][1] {
+                        return Err(RNError::InvalidParam);
+                    }
+                }
+            }
+        }
+
+        let mut chf = RcCompactHeightfield::new();
+        chf.width = self.width;
+        chf.height = self.height;
+        chf.span_count = span_count as i32;
+        chf.walkable_height = self.walkable_height;
+        chf.walkable_climb = self.walkable_climb;
+        chf.border_size = self.border_size;
+        chf.max_distance = self.max_distance;
+        chf.max_regions = self.max_regions;
+        chf.bmin = self.bmin;
+        chf.bmax = self.bmax;
+        chf.cs = self.cs;
+        chf.ch = self.ch;
+
+        chf.cells = alloc_copy::<u32>(&[], columns, 0)? as *mut RcCompactCell;
+        for (cell, [index, count]) in chf.cells_mut().iter_mut().zip(&self.cells) {
+            cell.set_index(*index);
+            cell.set_count(*count);
+        }
+        if span_count == 0 {
+            return Ok(chf);
+        }
+        chf.spans = alloc_copy::<u64>(&[], span_count, 0)? as *mut RcCompactSpan;
+        for (span, [y, reg, con, h]) in chf.spans_mut().iter_mut().zip(&self.spans) {
+            span.y = *y as u16;
+            span.reg = *reg as u16;
+            span.set_con(*con);
+            span.set_h(*h);
+        }
+        if !self.dist.is_empty() {
+            chf.dist = alloc_copy(&self.dist, span_count, 0)?;
+        }
+        chf.areas = alloc_copy(&self.areas, span_count, RC_NULL_AREA)?;
+        Ok(chf)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcContourData {
+    pub verts: Vec<[i32; 4]>,
+    pub rverts: Vec<[i32; 4]>,
+    pub reg: u16,
+    pub area: u8,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcContourSetData {
+    pub conts: Vec<RcContourData>,
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    pub cs: f32,
+    pub ch: f32,
+    pub width: i32,
+    pub height: i32,
+    pub border_size: i32,
+    pub max_error: f32,
+}
+
+impl RcContourSetData {
+    pub fn from_contour_set(cset: &RcContourSet) -> RcContourSetData {
+        RcContourSetData {
+            conts: cset
+                .conts()
+                .iter()
+                .map(|c| RcContourData {
+                    verts: c.verts().to_vec(),
+                    rverts: c.rverts().to_vec(),
+                    reg: c.reg,
+                    area: c.area,
+                })
+                .collect(),
+            bmin: cset.bmin,
+            bmax: cset.bmax,
+            cs: cset.cs,
+            ch: cset.ch,
+            width: cset.width,
+            height: cset.height,
+            border_size: cset.border_size,
+            max_error: cset.max_error,
+        }
+    }
+
+    pub fn to_contour_set(&self) -> RNResult<RcContourSet> {
+        let mut cset = RcContourSet::new();
+        cset.bmin = self.bmin;
+        cset.bmax = self.bmax;
+        cset.cs = self.cs;
+        cset.ch = self.ch;
+        cset.width = self.width;
+        cset.height = self.height;
+        cset.border_size = self.border_size;
+        cset.max_error = self.max_error;
+
+        if self.conts.is_empty() {
+            return Ok(cset);
+        }
+        // Zeroed contours, so rcFreeContourSet() can release a partially copied set.
+        cset.conts = alloc_copy::<u8>(&[], self.conts.len() * mem::size_of::<RcContour>(), 0)? as *mut RcContour;
+        cset.nconts = self.conts.len() as i32;
+        for (cont, data) in cset.conts_mut().iter_mut().zip(&self.conts) {
+            cont.verts = alloc_copy(&data.verts, data.verts.len(), [0; 4])?;
+            cont.nverts = data.verts.len() as i32;
+            cont.rverts = alloc_copy(&data.rverts, data.rverts.len(), [0; 4])?;
+            cont.nrverts = data.rverts.len() as i32;
+            cont.reg = data.reg;
+            cont.area = data.area;
+        }
+        Ok(cset)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcPolyMeshData {
+    pub verts: Vec<[u16; 3]>,
+    // [Size: npolys * 2 * nvp]
+    pub polys: Vec<u16>,
+    pub regs: Vec<u16>,
+    pub flags: Vec<u16>,
+    pub areas: Vec<u8>,
+    pub maxpolys: usize,
+    pub nvp: usize,
+    pub bmin: [f32; 3],
+    pub bmax: [f32; 3],
+    pub cs: f32,
+    pub ch: f32,
+    pub border_size: i32,
+    pub max_edge_error: f32,
+}
+
+impl RcPolyMeshData {
+    pub fn from_poly_mesh(pmesh: &RcPolyMesh) -> RcPolyMeshData {
+        RcPolyMeshData {
+            verts: pmesh.verts().to_vec(),
+            polys: pmesh.polys().to_vec(),
+            regs: pmesh.regs().to_vec(),
+            flags: pmesh.flags().to_vec(),
+            areas: pmesh.areas().to_vec(),
+            maxpolys: pmesh.maxpolys(),
+            nvp: pmesh.nvp(),
+            bmin: pmesh.bmin,
+            bmax: pmesh.bmax,
+            cs: pmesh.cs,
+            ch: pmesh.ch,
+            border_size: pmesh.border_size,
+            max_edge_error: pmesh.max_edge_error,
+        }
+    }
+
+    // Checks the vertex and neighbour indices of the polys, the C++ functions use them unchecked.
+    pub fn to_poly_mesh(&self) -> RNResult<RcPolyMesh> {
+        let npolys = self.regs.len();
+        let nvp = self.nvp;
+        let capacity = self.maxpolys.checked_mul(2 * nvp).ok_or(RNError::InvalidParam)?;
+        if nvp < 3
+            || npolys > self.maxpolys
+            || self.verts.len() >= RC_MESH_NULL_IDX as usize
+            || self.polys.len() != npolys * 2 * nvp
+            || self.flags.len() != npolys
+            || self.areas.len() != npolys
+        {
+            return Err(RNError::InvalidParam);
+        }
+        for poly in self.polys.chunks_exact(2 * nvp) {
+            let (verts, neis) = poly.split_at(nvp);
+            let count = verts.iter().position(|v| *v == RC_MESH_NULL_IDX).unwrap_or(nvp);
+            if count < 3
+                || verts[..count].iter().any(|v| *v as usize >= self.verts.len())
+                || verts[count..].iter().any(|v| *v != RC_MESH_NULL_IDX)
+            {
+                return Err(RNError::InvalidParam);
+            }
+            // No neighbour, a portal to a tile side `0x8000 | dir`, or a poly index.
+            let valid_nei = |nei: u16| {
+                nei == RC_MESH_NULL_IDX || (nei & 0x8000 != 0 && nei & 0x7fff <= 3) || (nei as usize) < npolys
+            };
+            if !neis.iter().all(|nei| valid_nei(*nei)) {
+                return Err(RNError::InvalidParam);
+            }
+        }
+
+        let mut pmesh = RcPolyMesh::new();
+        pmesh.bmin = self.bmin;
+        pmesh.bmax = self.bmax;
+        pmesh.cs = self.cs;
+        pmesh.ch = self.ch;
+        pmesh.border_size = self.border_size;
+        pmesh.max_edge_error = self.max_edge_error;
+
+        // Same capacities as rcBuildPolyMesh().
+        pmesh.verts = alloc_copy(&self.verts, self.verts.len(), [0; 3])?;
+        pmesh.nverts = self.verts.len() as i32;
+        pmesh.polys = alloc_copy(&self.polys, capacity, RC_MESH_NULL_IDX)?;
+        pmesh.regs = alloc_copy(&self.regs, self.maxpolys, 0)?;
+        pmesh.flags = alloc_copy(&self.flags, self.maxpolys, 0)?;
+        pmesh.areas = alloc_copy(&self.areas, self.maxpolys, 0)?;
+        pmesh.npolys = npolys as i32;
+        pmesh.maxpolys = self.maxpolys as i32;
+        pmesh.nvp = self.nvp as i32;
+        Ok(pmesh)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct RcPolyMeshDetailData {
+    pub meshes: Vec<[u32; 4]>,
+    pub verts: Vec<[f32; 3]>,
+    pub tris: Vec<[u8; 4]>,
+}
+
+impl RcPolyMeshDetailData {
+    pub fn from_poly_mesh_detail(dmesh: &RcPolyMeshDetail) -> RcPolyMeshDetailData {
+        RcPolyMeshDetailData {
+            meshes: dmesh.meshes().to_vec(),
+            verts: dmesh.verts().to_vec(),
+            tris: dmesh.tris().to_vec(),
+        }
+    }
+
+    // Checks the vertex and triangle ranges of the sub-meshes, the C++ functions use them unchecked.
+    pub fn to_poly_mesh_detail(&self) -> RNResult<RcPolyMeshDetail> {
+        for [vbase, nv, tbase, nt] in &self.meshes {
+            let (vbase, nv, tbase, nt) = (*vbase as usize, *nv as usize, *tbase as usize, *nt as usize);
+            if !in_range(vbase, nv, self.verts.len()) || !in_range(tbase, nt, self.tris.len()) {
+                return Err(RNError::InvalidParam);
+            }
+            if self.tris[tbase..tbase + nt]
+                .iter()
+                .any(|t| t[..3].iter().any(|i| *i as usize >= nv))
+            {
+                return Err(RNError::InvalidParam);
+            }
+        }
+
+        let mut dmesh = RcPolyMeshDetail::new();
+        dmesh.meshes = alloc_copy(&self.meshes, self.meshes.len(), [0; 4])?;
+        dmesh.nmeshes = self.meshes.len() as i32;
+        dmesh.verts = alloc_copy(&self.verts, self.verts.len(), [0.0; 3])?;
+        dmesh.nverts = self.verts.len() as i32;
+        dmesh.tris = alloc_copy(&self.tris, self.tris.len(), [0; 4])?;
+        dmesh.ntris = self.tris.len() as i32;
+        Ok(dmesh)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::detour::{DtBuf, DtNavMesh, OffMeshConnectionSet};
+use crate::error::{RNError, RNResult, RcBuildStage};
+use crate::recast::geometry::InputGeometry;
+use crate::recast::pipeline::{
+    at_stage, build_poly_mesh, check_geometry, create_nav_mesh_data, rasterize_triangles, RcPolyFlagsFn,
+};
+use crate::recast::recast::*;
+use crate::recast::settings::RcBuildSettings;
+use crate::recast::volumes::ConvexVolumeSet;
+
+// Builds a single tile navigation mesh from a triangle soup, the same way as RecastDemo's Sample_SoloMesh.
+//
+// The bounds and grid size of `cfg` are computed from the input geometry, the other fields are used as is.
+// If the geometry has no area ids, triangles are marked walkable by `cfg.walkable_slope_angle`.
+pub struct SoloMeshBuilder<'t, G: InputGeometry + ?Sized + 't = dyn InputGeometry + 't> {
+    pub cfg: RcConfig,
+    pub geometry: &'t G,
+
+    // Agent dimensions stored in the detour data. [Units: wu]
+    pub walkable_height: f32,
+    pub walkable_radius: f32,
+    pub walkable_climb: f32,
+
+    // The region partitioning algorithm. Defaults to `RcPartitionType::Watershed`.
+    pub partition: RcPartitionType,
+
+    // Volumes marking areas of the walkable surface, applied after erosion.
+    pub convex_volumes: Option<&'t ConvexVolumeSet>,
+
+    // Off-mesh connections, all stored in the single mesh. Those starting outside the mesh bounds are dropped.
+    pub off_mesh_connections: Option<&'t OffMeshConnectionSet>,
+
+    pub build_bv_tree: bool,
+
+    // Sets poly areas and flags before creating detour data. If None, all polys get flags 1.
+    pub poly_flags: Option<&'t RcPolyFlagsFn>,
+}
+
+impl<'t, G: InputGeometry + ?Sized> SoloMeshBuilder<'t, G> {
+    // Agent dimensions are derived from the voxel values of `cfg`.
+    pub fn new(cfg: RcConfig, geometry: &'t G) -> SoloMeshBuilder<'t, G> {
+        SoloMeshBuilder {
+            walkable_height: cfg.walkable_height as f32 * cfg.ch,
+            walkable_radius: cfg.walkable_radius as f32 * cfg.cs,
+            walkable_climb: cfg.walkable_climb as f32 * cfg.ch,
+            cfg,
+            geometry,
+            partition: RcPartitionType::Watershed,
+            convex_volumes: None,
+            off_mesh_connections: None,
+            build_bv_tree: true,
+            poly_flags: None,
+        }
+    }
+
+    // Agent dimensions are taken from `settings` as is.
+    pub fn from_settings(settings: &RcBuildSettings, geometry: &'t G) -> RNResult<SoloMeshBuilder<'t, G>> {
+        let mut builder = SoloMeshBuilder::new(settings.to_config()?, geometry);
+        builder.walkable_height = settings.agent_height;
+        builder.walkable_radius = settings.agent_radius;
+        builder.walkable_climb = settings.agent_max_climb;
+        Ok(builder)
+    }
+
+    // Returns the config actually used by the build, with bounds and grid size filled.
+    pub fn build_config(&self) -> RcConfig {
+        let mut cfg = self.cfg.clone();
+        let (bmin, bmax) = self.geometry.bounds();
+        cfg.bmin = bmin;
+        cfg.bmax = bmax;
+        let (width, height) = rc_calc_grid_size(&cfg.bmin, &cfg.bmax, cfg.cs);
+        cfg.width = width;
+        cfg.height = height;
+        cfg
+    }
+
+    pub fn build_data(&self, ctx: &mut RcContext) -> RNResult<DtBuf> {
+        check_geometry(self.geometry)?;
+        let verts = self.geometry.verts();
+        let tris = self.geometry.tris();
+        let cfg = self.build_config();
+
+        let mut solid = RcHeightfield::new();
+        at_stage(
+            rc_create_heightfield(
+                ctx, &mut solid, cfg.width, cfg.height, &cfg.bmin, &cfg.bmax, cfg.cs, cfg.ch,
+            ),
+            RcBuildStage::Heightfield,
+        )?;
+
+        let mut triareas = Vec::new();
+        rasterize_triangles(
+            ctx,
+            &cfg,
+            &mut solid,
+            verts,
+            tris,
+            self.geometry.tri_areas(),
+            &mut triareas,
+        )?;
+
+        let (mut pmesh, dmesh) = match build_poly_mesh(ctx, &cfg, self.partition, self.convex_volumes, &mut solid)? {
+            Some(meshes) => meshes,
+            None => return Err(RNError::BuildFailed(RcBuildStage::Contours)),
+        };
+
+        let off_mesh = self.off_mesh_connections.map(|set| set.columns());
+        create_nav_mesh_data(
+            &cfg,
+            &mut pmesh,
+            &dmesh,
+            self.walkable_height,
+            self.walkable_radius,
+            self.walkable_climb,
+            0,
+            0,
+            self.build_bv_tree,
+            self.poly_flags,
+            off_mesh.as_ref(),
+        )
+    }
+
+    pub fn build(&self, ctx: &mut RcContext) -> RNResult<DtNavMesh> {
+        let data = self.build_data(ctx)?;
+        at_stage(DtNavMesh::with_data(data), RcBuildStage::NavMesh)
+    }
+}
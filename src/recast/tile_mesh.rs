@@ -0,0 +1,669 @@
+use crate::demo::{rc_create_chunky_tri_mesh, rc_get_chunks_overlapping_rect, RcChunkyTriMesh};
+use crate::detour::{DtBuf, DtNavMesh, DtNavMeshParams, DtTileRef, OffMeshConnectionSet};
+use crate::detour_tile_cache::{dt_build_tile_cache_layers, DtTileCacheCompressor, DtTileCacheParams};
+use crate::error::{RNError, RNResult, RcBuildStage};
+use crate::recast::build_cache::{StableHasher, TileBuildCache, TileCacheStats};
+use crate::recast::geometry::InputGeometry;
+use crate::recast::pipeline::{
+    at_stage, build_heightfield_layers, build_poly_mesh, check_geometry, create_nav_mesh_data, rasterize_triangles,
+    RcPolyFlagsFn,
+};
+use crate::recast::recast::*;
+use crate::recast::settings::RcBuildSettings;
+use crate::recast::volumes::ConvexVolumeSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+const TRIS_PER_CHUNK: i32 = 256;
+
+// The timings of a tile built by `TileMeshBuilder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcTileTimings {
+    pub tx: i32,
+    pub ty: i32,
+    pub timings: RcTimings,
+}
+
+// Builds a tiled navigation mesh from a triangle soup, the same way as RecastDemo's Sample_TileMesh.
+//
+// `cfg.tile_size` must be set. The bounds, grid size and border size of `cfg` are computed per tile,
+// the other fields are used as is. If the geometry has no area ids, triangles are marked walkable
+// by `cfg.walkable_slope_angle`.
+pub struct TileMeshBuilder<'t> {
+    pub cfg: RcConfig,
+    verts: &'t [[f32; 3]],
+    chunky_mesh: RcChunkyTriMesh,
+    // Area ids in the triangle order of the chunky mesh.
+    chunky_areas: Option<Vec<u8>>,
+    bmin: [f32; 3],
+    bmax: [f32; 3],
+    tiles_x: i32,
+    tiles_y: i32,
+
+    // Agent dimensions stored in the detour data. [Units: wu]
+    pub walkable_height: f32,
+    pub walkable_radius: f32,
+    pub walkable_climb: f32,
+
+    // The region partitioning algorithm. Defaults to `RcPartitionType::Watershed`.
+    pub partition: RcPartitionType,
+
+    // Volumes marking areas of the walkable surface, applied after erosion.
+    pub convex_volumes: Option<&'t ConvexVolumeSet>,
+
+    // Off-mesh connections, each one is stored in the tile containing its start point.
+    pub off_mesh_connections: Option<&'t OffMeshConnectionSet>,
+
+    pub build_bv_tree: bool,
+
+    // Sets poly areas and flags before creating detour data. If None, all polys get flags 1.
+    pub poly_flags: Option<&'t RcPolyFlagsFn>,
+
+    // Mixed into `tile_hash()`, change it when `poly_flags` or anything else outside the builder changes
+    // the tile data, e.g. to a version of the flag rules. Defaults to 0.
+    pub cache_salt: u64,
+}
+
+impl<'t> TileMeshBuilder<'t> {
+    // Agent dimensions are derived from the voxel values of `cfg`.
+    pub fn new<G: InputGeometry + ?Sized>(cfg: RcConfig, geometry: &'t G) -> RNResult<TileMeshBuilder<'t>> {
+        check_geometry(geometry)?;
+        if cfg.tile_size <= 0 || cfg.cs <= 0.0 {
+            return Err(RNError::BuildFailed(RcBuildStage::Input));
+        }
+        let (chunky_mesh, chunky_areas) = at_stage(create_chunky_mesh(geometry), RcBuildStage::Input)?;
+
+        let (bmin, bmax) = geometry.bounds();
+        let (grid_width, grid_height) = rc_calc_grid_size(&bmin, &bmax, cfg.cs);
+        let ts = cfg.tile_size;
+        Ok(TileMeshBuilder {
+            walkable_height: cfg.walkable_height as f32 * cfg.ch,
+            walkable_radius: cfg.walkable_radius as f32 * cfg.cs,
+            walkable_climb: cfg.walkable_climb as f32 * cfg.ch,
+            cfg,
+            verts: geometry.verts(),
+            chunky_mesh,
+            chunky_areas,
+            bmin,
+            bmax,
+            tiles_x: (grid_width + ts - 1) / ts,
+            tiles_y: (grid_height + ts - 1) / ts,
+            partition: RcPartitionType::Watershed,
+            convex_volumes: None,
+            off_mesh_connections: None,
+            build_bv_tree: true,
+            poly_flags: None,
+            cache_salt: 0,
+        })
+    }
+
+    // Agent dimensions are taken from `settings` as is. `settings.tile_size` must be set.
+    pub fn from_settings<G: InputGeometry + ?Sized>(
+        settings: &RcBuildSettings,
+        geometry: &'t G,
+    ) -> RNResult<TileMeshBuilder<'t>> {
+        let mut builder = TileMeshBuilder::new(settings.to_tiled_config()?, geometry)?;
+        builder.walkable_height = settings.agent_height;
+        builder.walkable_radius = settings.agent_radius;
+        builder.walkable_climb = settings.agent_max_climb;
+        Ok(builder)
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        (self.bmin, self.bmax)
+    }
+
+    // The number of tiles along the x-axis and the z-axis.
+    #[inline]
+    pub fn tile_count(&self) -> (i32, i32) {
+        (self.tiles_x, self.tiles_y)
+    }
+
+    // Navigation mesh params with tile and poly bits sized for the tile count.
+    pub fn nav_mesh_params(&self) -> DtNavMeshParams {
+        let tile_bits = i32::min(ilog2(next_pow2(self.tiles_x * self.tiles_y)), 14);
+        let poly_bits = 22 - tile_bits;
+        let tcs = self.cfg.tile_size as f32 * self.cfg.cs;
+        DtNavMeshParams {
+            orig: self.bmin,
+            tile_width: tcs,
+            tile_height: tcs,
+            max_tiles: 1 << tile_bits,
+            max_polys: 1 << poly_bits,
+        }
+    }
+
+    // The bounds of the tile, without border.
+    pub fn tile_bounds(&self, tx: i32, ty: i32) -> ([f32; 3], [f32; 3]) {
+        let tcs = self.cfg.tile_size as f32 * self.cfg.cs;
+        let tile_bmin = [
+            self.bmin[0] + tx as f32 * tcs,
+            self.bmin[1],
+            self.bmin[2] + ty as f32 * tcs,
+        ];
+        let tile_bmax = [
+            self.bmin[0] + (tx + 1) as f32 * tcs,
+            self.bmax[1],
+            self.bmin[2] + (ty + 1) as f32 * tcs,
+        ];
+        (tile_bmin, tile_bmax)
+    }
+
+    // The config used to build the tile. Its bounds are expanded by the border size, so that
+    // the navmesh tiles connect correctly at the borders, and the obstacles close to the border
+    // work correctly with the dilation process.
+    pub fn tile_config(&self, tx: i32, ty: i32) -> RcConfig {
+        let mut cfg = self.cfg.clone();
+        cfg.border_size = cfg.walkable_radius + 3;
+        cfg.width = cfg.tile_size + cfg.border_size * 2;
+        cfg.height = cfg.tile_size + cfg.border_size * 2;
+
+        let (bmin, bmax) = self.tile_bounds(tx, ty);
+        cfg.bmin = bmin;
+        cfg.bmax = bmax;
+        cfg.bmin[0] -= (cfg.border_size as f32) * cfg.cs;
+        cfg.bmin[2] -= (cfg.border_size as f32) * cfg.cs;
+        cfg.bmax[0] += (cfg.border_size as f32) * cfg.cs;
+        cfg.bmax[2] += (cfg.border_size as f32) * cfg.cs;
+        cfg
+    }
+
+    // Builds the detour data of a tile. Returns `None` if the tile has nothing walkable.
+    //
+    // The timers of `ctx` are reset, `ctx.timings()` gives the timings of this tile afterwards.
+    pub fn build_tile_data(&self, ctx: &mut RcContext, tx: i32, ty: i32) -> RNResult<Option<DtBuf>> {
+        self.build_tile_data_with(ctx, &mut TileScratch::default(), tx, ty)
+    }
+
+    fn build_tile_data_with(
+        &self,
+        ctx: &mut RcContext,
+        scratch: &mut TileScratch,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Option<DtBuf>> {
+        ctx.reset_timers();
+        ctx.start_timer(RcTimerLabel::RC_TIMER_TOTAL);
+        let res = self.build_tile_data_impl(ctx, scratch, tx, ty);
+        ctx.stop_timer(RcTimerLabel::RC_TIMER_TOTAL);
+        res
+    }
+
+    fn build_tile_data_impl(
+        &self,
+        ctx: &mut RcContext,
+        scratch: &mut TileScratch,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Option<DtBuf>> {
+        if tx < 0 || ty < 0 || tx >= self.tiles_x || ty >= self.tiles_y {
+            return Err(RNError::BuildFailed(RcBuildStage::Input));
+        }
+        let cfg = self.tile_config(tx, ty);
+        let mut solid = match self.rasterize_tile(ctx, scratch, &cfg)? {
+            Some(solid) => solid,
+            None => return Ok(None),
+        };
+
+        let (mut pmesh, dmesh) = match build_poly_mesh(ctx, &cfg, self.partition, self.convex_volumes, &mut solid)? {
+            Some(meshes) => meshes,
+            None => return Ok(None),
+        };
+
+        let off_mesh = self.off_mesh_connections.map(|set| {
+            let (bmin, bmax) = self.tile_bounds(tx, ty);
+            set.columns_in(&bmin, &bmax, &self.bmax)
+        });
+        let data = create_nav_mesh_data(
+            &cfg,
+            &mut pmesh,
+            &dmesh,
+            self.walkable_height,
+            self.walkable_radius,
+            self.walkable_climb,
+            tx,
+            ty,
+            self.build_bv_tree,
+            self.poly_flags,
+            off_mesh.as_ref(),
+        )?;
+        Ok(Some(data))
+    }
+
+    // Rasterizes the triangles overlapping the tile. Returns `None` if no triangle overlaps it.
+    fn rasterize_tile(
+        &self,
+        ctx: &mut RcContext,
+        scratch: &mut TileScratch,
+        cfg: &RcConfig,
+    ) -> RNResult<Option<RcHeightfield>> {
+        scratch.cids.resize(self.chunky_mesh.nodes().len(), 0);
+        let ncids = rc_get_chunks_overlapping_rect(
+            &self.chunky_mesh,
+            &[cfg.bmin[0], cfg.bmin[2]],
+            &[cfg.bmax[0], cfg.bmax[2]],
+            &mut scratch.cids,
+        );
+        if ncids == 0 {
+            return Ok(None);
+        }
+
+        let mut solid = RcHeightfield::new();
+        at_stage(
+            rc_create_heightfield(
+                ctx, &mut solid, cfg.width, cfg.height, &cfg.bmin, &cfg.bmax, cfg.cs, cfg.ch,
+            ),
+            RcBuildStage::Heightfield,
+        )?;
+
+        for cid in scratch.cids.iter().take(ncids) {
+            let node = &self.chunky_mesh.nodes()[*cid as usize];
+            let range = node.i as usize..(node.i + node.n) as usize;
+            let tris = &self.chunky_mesh.tris()[range.clone()];
+            let areas = self.chunky_areas.as_ref().map(|areas| &areas[range]);
+            rasterize_triangles(ctx, cfg, &mut solid, self.verts, tris, areas, &mut scratch.triareas)?;
+        }
+        Ok(Some(solid))
+    }
+
+    // Tile cache params matching the layers of `build_tile_cache_layers()`.
+    // `max_tiles` counts the layers, a tile can have several of them.
+    pub fn tile_cache_params(&self, max_tiles: i32, max_obstacles: i32) -> DtTileCacheParams {
+        DtTileCacheParams {
+            orig: self.bmin,
+            cs: self.cfg.cs,
+            ch: self.cfg.ch,
+            width: self.cfg.tile_size,
+            height: self.cfg.tile_size,
+            walkable_height: self.walkable_height,
+            walkable_radius: self.walkable_radius,
+            walkable_climb: self.walkable_climb,
+            max_simplification_error: self.cfg.max_simplification_error,
+            max_tiles,
+            max_obstacles,
+        }
+    }
+
+    // Builds the compressed tile cache layers of a tile, see `dt_build_tile_cache_layers()`.
+    // Returns no layer if the tile has nothing walkable. The layers only depend on the geometry, the config
+    // and the convex volumes, so a whole tile cache can be precomputed offline and added at load time.
+    //
+    // `cfg.tile_size` must be at most 255, since the layer dimensions are stored in bytes.
+    // `compressor` must be the one of the tile cache the layers are added to.
+    pub fn build_tile_cache_layers(
+        &self,
+        ctx: &mut RcContext,
+        compressor: &dyn DtTileCacheCompressor,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Vec<DtBuf>> {
+        if tx < 0 || ty < 0 || tx >= self.tiles_x || ty >= self.tiles_y {
+            return Err(RNError::BuildFailed(RcBuildStage::Input));
+        }
+        ctx.reset_timers();
+        ctx.start_timer(RcTimerLabel::RC_TIMER_TOTAL);
+        let res = self.build_tile_cache_layers_impl(ctx, compressor, tx, ty);
+        ctx.stop_timer(RcTimerLabel::RC_TIMER_TOTAL);
+        res
+    }
+
+    fn build_tile_cache_layers_impl(
+        &self,
+        ctx: &mut RcContext,
+        compressor: &dyn DtTileCacheCompressor,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Vec<DtBuf>> {
+        let cfg = self.tile_config(tx, ty);
+        let mut solid = match self.rasterize_tile(ctx, &mut TileScratch::default(), &cfg)? {
+            Some(solid) => solid,
+            None => return Ok(Vec::new()),
+        };
+        let lset = build_heightfield_layers(ctx, &cfg, self.convex_volumes, &mut solid)?;
+        at_stage(
+            dt_build_tile_cache_layers(compressor, &lset, tx, ty),
+            RcBuildStage::TileCacheLayer,
+        )
+    }
+
+    // Builds all tiles into a new navigation mesh.
+    pub fn build(&self, ctx: &mut RcContext) -> RNResult<DtNavMesh> {
+        Ok(self.build_with_timings(ctx)?.0)
+    }
+
+    // Same as `build()`, also returns the timings of each tile, in build order.
+    pub fn build_with_timings(&self, ctx: &mut RcContext) -> RNResult<(DtNavMesh, Vec<RcTileTimings>)> {
+        let mut nav_mesh = at_stage(DtNavMesh::with_params(&self.nav_mesh_params()), RcBuildStage::NavMesh)?;
+        let mut scratch = TileScratch::default();
+        let mut tile_timings = Vec::with_capacity((self.tiles_x * self.tiles_y) as usize);
+        for ty in 0..self.tiles_y {
+            for tx in 0..self.tiles_x {
+                let data = self.build_tile_data_with(ctx, &mut scratch, tx, ty)?;
+                tile_timings.push(RcTileTimings {
+                    tx,
+                    ty,
+                    timings: ctx.timings(),
+                });
+                if let Some(data) = data {
+                    at_stage(nav_mesh.add_tile(data, DtTileRef::default()), RcBuildStage::NavMesh)?;
+                }
+            }
+        }
+        Ok((nav_mesh, tile_timings))
+    }
+
+    // Builds all tiles on `threads` worker threads (0 means the available parallelism) into a new
+    // navigation mesh. Each worker has its own context and scratch data. Tiles are added in the same
+    // order as `build()`, so the result is bit-identical to a single-threaded build.
+    pub fn build_parallel(&self, threads: usize) -> RNResult<DtNavMesh> {
+        Ok(self.build_parallel_with_timings(threads)?.0)
+    }
+
+    // Same as `build_parallel()`, also returns the timings of each tile, in the same order as `build_with_timings()`.
+    pub fn build_parallel_with_timings(&self, threads: usize) -> RNResult<(DtNavMesh, Vec<RcTileTimings>)> {
+        let threads = match threads {
+            0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            n => n,
+        };
+        let tile_count = (self.tiles_x * self.tiles_y) as usize;
+        let next_tile = AtomicUsize::new(0);
+
+        let mut results: Vec<Option<(RNResult<Option<DtBuf>>, RcTimings)>> = (0..tile_count).map(|_| None).collect();
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(tile_count))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut ctx = RcContext::new(true);
+                        let mut scratch = TileScratch::default();
+                        let mut built = Vec::new();
+                        loop {
+                            let idx = next_tile.fetch_add(1, Ordering::Relaxed);
+                            if idx >= tile_count {
+                                break;
+                            }
+                            let tx = idx as i32 % self.tiles_x;
+                            let ty = idx as i32 / self.tiles_x;
+                            let res = self.build_tile_data_with(&mut ctx, &mut scratch, tx, ty);
+                            built.push((idx, res, ctx.timings()));
+                        }
+                        built
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (idx, res, timings) in worker.join().expect("tile worker panicked") {
+                    results[idx] = Some((res, timings));
+                }
+            }
+        });
+
+        let mut nav_mesh = at_stage(DtNavMesh::with_params(&self.nav_mesh_params()), RcBuildStage::NavMesh)?;
+        let mut tile_timings = Vec::with_capacity(tile_count);
+        for (idx, (res, timings)) in results.into_iter().flatten().enumerate() {
+            let data = res?;
+            tile_timings.push(RcTileTimings {
+                tx: idx as i32 % self.tiles_x,
+                ty: idx as i32 / self.tiles_x,
+                timings,
+            });
+            if let Some(data) = data {
+                at_stage(nav_mesh.add_tile(data, DtTileRef::default()), RcBuildStage::NavMesh)?;
+            }
+        }
+        Ok((nav_mesh, tile_timings))
+    }
+
+    // A stable hash of everything the data of a tile depends on: the input triangles of the chunks
+    // overlapping the tile, their area ids, the tile config, the agent dimensions, the partitioning,
+    // the convex volumes overlapping the tile and the off-mesh connections starting in it.
+    //
+    // `poly_flags` can not be hashed, change `cache_salt` when it changes.
+    pub fn tile_hash(&self, tx: i32, ty: i32) -> u64 {
+        self.tile_hash_with(&mut TileScratch::default(), tx, ty)
+    }
+
+    fn tile_hash_with(&self, scratch: &mut TileScratch, tx: i32, ty: i32) -> u64 {
+        let cfg = self.tile_config(tx, ty);
+        let mut h = StableHasher::new();
+        h.bytes(env!("CARGO_PKG_VERSION").as_bytes());
+        h.u64(self.cache_salt);
+        h.i32(tx);
+        h.i32(ty);
+        hash_config(&mut h, &cfg);
+        h.f32(self.walkable_height);
+        h.f32(self.walkable_radius);
+        h.f32(self.walkable_climb);
+        h.u8(self.partition as u8);
+        h.u8(self.build_bv_tree as u8);
+        h.u8(self.chunky_areas.is_some() as u8);
+
+        scratch.cids.resize(self.chunky_mesh.nodes().len(), 0);
+        let ncids = rc_get_chunks_overlapping_rect(
+            &self.chunky_mesh,
+            &[cfg.bmin[0], cfg.bmin[2]],
+            &[cfg.bmax[0], cfg.bmax[2]],
+            &mut scratch.cids,
+        );
+        h.u32(ncids as u32);
+        for cid in scratch.cids.iter().take(ncids) {
+            let node = &self.chunky_mesh.nodes()[*cid as usize];
+            let range = node.i as usize..(node.i + node.n) as usize;
+            h.u32(node.n as u32);
+            for tri in &self.chunky_mesh.tris()[range.clone()] {
+                tri.iter().for_each(|idx| h.vec3(&self.verts[*idx as usize]));
+            }
+            if let Some(areas) = &self.chunky_areas {
+                h.bytes(&areas[range]);
+            }
+        }
+
+        let volumes = self.convex_volumes.map(|set| set.volumes()).unwrap_or_default();
+        for volume in volumes {
+            let (bmin, bmax) = volume.bounds();
+            if bmin[0] > cfg.bmax[0] || bmax[0] < cfg.bmin[0] || bmin[2] > cfg.bmax[2] || bmax[2] < cfg.bmin[2] {
+                continue;
+            }
+            h.u32(volume.verts.len() as u32);
+            volume.verts.iter().for_each(|v| h.vec3(v));
+            h.f32(volume.hmin);
+            h.f32(volume.hmax);
+            h.u8(volume.area);
+        }
+        h.u8(0xff);
+
+        if let Some(set) = self.off_mesh_connections {
+            let (bmin, bmax) = self.tile_bounds(tx, ty);
+            for con in set.connections_in(&bmin, &bmax, &self.bmax) {
+                h.vec3(&con.start);
+                h.vec3(&con.end);
+                h.f32(con.radius);
+                h.u8(con.bidirectional as u8);
+                h.u8(con.area);
+                h.u16(con.flags);
+                h.u32(con.user_id);
+            }
+        }
+        h.finish()
+    }
+
+    // Same as `build_tile_data()`, but loads the tile from `cache` if its hash is cached, otherwise
+    // builds it and stores it into `cache`. Cache read and write failures fall back to building.
+    pub fn build_tile_data_cached(
+        &self,
+        ctx: &mut RcContext,
+        cache: &TileBuildCache,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Option<DtBuf>> {
+        let mut stats = TileCacheStats::default();
+        self.build_tile_data_cached_with(ctx, &mut TileScratch::default(), cache, &mut stats, tx, ty)
+    }
+
+    fn build_tile_data_cached_with(
+        &self,
+        ctx: &mut RcContext,
+        scratch: &mut TileScratch,
+        cache: &TileBuildCache,
+        stats: &mut TileCacheStats,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Option<DtBuf>> {
+        if tx < 0 || ty < 0 || tx >= self.tiles_x || ty >= self.tiles_y {
+            return Err(RNError::BuildFailed(RcBuildStage::Input));
+        }
+        let hash = self.tile_hash_with(scratch, tx, ty);
+        stats.hashes.push(hash);
+        if let Ok(Some(data)) = cache.load(hash) {
+            ctx.reset_timers();
+            stats.hits += 1;
+            return Ok(if data.is_empty() { None } else { Some(data) });
+        }
+
+        let data = self.build_tile_data_with(ctx, scratch, tx, ty)?;
+        let bytes = data.as_ref().map(|data| data.as_slice()).unwrap_or_default();
+        match cache.store(hash, bytes) {
+            Ok(()) => stats.misses += 1,
+            Err(_) => stats.store_failures += 1,
+        }
+        Ok(data)
+    }
+
+    // Same as `build()`, but reuses the tiles cached in `cache` and stores the rebuilt ones.
+    // Unchanged tiles are loaded as is, so the result is identical to a full build.
+    pub fn build_cached(&self, ctx: &mut RcContext, cache: &TileBuildCache) -> RNResult<(DtNavMesh, TileCacheStats)> {
+        let mut nav_mesh = at_stage(DtNavMesh::with_params(&self.nav_mesh_params()), RcBuildStage::NavMesh)?;
+        let mut scratch = TileScratch::default();
+        let mut stats = TileCacheStats::default();
+        for ty in 0..self.tiles_y {
+            for tx in 0..self.tiles_x {
+                if let Some(data) = self.build_tile_data_cached_with(ctx, &mut scratch, cache, &mut stats, tx, ty)? {
+                    at_stage(nav_mesh.add_tile(data, DtTileRef::default()), RcBuildStage::NavMesh)?;
+                }
+            }
+        }
+        Ok((nav_mesh, stats))
+    }
+
+    // Rebuilds a tile and replaces it in the navigation mesh.
+    // Returns `None` if the rebuilt tile has nothing walkable, in which case the old tile is only removed.
+    pub fn rebuild_tile(
+        &self,
+        ctx: &mut RcContext,
+        nav_mesh: &mut DtNavMesh,
+        tx: i32,
+        ty: i32,
+    ) -> RNResult<Option<DtTileRef>> {
+        let data = self.build_tile_data(ctx, tx, ty)?;
+        self.remove_tile(nav_mesh, tx, ty)?;
+        match data {
+            Some(data) => {
+                let re = at_stage(nav_mesh.add_tile(data, DtTileRef::default()), RcBuildStage::NavMesh)?;
+                Ok(Some(re))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Removes a tile from the navigation mesh. Does nothing if there is no tile at the location.
+    pub fn remove_tile(&self, nav_mesh: &mut DtNavMesh, tx: i32, ty: i32) -> RNResult<()> {
+        let re = nav_mesh.get_tile_ref_at(tx, ty, 0);
+        if re != DtTileRef::default() {
+            nav_mesh.remove_tile(re)?;
+        }
+        Ok(())
+    }
+}
+
+// Per-worker buffers reused between tiles.
+#[derive(Default)]
+struct TileScratch {
+    cids: Vec<i32>,
+    triareas: Vec<u8>,
+}
+
+// Builds the chunky mesh, and the area ids in its triangle order if the geometry has some.
+fn create_chunky_mesh<G: InputGeometry + ?Sized>(geometry: &G) -> RNResult<(RcChunkyTriMesh, Option<Vec<u8>>)> {
+    let mut chunky_mesh = RcChunkyTriMesh::new();
+    rc_create_chunky_tri_mesh(&mut chunky_mesh, geometry, TRIS_PER_CHUNK)?;
+    let Some(areas) = geometry.tri_areas() else {
+        return Ok((chunky_mesh, None));
+    };
+
+    // The chunky mesh copies the vertex indices of each triangle without its original index. Sorted by
+    // vertex indices, both triangle lists are the same, so the k-th of each is the same triangle. Equal
+    // triangles may swap their area ids, but they rasterize to the same spans, whose areas are merged.
+    let (tris, chunky_tris) = (geometry.tris(), chunky_mesh.tris());
+    if chunky_tris.len() != tris.len() {
+        return Err(RNError::Failed);
+    }
+    let mut order: Vec<u32> = (0..tris.len() as u32).collect();
+    order.sort_unstable_by_key(|i| tris[*i as usize]);
+    let mut chunky_order: Vec<u32> = (0..chunky_tris.len() as u32).collect();
+    chunky_order.sort_unstable_by_key(|i| chunky_tris[*i as usize]);
+
+    let mut chunky_areas = vec![RC_NULL_AREA; tris.len()];
+    for (i, ci) in order.iter().zip(&chunky_order) {
+        if tris[*i as usize] != chunky_tris[*ci as usize] {
+            return Err(RNError::Failed);
+        }
+        chunky_areas[*ci as usize] = areas[*i as usize];
+    }
+    Ok((chunky_mesh, Some(chunky_areas)))
+}
+
+fn hash_config(h: &mut StableHasher, cfg: &RcConfig) {
+    h.i32(cfg.width);
+    h.i32(cfg.height);
+    h.i32(cfg.tile_size);
+    h.i32(cfg.border_size);
+    h.f32(cfg.cs);
+    h.f32(cfg.ch);
+    h.vec3(&cfg.bmin);
+    h.vec3(&cfg.bmax);
+    h.f32(cfg.walkable_slope_angle);
+    h.i32(cfg.walkable_height);
+    h.i32(cfg.walkable_climb);
+    h.i32(cfg.walkable_radius);
+    h.i32(cfg.max_edge_len);
+    h.f32(cfg.max_simplification_error);
+    h.i32(cfg.min_region_area);
+    h.i32(cfg.merge_region_area);
+    h.i32(cfg.max_verts_per_poly);
+    h.f32(cfg.detail_sample_dist);
+    h.f32(cfg.detail_sample_max_error);
+}
+
+fn next_pow2(mut v: i32) -> i32 {
+    v -= 1;
+    v |= v >> 1;
+    v |= v >> 2;
+    v |= v >> 4;
+    v |= v >> 8;
+    v |= v >> 16;
+    v += 1;
+    v
+}
+
+fn ilog2(mut v: i32) -> i32 {
+    let mut r: i32;
+    let mut shift: i32;
+
+    r = ((v > 0xffff) as i32) << 4;
+    v >>= r;
+
+    shift = ((v > 0xff) as i32) << 3;
+    v >>= shift;
+    r |= shift;
+
+    shift = ((v > 0xf) as i32) << 2;
+    v >>= shift;
+    r |= shift;
+
+    shift = ((v > 0x3) as i32) << 1;
+    v >>= shift;
+    r |= shift;
+
+    r |= v >> 1;
+    r
+}
@@ -0,0 +1,265 @@
+use crate::detour::DT_VERTS_PER_POLYGON;
+use crate::recast::recast::{RcPolyMesh, RcPolyMeshDetail, RC_MESH_NULL_IDX};
+
+// A problem found by `RcPolyMesh::validate()` or `RcPolyMeshDetail::validate()`.
+// `poly` is a poly index, `vert` and `edge` are indices in the poly, `tri` is a tri index in the detail mesh of the poly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RcMeshIssue {
+    // The mesh has no polys, e.g. a tile with nothing walkable.
+    NoPolys,
+    // The vertex indices overflow into `RC_MESH_NULL_IDX`.
+    TooManyVerts { nverts: usize },
+    // Detour supports at most `DT_VERTS_PER_POLYGON` verts per poly.
+    NvpOutOfRange { nvp: usize },
+    // Warning, the mesh is full, more polys would have been dropped.
+    PolyLimitReached { maxpolys: usize },
+    // Warning, `polys` polys have `nvp` verts, a larger `max_verts_per_poly` would merge them further.
+    NvpLimitReached { polys: usize },
+    TooFewVerts { poly: usize },
+    VertOutOfRange { poly: usize, vert: usize },
+    DuplicateVert { poly: usize, vert: usize },
+    ZeroArea { poly: usize },
+    NonConvex { poly: usize, vert: usize },
+    NeighbourOutOfRange { poly: usize, edge: usize },
+    // The neighbour does not share the edge, or does not link back.
+    BadNeighbour { poly: usize, edge: usize },
+    DetailMeshCount { npolys: usize, nmeshes: usize },
+    DetailOutOfRange { poly: usize },
+    DetailEmpty { poly: usize },
+    DetailTriOutOfRange { poly: usize, tri: usize },
+    // A detail tri is not inside the parent poly on the xz-plane.
+    DetailTriOutside { poly: usize, tri: usize },
+}
+
+impl RcMeshIssue {
+    // Warnings are limits being hit, the mesh can still be used.
+    #[inline]
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            RcMeshIssue::PolyLimitReached { .. } | RcMeshIssue::NvpLimitReached { .. }
+        )
+    }
+}
+
+// The issues found by a validation, in mesh order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RcMeshReport {
+    pub issues: Vec<RcMeshIssue>,
+}
+
+impl RcMeshReport {
+    // True if there is no issue other than warnings.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &RcMeshIssue> {
+        self.issues.iter().filter(|issue| !issue.is_warning())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &RcMeshIssue> {
+        self.issues.iter().filter(|issue| issue.is_warning())
+    }
+}
+
+// The vertex indices of a poly, without the unused slots.
+fn poly_verts(pmesh: &RcPolyMesh, poly: usize) -> &[u16] {
+    let nvp = pmesh.nvp();
+    let verts = &pmesh.polys()[poly * 2 * nvp..poly * 2 * nvp + nvp];
+    let count = verts.iter().position(|v| *v == RC_MESH_NULL_IDX).unwrap_or(nvp);
+    &verts[..count]
+}
+
+// Twice the signed area of the poly on the xz-plane, and the index of a vertex turning against it.
+fn poly_shape(pmesh: &RcPolyMesh, verts: &[u16]) -> (i64, Option<usize>) {
+    let pos = |i: usize| {
+        let v = pmesh.verts()[verts[i % verts.len()] as usize];
+        (v[0] as i64, v[2] as i64)
+    };
+    let n = verts.len();
+    let area: i64 = (0..n)
+        .map(|i| {
+            let (a, b) = (pos(i), pos(i + 1));
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    // Collinear verts are allowed, e.g. on tile borders.
+    let reflex = (0..n).find(|&i| {
+        let (a, b, c) = (pos(i + n - 1), pos(i), pos(i + 1));
+        let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+        cross != 0 && (cross > 0) != (area > 0)
+    });
+    (area, reflex)
+}
+
+impl RcPolyMesh {
+    // Checks the polys built by `rc_build_poly_mesh()`: degenerate or non-convex polys, out of range vertex indices,
+    // inconsistent neighbour links, and the `nvp`/`maxpolys` limits.
+    pub fn validate(&self) -> RcMeshReport {
+        let mut issues = Vec::new();
+        let (npolys, nverts, nvp) = (self.npolys(), self.nverts(), self.nvp());
+        if npolys == 0 {
+            issues.push(RcMeshIssue::NoPolys);
+        }
+        if nverts >= RC_MESH_NULL_IDX as usize {
+            issues.push(RcMeshIssue::TooManyVerts { nverts });
+        }
+        if !(3..=DT_VERTS_PER_POLYGON).contains(&nvp) {
+            issues.push(RcMeshIssue::NvpOutOfRange { nvp });
+        }
+        if npolys > 0 && npolys >= self.maxpolys() {
+            issues.push(RcMeshIssue::PolyLimitReached {
+                maxpolys: self.maxpolys(),
+            });
+        }
+
+        let mut full_polys = 0;
+        for poly in 0..npolys {
+            let verts = poly_verts(self, poly);
+            if verts.len() == nvp {
+                full_polys += 1;
+            }
+            if verts.len() < 3 {
+                issues.push(RcMeshIssue::TooFewVerts { poly });
+                continue;
+            }
+            if let Some(vert) = verts.iter().position(|v| *v as usize >= nverts) {
+                issues.push(RcMeshIssue::VertOutOfRange { poly, vert });
+                continue;
+            }
+            if let Some(vert) = (1..verts.len()).find(|&i| verts[..i].contains(&verts[i])) {
+                issues.push(RcMeshIssue::DuplicateVert { poly, vert });
+                continue;
+            }
+            match poly_shape(self, verts) {
+                (0, _) => issues.push(RcMeshIssue::ZeroArea { poly }),
+                (_, Some(vert)) => issues.push(RcMeshIssue::NonConvex { poly, vert }),
+                _ => {}
+            }
+            self.validate_neighbours(poly, verts, &mut issues);
+        }
+        if full_polys > 0 {
+            issues.push(RcMeshIssue::NvpLimitReached { polys: full_polys });
+        }
+        RcMeshReport { issues }
+    }
+
+    fn validate_neighbours(&self, poly: usize, verts: &[u16], issues: &mut Vec<RcMeshIssue>) {
+        let nvp = self.nvp();
+        let neis = &self.polys()[poly * 2 * nvp + nvp..(poly + 1) * 2 * nvp];
+        for (edge, nei) in neis.iter().take(verts.len()).enumerate() {
+            if *nei == RC_MESH_NULL_IDX {
+                continue;
+            }
+            // A portal to the tile side `nei & 0x7fff`, set by rc_build_poly_mesh() on tile borders.
+            if nei & 0x8000 != 0 {
+                if nei & 0x7fff > 3 {
+                    issues.push(RcMeshIssue::BadNeighbour { poly, edge });
+                }
+                continue;
+            }
+            let other = *nei as usize;
+            if other >= self.npolys() {
+                issues.push(RcMeshIssue::NeighbourOutOfRange { poly, edge });
+                continue;
+            }
+            let (va, vb) = (verts[edge], verts[(edge + 1) % verts.len()]);
+            let other_verts = poly_verts(self, other);
+            let other_neis = &self.polys()[other * 2 * nvp + nvp..(other + 1) * 2 * nvp];
+            let linked = (0..other_verts.len()).any(|k| {
+                other_verts[k] == vb && other_verts[(k + 1) % other_verts.len()] == va && other_neis[k] as usize == poly
+            });
+            if !linked {
+                issues.push(RcMeshIssue::BadNeighbour { poly, edge });
+            }
+        }
+    }
+}
+
+impl RcPolyMeshDetail {
+    // Checks the detail mesh built by `rc_build_poly_mesh_detail()` from `pmesh`: one sub-mesh per poly, index ranges,
+    // and detail tris escaping their parent poly on the xz-plane.
+    pub fn validate(&self, pmesh: &RcPolyMesh) -> RcMeshReport {
+        let mut issues = Vec::new();
+        if self.nmeshes() != pmesh.npolys() {
+            issues.push(RcMeshIssue::DetailMeshCount {
+                npolys: pmesh.npolys(),
+                nmeshes: self.nmeshes(),
+            });
+        }
+        // Rounding of the edge samples, far below a cell.
+        let tolerance = pmesh.cs * 0.01;
+
+        for (poly, mesh) in self.meshes().iter().enumerate().take(pmesh.npolys()) {
+            let [vbase, nv, tbase, nt] = mesh.map(|v| v as usize);
+            if vbase + nv > self.nverts() || tbase + nt > self.ntris() {
+                issues.push(RcMeshIssue::DetailOutOfRange { poly });
+                continue;
+            }
+            if nt == 0 {
+                issues.push(RcMeshIssue::DetailEmpty { poly });
+                continue;
+            }
+
+            // The parent poly on the xz-plane, only if it is valid.
+            let verts = poly_verts(pmesh, poly);
+            let valid_poly = verts.len() >= 3
+                && verts.iter().all(|v| (*v as usize) < pmesh.nverts())
+                && poly_shape(pmesh, verts).0 != 0;
+            let outline: Option<Vec<[f32; 2]>> = valid_poly.then(|| {
+                verts
+                    .iter()
+                    .map(|v| {
+                        let v = pmesh.verts()[*v as usize];
+                        [
+                            pmesh.bmin[0] + v[0] as f32 * pmesh.cs,
+                            pmesh.bmin[2] + v[2] as f32 * pmesh.cs,
+                        ]
+                    })
+                    .collect()
+            });
+
+            for (tri, t) in self.tris()[tbase..tbase + nt].iter().enumerate() {
+                if t[..3].iter().any(|i| *i as usize >= nv) {
+                    issues.push(RcMeshIssue::DetailTriOutOfRange { poly, tri });
+                    continue;
+                }
+                if let Some(outline) = &outline {
+                    let inside = t[..3].iter().all(|i| {
+                        let v = self.verts()[vbase + *i as usize];
+                        inside_convex(outline, [v[0], v[2]], tolerance)
+                    });
+                    if !inside {
+                        issues.push(RcMeshIssue::DetailTriOutside { poly, tri });
+                    }
+                }
+            }
+        }
+        RcMeshReport { issues }
+    }
+}
+
+// True if `p` is inside the convex polygon, or outside by at most `tolerance`.
+fn inside_convex(outline: &[[f32; 2]], p: [f32; 2], tolerance: f32) -> bool {
+    let n = outline.len();
+    let area: f32 = (0..n)
+        .map(|i| {
+            let (a, b) = (outline[i], outline[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    let sign = area.signum();
+    (0..n).all(|i| {
+        let (a, b) = (outline[i], outline[(i + 1) % n]);
+        let (ex, ez) = (b[0] - a[0], b[1] - a[1]);
+        let len = (ex * ex + ez * ez).sqrt();
+        if len == 0.0 {
+            return true;
+        }
+        // The distance to the edge line, positive inside.
+        let dist = (ex * (p[1] - a[1]) - ez * (p[0] - a[0])) * sign / len;
+        dist >= -tolerance
+    })
+}
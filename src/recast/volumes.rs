@@ -0,0 +1,100 @@
+use crate::error::{RNError, RNResult};
+use crate::recast::recast::{rc_mark_convex_poly_area, RcCompactHeightfield, RcContext};
+
+// The maximum number of points of a convex volume, same as RecastDemo's MAX_CONVEXVOL_PTS.
+pub const MAX_CONVEXVOL_PTS: usize = 12;
+
+// A convex prism marking the area id of the walkable surface inside it, like RecastDemo's ConvexVolume.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct ConvexVolume {
+    // The convex polygon on the xz-plane. [Limits: 3 <= len <= MAX_CONVEXVOL_PTS] [Units: wu]
+    pub verts: Vec<[f32; 3]>,
+
+    // The height range of the prism. [Units: wu]
+    pub hmin: f32,
+    pub hmax: f32,
+
+    pub area: u8,
+}
+
+impl ConvexVolume {
+    // The AABB of the prism.
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let mut bmin = [f32::MAX, self.hmin, f32::MAX];
+        let mut bmax = [f32::MIN, self.hmax, f32::MIN];
+        for v in &self.verts {
+            bmin[0] = bmin[0].min(v[0]);
+            bmin[2] = bmin[2].min(v[2]);
+            bmax[0] = bmax[0].max(v[0]);
+            bmax[2] = bmax[2].max(v[2]);
+        }
+        (bmin, bmax)
+    }
+}
+
+// The convex volumes applied by the builders after the walkable area is eroded.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct ConvexVolumeSet {
+    volumes: Vec<ConvexVolume>,
+}
+
+impl ConvexVolumeSet {
+    #[inline]
+    pub fn new() -> ConvexVolumeSet {
+        ConvexVolumeSet::default()
+    }
+
+    // Adds a volume and returns its index.
+    pub fn add(&mut self, volume: ConvexVolume) -> RNResult<usize> {
+        if volume.verts.len() < 3 || volume.verts.len() > MAX_CONVEXVOL_PTS || volume.hmin > volume.hmax {
+            return Err(RNError::InvalidParam);
+        }
+        self.volumes.push(volume);
+        Ok(self.volumes.len() - 1)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, idx: usize) -> ConvexVolume {
+        self.volumes.remove(idx)
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.volumes.clear();
+    }
+
+    #[inline]
+    pub fn volumes(&self) -> &[ConvexVolume] {
+        &self.volumes
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.volumes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.volumes.is_empty()
+    }
+
+    // Marks the area of the volumes overlapping the compact heightfield.
+    // Volumes outside the bounds of `chf` (e.g. of another tile) are skipped.
+    pub fn mark_areas(&self, ctx: &mut RcContext, chf: &mut RcCompactHeightfield) {
+        let (chf_bmin, chf_bmax) = (chf.bmin, chf.bmax);
+        for volume in &self.volumes {
+            let (bmin, bmax) = volume.bounds();
+            let overlap = bmin[0] <= chf_bmax[0]
+                && bmax[0] >= chf_bmin[0]
+                && bmin[1] <= chf_bmax[1]
+                && bmax[1] >= chf_bmin[1]
+                && bmin[2] <= chf_bmax[2]
+                && bmax[2] >= chf_bmin[2];
+            if overlap {
+                rc_mark_convex_poly_area(ctx, &volume.verts, volume.hmin, volume.hmax, volume.area, chf);
+            }
+        }
+    }
+}
@@ -1,10 +1,14 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+use recastnavigation_rs::recast::RC_WALKABLE_AREA;
+
 mod file;
+mod pipeline;
 mod state;
 
 pub use file::*;
+pub use pipeline::*;
 pub use state::*;
 
 #[repr(u8)]
@@ -26,3 +30,21 @@ pub enum SamplePolyFlags {
     Disabled = 0x10,
     All = 0xff,
 }
+
+pub fn sample_poly_flags(areas: &mut [u8], flags: &mut [u16]) {
+    for i in 0..areas.len() {
+        if areas[i] == RC_WALKABLE_AREA {
+            areas[i] = SamplePolyAreas::Ground as u8;
+        }
+        if areas[i] == SamplePolyAreas::Ground as u8
+            || areas[i] == SamplePolyAreas::Grass as u8
+            || areas[i] == SamplePolyAreas::Road as u8
+        {
+            flags[i] = SamplePolyFlags::Walk as u16;
+        } else if areas[i] == SamplePolyAreas::Water as u8 {
+            flags[i] = SamplePolyFlags::Swim as u16;
+        } else if areas[i] == SamplePolyAreas::Door as u8 {
+            flags[i] = SamplePolyFlags::Walk as u16 | SamplePolyFlags::Door as u16;
+        }
+    }
+}
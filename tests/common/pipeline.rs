@@ -0,0 +1,130 @@
+use recastnavigation_rs::demo::*;
+use recastnavigation_rs::recast::*;
+
+// The default config of RecastDemo, without bounds.
+pub fn sample_config() -> RcConfig {
+    let mut cfg = RcConfig::default();
+    cfg.cs = 0.3;
+    cfg.ch = 0.2;
+    cfg.walkable_slope_angle = 45.0;
+    cfg.walkable_height = (2.0 / cfg.ch).ceil() as i32;
+    cfg.walkable_climb = (0.9 / cfg.ch).floor() as i32;
+    cfg.walkable_radius = (0.6 / cfg.cs).floor() as i32;
+    cfg.max_edge_len = (12.0 / cfg.cs) as i32;
+    cfg.max_simplification_error = 1.3;
+    cfg.min_region_area = 8 * 8;
+    cfg.merge_region_area = 20 * 20;
+    cfg.max_verts_per_poly = 6;
+    cfg.detail_sample_dist = cfg.cs * 6.0;
+    cfg.detail_sample_max_error = cfg.ch * 1.0;
+    cfg
+}
+
+pub fn load_mesh(name: &str) -> RcMeshLoaderObj {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load(&format!("./resource/{}.obj", name));
+    mesh_loader
+}
+
+// `sample_config()` covering the whole mesh, as a single tile.
+pub fn solo_config(mesh_loader: &RcMeshLoaderObj) -> RcConfig {
+    let mut cfg = sample_config();
+    let (bmin, bmax) = rc_calc_bounds(mesh_loader.get_verts());
+    cfg.bmin = bmin;
+    cfg.bmax = bmax;
+    (cfg.width, cfg.height) = rc_calc_grid_size(&bmin, &bmax, cfg.cs);
+    cfg
+}
+
+// Rasterizes the whole mesh into the bounds of `cfg`, then filters the walkable surfaces.
+pub fn build_solid(ctx: &mut RcContext, cfg: &RcConfig, mesh_loader: &RcMeshLoaderObj) -> RcHeightfield {
+    let mut solid = RcHeightfield::new();
+    rc_create_heightfield(
+        ctx, &mut solid, cfg.width, cfg.height, &cfg.bmin, &cfg.bmax, cfg.cs, cfg.ch,
+    )
+    .unwrap();
+    let mut triareas = vec![0; mesh_loader.get_tri_count() as usize];
+    rc_mark_walkable_triangles(
+        ctx,
+        cfg.walkable_slope_angle,
+        mesh_loader.get_verts(),
+        mesh_loader.get_tris(),
+        &mut triareas,
+    )
+    .unwrap();
+    rc_rasterize_triangles_1(
+        ctx,
+        mesh_loader.get_verts(),
+        mesh_loader.get_tris(),
+        &triareas,
+        &mut solid,
+        cfg.walkable_climb,
+    )
+    .unwrap();
+    rc_filter_low_hanging_walkable_obstacles(ctx, cfg.walkable_climb, &mut solid);
+    rc_filter_ledge_spans(ctx, cfg.walkable_height, cfg.walkable_climb, &mut solid);
+    rc_filter_walkable_low_height_spans(ctx, cfg.walkable_height, &mut solid);
+    solid
+}
+
+// The eroded compact heightfield, without regions.
+pub fn build_compact_heightfield(ctx: &mut RcContext, cfg: &RcConfig, solid: &RcHeightfield) -> RcCompactHeightfield {
+    let mut chf = RcCompactHeightfield::new();
+    rc_build_compact_heightfield(ctx, cfg.walkable_height, cfg.walkable_climb, solid, &mut chf).unwrap();
+    rc_erode_walkable_area(ctx, cfg.walkable_radius, &mut chf).unwrap();
+    chf
+}
+
+// The eroded compact heightfield, with watershed regions.
+pub fn build_chf(ctx: &mut RcContext, cfg: &RcConfig, solid: &RcHeightfield) -> RcCompactHeightfield {
+    let mut chf = build_compact_heightfield(ctx, cfg, solid);
+    rc_build_distance_field(ctx, &mut chf).unwrap();
+    rc_build_regions(
+        ctx,
+        &mut chf,
+        cfg.border_size,
+        cfg.min_region_area,
+        cfg.merge_region_area,
+    )
+    .unwrap();
+    chf
+}
+
+pub fn build_cset(ctx: &mut RcContext, cfg: &RcConfig, chf: &RcCompactHeightfield) -> RcContourSet {
+    let mut cset = RcContourSet::new();
+    rc_build_contours(
+        ctx,
+        chf,
+        cfg.max_simplification_error,
+        cfg.max_edge_len,
+        &mut cset,
+        RcBuildContoursFlags::RC_CONTOUR_TESS_WALL_EDGES,
+    )
+    .unwrap();
+    cset
+}
+
+pub fn build_pmesh(ctx: &mut RcContext, cfg: &RcConfig, cset: &RcContourSet) -> RcPolyMesh {
+    let mut pmesh = RcPolyMesh::new();
+    rc_build_poly_mesh(ctx, cset, cfg.max_verts_per_poly, &mut pmesh).unwrap();
+    pmesh
+}
+
+pub fn build_dmesh(
+    ctx: &mut RcContext,
+    cfg: &RcConfig,
+    pmesh: &RcPolyMesh,
+    chf: &RcCompactHeightfield,
+) -> RcPolyMeshDetail {
+    let mut dmesh = RcPolyMeshDetail::new();
+    rc_build_poly_mesh_detail(
+        ctx,
+        pmesh,
+        chf,
+        cfg.detail_sample_dist,
+        cfg.detail_sample_max_error,
+        &mut dmesh,
+    )
+    .unwrap();
+    dmesh
+}
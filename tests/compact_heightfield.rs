@@ -0,0 +1,215 @@
+use recastnavigation_rs::recast::*;
+
+mod common;
+
+const SIZE: i32 = 12;
+
+fn build_compact_heightfield(ctx: &mut RcContext) -> RcCompactHeightfield {
+    let mut hf = RcHeightfield::new();
+    let bmax = [SIZE as f32, 20.0, SIZE as f32];
+    rc_create_heightfield(ctx, &mut hf, SIZE, SIZE, &[0.0; 3], &bmax, 1.0, 0.5).unwrap();
+    for z in 0..SIZE {
+        for x in 0..SIZE {
+            // A raised block, too high to climb, and a second floor above a part of the ground.
+            let top = if (4..7).contains(&x) && (3..9).contains(&z) {
+                6
+            } else {
+                2
+            };
+            hf.insert_span(x, z, 0, top, RC_WALKABLE_AREA as u32).unwrap();
+            if x >= 8 && z >= 6 {
+                hf.insert_span(x, z, 20, 22, RC_WALKABLE_AREA as u32).unwrap();
+            }
+        }
+    }
+    let mut chf = RcCompactHeightfield::new();
+    rc_build_compact_heightfield(ctx, 4, 1, &hf, &mut chf).unwrap();
+    chf
+}
+
+#[test]
+fn test_compact_span_con() {
+    for dir in 0..4 {
+        let (dx, dz) = (rc_get_dir_offset_x(dir), rc_get_dir_offset_y(dir));
+        assert_eq!(rc_get_dir_for_offset(dx, dz), dir);
+    }
+    assert_eq!(rc_get_dir_for_offset(0, 0), -1);
+    assert_eq!(rc_get_dir_for_offset(1, 1), -1);
+
+    let mut chf = build_compact_heightfield(&mut RcContext::new(true));
+    let span = &mut chf.spans_mut()[0];
+    for dir in 0..4 {
+        rc_set_con(span, dir, RC_NOT_CONNECTED);
+    }
+    rc_set_con(span, 2, 5);
+    assert_eq!(rc_get_con(span, 2), 5);
+    assert_eq!(span.get_con(2), Some(5));
+    assert_eq!(span.get_con(1), None);
+    assert_eq!(span.con(), (0x3f << 18) | (5 << 12) | (0x3f << 6) | 0x3f);
+
+    span.set_h(200);
+    assert_eq!(span.h(), 200);
+    assert_eq!(rc_get_con(span, 2), 5);
+    // set_h() used to clear the low con bits instead of the old height, and OR the new height in.
+    span.set_h(0xff);
+    span.set_h(3);
+    assert_eq!(span.h(), 3);
+    assert_eq!(span.con(), (0x3f << 18) | (5 << 12) | (0x3f << 6) | 0x3f);
+
+    // Same for set_count(), which cleared the low index bits.
+    let cell = &mut chf.cells_mut()[0];
+    cell.set_index(0x12_34ff);
+    cell.set_count(0xff);
+    cell.set_count(2);
+    assert_eq!((cell.index(), cell.count()), (0x12_34ff, 2));
+}
+
+#[test]
+fn test_compact_heightfield_iterators() {
+    let chf = build_compact_heightfield(&mut RcContext::new(true));
+    let spans: Vec<_> = chf.span_indices().collect();
+    assert_eq!(spans.len(), chf.span_count());
+    for (i, (x, z, span)) in spans.iter().enumerate() {
+        assert_eq!(*span, i);
+        assert_eq!(chf.span_cell(i), Some((*x, *z)));
+        assert!(chf.cell_spans(*x, *z).contains(&i));
+    }
+    assert_eq!(chf.span_cell(chf.span_count()), None);
+    assert_eq!(chf.columns().count(), (SIZE * SIZE) as usize);
+    assert_eq!(chf.cell_spans(9, 10).len(), 2);
+    assert_eq!(chf.cell_spans(SIZE, 0), 0..0);
+
+    let upper = chf.cell_spans(9, 10).end - 1;
+    assert_eq!(chf.span_pos(9, 10, upper), [9.0, 11.0, 10.0]);
+}
+
+#[test]
+fn test_compact_heightfield_neighbours() {
+    let chf = build_compact_heightfield(&mut RcContext::new(true));
+    for (x, z, i) in chf.span_indices() {
+        for dir in 0..4 {
+            if let Some(n) = chf.neighbour_at(x, z, i, dir) {
+                let (nx, nz) = chf.span_cell(n).unwrap();
+                assert_eq!((nx, nz), (x + rc_get_dir_offset_x(dir), z + rc_get_dir_offset_y(dir)));
+                assert!(chf.spans()[n].y.abs_diff(chf.spans()[i].y) <= 1);
+                assert_eq!(chf.neighbour_at(nx, nz, n, (dir + 2) & 0x3), Some(i));
+            }
+        }
+    }
+
+    // The border of the grid and the raised block have no neighbours outside.
+    let first = chf.cell_spans(0, 5).start;
+    assert_eq!(chf.neighbour_at(0, 5, first, 0), None);
+    let block = chf.cell_spans(4, 5).start;
+    assert_eq!(chf.neighbour_at(4, 5, block, 0), None);
+    assert!(chf.neighbour_at(4, 5, block, 2).is_some());
+}
+
+#[test]
+fn test_compact_heightfield_empty_cells() {
+    // The dungeon has cells without spans, they keep an index of 0 in the middle of the grid.
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = common::load_mesh("dungeon");
+    let cfg = common::solo_config(&mesh_loader);
+    let solid = common::build_solid(&mut ctx, &cfg, &mesh_loader);
+    let chf = common::build_compact_heightfield(&mut ctx, &cfg, &solid);
+    let empty = chf.columns().position(|(_, _, spans)| spans.is_empty()).unwrap();
+    assert!(chf.columns().skip(empty).any(|(_, _, spans)| !spans.is_empty()));
+
+    for (x, z, i) in chf.span_indices().step_by(97) {
+        assert_eq!(chf.span_cell(i), Some((x, z)));
+        for dir in 0..4 {
+            if let Some(n) = chf.neighbour_at(x, z, i, dir) {
+                let cell = (x + rc_get_dir_offset_x(dir), z + rc_get_dir_offset_y(dir));
+                assert_eq!(chf.span_cell(n), Some(cell));
+            }
+        }
+    }
+    let (x, z, last) = chf.span_indices().last().unwrap();
+    assert_eq!(chf.span_cell(last), Some((x, z)));
+}
+
+#[test]
+fn test_compact_heightfield_custom_erosion() {
+    let mut ctx = RcContext::new(true);
+    let mut expected = build_compact_heightfield(&mut ctx);
+    rc_erode_walkable_area(&mut ctx, 1, &mut expected).unwrap();
+
+    // With a radius of 1, erosion removes the spans without 4 walkable neighbours.
+    let mut chf = build_compact_heightfield(&mut ctx);
+    let border: Vec<usize> = chf
+        .span_indices()
+        .filter(|&(x, z, i)| {
+            (0..4).any(|dir| match chf.neighbour_at(x, z, i, dir) {
+                Some(n) => chf.areas()[n] == RC_NULL_AREA,
+                None => true,
+            })
+        })
+        .map(|(_, _, i)| i)
+        .collect();
+    for i in border {
+        chf.areas_mut()[i] = RC_NULL_AREA;
+    }
+    assert_eq!(chf.areas(), expected.areas());
+}
+
+#[test]
+fn test_region_stats() {
+    let mut ctx = RcContext::new(true);
+    let mut chf = build_compact_heightfield(&mut ctx);
+    // A patch of another area on the ground, which gets its own region.
+    for z in 2..4 {
+        for x in 2..4 {
+            let ground = chf.cell_spans(x, z).start;
+            chf.areas_mut()[ground] = 7;
+        }
+    }
+    // Monotone partitioning keeps the rectangles below in one region each.
+    rc_build_regions_monotone(&mut ctx, &mut chf, 2, 0, 0).unwrap();
+
+    let stats = rc_region_stats(&chf);
+    assert!(stats.windows(2).all(|w| w[0].id < w[1].id));
+    let span_count = chf.spans().iter().filter(|span| span.reg != 0).count();
+    assert_eq!(stats.iter().map(|region| region.span_count).sum::<usize>(), span_count);
+
+    let find = |x: i32, z: i32, top: bool| {
+        let spans = chf.cell_spans(x, z);
+        let i = if top { spans.end - 1 } else { spans.start };
+        stats.iter().find(|region| region.id == chf.spans()[i].reg).unwrap()
+    };
+
+    for region in &stats {
+        assert_eq!(region.border, region.id & RC_BORDER_REG != 0);
+        assert!(region.cmin[0] <= region.cmax[0] && region.cmin[1] <= region.cmax[1]);
+        assert!(region.bmin[1] <= region.bmax[1]);
+        assert!(region.area_span_count > 0 && region.area_span_count <= region.span_count);
+        assert!(!region.neighbours.contains(&region.id));
+        for n in &region.neighbours {
+            let other = stats.iter().find(|other| other.id == *n).unwrap();
+            assert!(other.neighbours.contains(&region.id));
+        }
+    }
+    assert!(stats.iter().any(|region| region.border));
+
+    // The raised block is not climbable, so it has no neighbours.
+    let block = find(5, 5, false);
+    assert!(!block.border);
+    assert_eq!((block.cmin, block.cmax), ([4, 3], [6, 8]));
+    assert_eq!(block.span_count, 18);
+    assert_eq!(block.area, RC_WALKABLE_AREA);
+    assert!(block.neighbours.is_empty());
+    assert_eq!(block.bmin, [4.0, 3.0, 3.0]);
+    assert_eq!(block.bmax, [7.0, 3.0, 9.0]);
+
+    let patch = find(2, 2, false);
+    assert_eq!((patch.cmin, patch.cmax), ([2, 2], [3, 3]));
+    assert_eq!((patch.area, patch.area_span_count), (7, 4));
+    assert!(!patch.neighbours.is_empty());
+
+    // The part of the second floor inside the border touches a border region.
+    let upper = find(8, 8, true);
+    assert!(!upper.border);
+    assert_eq!((upper.cmin, upper.cmax), ([8, 6], [9, 9]));
+    assert!(upper.neighbours.iter().any(|n| n & RC_BORDER_REG != 0));
+    assert!(find(11, 11, true).border);
+}
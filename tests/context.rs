@@ -0,0 +1,98 @@
+use recastnavigation_rs::recast::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_context_log() {
+    let mut ctx = RcContext::new(true);
+    ctx.log(RcLogCategory::RC_LOG_PROGRESS, "progress");
+    ctx.log(RcLogCategory::RC_LOG_ERROR, "error 100%");
+    assert_eq!(
+        ctx.log_messages(),
+        &[
+            RcLogMessage {
+                category: RcLogCategory::RC_LOG_PROGRESS,
+                text: "progress".into(),
+            },
+            RcLogMessage {
+                category: RcLogCategory::RC_LOG_ERROR,
+                text: "error 100%".into(),
+            },
+        ]
+    );
+
+    let messages = ctx.drain_log();
+    assert_eq!(messages.len(), 2);
+    assert!(ctx.log_messages().is_empty());
+
+    ctx.enable_log(false);
+    ctx.log(RcLogCategory::RC_LOG_WARNING, "ignored");
+    assert!(ctx.log_messages().is_empty());
+
+    ctx.enable_log(true);
+    ctx.log(RcLogCategory::RC_LOG_WARNING, "warning");
+    ctx.reset_log();
+    assert!(ctx.log_messages().is_empty());
+}
+
+#[test]
+fn test_context_log_sink() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut ctx = RcContext::new(true);
+    let sink_received = received.clone();
+    ctx.set_log_sink(Some(Box::new(move |category: RcLogCategory, text: &str| {
+        sink_received.lock().unwrap().push((category, text.to_string()));
+    })));
+
+    ctx.log(RcLogCategory::RC_LOG_WARNING, "warning");
+    assert_eq!(
+        received.lock().unwrap().as_slice(),
+        &[(RcLogCategory::RC_LOG_WARNING, "warning".to_string())]
+    );
+    assert_eq!(ctx.log_messages().len(), 1);
+
+    // The accumulated messages are capped, the sink still gets all of them.
+    for _ in 0..RC_MAX_LOG_MESSAGES + 10 {
+        ctx.log(RcLogCategory::RC_LOG_PROGRESS, "progress");
+    }
+    assert_eq!(ctx.log_messages().len(), RC_MAX_LOG_MESSAGES);
+    assert_eq!(received.lock().unwrap().len(), RC_MAX_LOG_MESSAGES + 11);
+    ctx.drain_log();
+    ctx.log(RcLogCategory::RC_LOG_PROGRESS, "progress");
+    assert_eq!(ctx.log_messages().len(), 1);
+}
+
+#[test]
+fn test_context_timings() {
+    let mut ctx = RcContext::new(true);
+    let timings = ctx.timings();
+    assert!(timings.iter().next().is_none());
+
+    ctx.start_timer(RcTimerLabel::RC_TIMER_TOTAL);
+    ctx.start_timer(RcTimerLabel::RC_TIMER_BUILD_CONTOURS);
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    ctx.stop_timer(RcTimerLabel::RC_TIMER_BUILD_CONTOURS);
+    ctx.stop_timer(RcTimerLabel::RC_TIMER_TOTAL);
+
+    let timings = ctx.timings();
+    assert!(timings.total().unwrap() >= timings.get(RcTimerLabel::RC_TIMER_BUILD_CONTOURS).unwrap());
+    assert!(timings.get(RcTimerLabel::RC_TIMER_BUILD_CONTOURS).unwrap().as_micros() >= 2000);
+    assert!(timings.get(RcTimerLabel::RC_TIMER_BUILD_REGIONS).is_none());
+    assert_eq!(timings.iter().count(), 2);
+
+    let report = timings.to_string();
+    assert!(report.starts_with("Build Times\n- Build Contours:\t"));
+    assert!(report.contains("\n=== TOTAL:\t"));
+    assert!(report.contains("%)"));
+
+    // A total of 0 prints the times without percentages.
+    let mut timings = RcTimings::default();
+    timings.times[RcTimerLabel::RC_TIMER_TOTAL.repr as usize] = Some(std::time::Duration::ZERO);
+    timings.times[RcTimerLabel::RC_TIMER_BUILD_CONTOURS.repr as usize] = Some(std::time::Duration::ZERO);
+    assert_eq!(
+        timings.to_string(),
+        "Build Times\n- Build Contours:\t0.00ms\n=== TOTAL:\t0.00ms"
+    );
+
+    ctx.reset_timers();
+    assert!(ctx.timings().total().is_none());
+}
@@ -0,0 +1,97 @@
+use recastnavigation_rs::demo::*;
+use recastnavigation_rs::recast::*;
+
+const SIZE: i32 = 12;
+
+// A ground floor with a second floor above the cells x >= 8, z >= 6.
+fn build_compact_heightfield(ctx: &mut RcContext) -> RcCompactHeightfield {
+    let mut hf = RcHeightfield::new();
+    let bmax = [SIZE as f32, 20.0, SIZE as f32];
+    rc_create_heightfield(ctx, &mut hf, SIZE, SIZE, &[0.0; 3], &bmax, 1.0, 0.5).unwrap();
+    for z in 0..SIZE {
+        for x in 0..SIZE {
+            hf.insert_span(x, z, 0, 2, RC_WALKABLE_AREA as u32).unwrap();
+            if x >= 8 && z >= 6 {
+                hf.insert_span(x, z, 20, 22, 5).unwrap();
+            }
+        }
+    }
+    let mut chf = RcCompactHeightfield::new();
+    rc_build_compact_heightfield(ctx, 4, 1, &hf, &mut chf).unwrap();
+    chf
+}
+
+#[test]
+fn test_debug_image_compact_heightfield() {
+    let mut ctx = RcContext::new(true);
+    let mut chf = build_compact_heightfield(&mut ctx);
+    assert!(rc_debug_image_dist(&chf, RcSpanSelector::Lowest).is_err());
+    rc_build_distance_field(&mut ctx, &mut chf).unwrap();
+    rc_build_regions(&mut ctx, &mut chf, 0, 0, 0).unwrap();
+
+    let dist = rc_debug_image_dist(&chf, RcSpanSelector::Lowest).unwrap();
+    assert!(!dist.is_color());
+    assert_eq!((dist.width(), dist.height()), (SIZE as usize, SIZE as usize));
+    assert_eq!(dist.pixel(0, 0), &[0]);
+    assert!(dist.pixel(3, 3)[0] > 0);
+
+    let mut bytes = Vec::new();
+    dist.write(&mut bytes).unwrap();
+    let header = b"P5\n12 12\n255\n";
+    assert_eq!(&bytes[..header.len()], header);
+    assert_eq!(&bytes[header.len()..], dist.pixels());
+
+    let areas = rc_debug_image_areas(&chf, RcSpanSelector::Highest);
+    assert!(areas.is_color());
+    assert_eq!(areas.pixel(0, 0), &[0, 192, 255]);
+    assert_eq!(areas.pixel(9, 10), &rc_debug_area_color(5));
+    assert_eq!(
+        rc_debug_image_areas(&chf, RcSpanSelector::Nth(0)).pixel(9, 10),
+        &[0, 192, 255]
+    );
+    assert_eq!(
+        rc_debug_image_areas(&chf, RcSpanSelector::Nth(1)).pixel(0, 0),
+        &[0, 0, 0]
+    );
+
+    let lower = rc_debug_image_regions(&chf, RcSpanSelector::Lowest);
+    let upper = rc_debug_image_regions(&chf, RcSpanSelector::HeightSlice(20, 30));
+    assert_ne!(lower.pixel(9, 10), &[0, 0, 0]);
+    assert_ne!(lower.pixel(9, 10), upper.pixel(9, 10));
+    assert_eq!(upper.pixel(0, 0), &[0, 0, 0]);
+
+    let path = std::env::temp_dir().join("recastnavigation_rs_debug_regions.ppm");
+    lower.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    assert_eq!(saved.len(), b"P6\n12 12\n255\n".len() + 12 * 12 * 3);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_debug_image_layers() {
+    let mut ctx = RcContext::new(true);
+    let chf = build_compact_heightfield(&mut ctx);
+    let mut lset = RcHeightfieldLayerSet::new();
+    rc_build_heightfield_layers(&mut ctx, &chf, 0, 4, &mut lset).unwrap();
+    assert!(lset.layers().len() >= 2);
+
+    for layer in lset.layers() {
+        let size = (layer.width() as usize, layer.height() as usize);
+        // areas() and cons() used to return `height` items instead of the whole grid.
+        assert_eq!(layer.heights().len(), size.0 * size.1);
+        assert_eq!(layer.areas().len(), size.0 * size.1);
+        assert_eq!(layer.cons().len(), size.0 * size.1);
+        let heights = rc_debug_image_layer_heights(layer);
+        let areas = rc_debug_image_layer_areas(layer);
+        let cons = rc_debug_image_layer_cons(layer);
+        assert_eq!((heights.width(), heights.height()), size);
+        assert_eq!((areas.width(), areas.height()), size);
+        assert_eq!((cons.width(), cons.height()), size);
+        assert!(heights.pixels().iter().any(|p| *p != 0));
+        assert!(cons.pixels().iter().any(|p| *p != 0));
+    }
+    assert_eq!(
+        rc_debug_image_layer_areas(&lset.layers()[0]).pixel(0, 0),
+        &[0, 192, 255]
+    );
+}
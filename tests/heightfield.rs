@@ -0,0 +1,159 @@
+use recastnavigation_rs::recast::*;
+
+mod common;
+
+fn new_heightfield(ctx: &mut RcContext, size: i32) -> RcHeightfield {
+    let bmin = [0.0, 0.0, 0.0];
+    let bmax = [size as f32, 20.0, size as f32];
+    let mut hf = RcHeightfield::new();
+    rc_create_heightfield(ctx, &mut hf, size, size, &bmin, &bmax, 1.0, 0.5).unwrap();
+    hf
+}
+
+fn column(hf: &RcHeightfield, x: i32, z: i32) -> Vec<(u32, u32, u32)> {
+    hf.column(x, z).map(|sp| (sp.smin(), sp.smax(), sp.area())).collect()
+}
+
+#[test]
+fn test_span_setters() {
+    // set_smax() used to OR the whole smax mask in, so any smax read back as RC_SPAN_MAX_HEIGHT.
+    let mut ctx = RcContext::new(true);
+    let mut hf = new_heightfield(&mut ctx, 2);
+    rc_add_span(&mut ctx, &mut hf, 0, 0, 3, 9, 5, 1).unwrap();
+    let (_, _, sp) = hf.spans_mut().next().unwrap();
+    sp.set_smax(RC_SPAN_MAX_HEIGHT);
+    sp.set_smax(6);
+    assert_eq!((sp.smin(), sp.smax(), sp.area()), (3, 6, 5));
+    sp.set_smin(RC_SPAN_MAX_HEIGHT);
+    sp.set_smin(2);
+    sp.set_area(0);
+    assert_eq!((sp.smin(), sp.smax(), sp.area()), (2, 6, 0));
+}
+
+#[test]
+fn test_heightfield_iterators() {
+    let mut ctx = RcContext::new(true);
+    let mut hf = new_heightfield(&mut ctx, 8);
+    rc_add_span(&mut ctx, &mut hf, 1, 2, 0, 4, RC_WALKABLE_AREA, 1).unwrap();
+    rc_add_span(&mut ctx, &mut hf, 1, 2, 10, 12, 3, 1).unwrap();
+    rc_add_span(&mut ctx, &mut hf, 7, 7, 5, 6, 4, 1).unwrap();
+
+    let columns: Vec<_> = hf.columns().map(|(x, z, spans)| (x, z, spans.count())).collect();
+    assert_eq!(columns.len(), 64);
+    assert_eq!(columns[2 * 8 + 1], (1, 2, 2));
+    assert_eq!(columns[63], (7, 7, 1));
+    assert_eq!(columns.iter().map(|c| c.2).sum::<usize>(), 3);
+
+    let spans: Vec<_> = hf.spans().map(|(x, z, sp)| (x, z, sp.smin(), sp.area())).collect();
+    assert_eq!(
+        spans,
+        vec![(1, 2, 0, RC_WALKABLE_AREA as u32), (1, 2, 10, 3), (7, 7, 5, 4)]
+    );
+    assert_eq!(column(&hf, 1, 2), vec![(0, 4, RC_WALKABLE_AREA as u32), (10, 12, 3)]);
+    assert!(column(&hf, -1, 2).is_empty());
+    assert!(column(&hf, 8, 0).is_empty());
+
+    for (_, _, sp) in hf.spans_mut() {
+        sp.set_smax(sp.smax() + 1);
+        sp.set_area(2);
+    }
+    for (_, _, spans) in hf.columns_mut() {
+        if let Some(sp) = spans.last() {
+            sp.set_area(1);
+        }
+    }
+    assert_eq!(column(&hf, 1, 2), vec![(0, 5, 2), (10, 13, 1)]);
+    assert_eq!(column(&hf, 7, 7), vec![(5, 7, 1)]);
+}
+
+#[test]
+fn test_heightfield_insert_remove() {
+    let mut ctx = RcContext::new(true);
+    let mut hf = new_heightfield(&mut ctx, 4);
+
+    hf.insert_span(2, 1, 10, 12, 5).unwrap();
+    hf.insert_span(2, 1, 0, 3, 6).unwrap();
+    hf.insert_span(2, 1, 20, 30, 7).unwrap();
+    hf.insert_span(2, 1, 12, 15, 8).unwrap();
+    assert_eq!(
+        column(&hf, 2, 1),
+        vec![(0, 3, 6), (10, 12, 5), (12, 15, 8), (20, 30, 7)]
+    );
+
+    assert!(hf.insert_span(2, 1, 2, 5, 1).is_err());
+    assert!(hf.insert_span(2, 1, 25, 26, 1).is_err());
+    assert!(hf.insert_span(2, 1, 5, 5, 1).is_err());
+    assert!(hf.insert_span(2, 1, 5, RC_SPAN_MAX_HEIGHT + 1, 1).is_err());
+    assert!(hf.insert_span(2, 1, 5, 6, 64).is_err());
+    assert!(hf.insert_span(4, 1, 5, 6, 1).is_err());
+
+    assert!(hf.remove_span(2, 1, 1));
+    assert!(!hf.remove_span(2, 1, 3));
+    assert_eq!(column(&hf, 2, 1), vec![(0, 3, 6), (12, 15, 8), (20, 30, 7)]);
+
+    hf.retain_spans(|_, _, sp| sp.area() != 8);
+    assert_eq!(column(&hf, 2, 1), vec![(0, 3, 6), (20, 30, 7)]);
+
+    hf.clear_column(2, 1);
+    assert_eq!(hf.spans().count(), 0);
+}
+
+#[test]
+fn test_heightfield_insert_many() {
+    let mut ctx = RcContext::new(true);
+    let mut hf = new_heightfield(&mut ctx, 32);
+
+    // More spans than a single pool holds, so new pools are allocated.
+    for z in 0..32 {
+        for x in 0..32 {
+            for y in 0..3 {
+                hf.insert_span(x, z, y * 10, y * 10 + 2, RC_WALKABLE_AREA as u32)
+                    .unwrap();
+            }
+        }
+    }
+    assert_eq!(hf.spans().count(), 32 * 32 * 3);
+    assert_eq!(rc_get_height_field_span_count(&mut ctx, &hf), 32 * 32 * 3);
+
+    // Removed spans are reused.
+    hf.retain_spans(|x, _, _| x % 2 == 0);
+    for z in 0..32 {
+        for x in (1..32).step_by(2) {
+            hf.insert_span(x, z, 0, 2, RC_WALKABLE_AREA as u32).unwrap();
+        }
+    }
+    assert_eq!(hf.spans().count(), 16 * 32 * 3 + 16 * 32);
+
+    let mut chf = RcCompactHeightfield::new();
+    rc_build_compact_heightfield(&mut ctx, 2, 1, &hf, &mut chf).unwrap();
+    assert_eq!(chf.span_count(), 16 * 32 * 3 + 16 * 32);
+}
+
+#[test]
+fn test_heightfield_iterators_match_span_lists() {
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = common::load_mesh("dungeon");
+    let cfg = common::solo_config(&mesh_loader);
+    let hf = common::build_solid(&mut ctx, &cfg, &mesh_loader);
+
+    // The iterators walk the same span lists as `get_span()` and `next()`, in the same order.
+    let mut expected = Vec::new();
+    for z in 0..hf.height() {
+        for x in 0..hf.width() {
+            let mut column_spans = Vec::new();
+            let mut span = hf.get_span((x + z * hf.width()) as usize);
+            while let Some(sp) = span {
+                column_spans.push((sp.smin(), sp.smax(), sp.area()));
+                expected.push((x, z, sp.smin(), sp.smax(), sp.area()));
+                span = sp.next();
+            }
+            assert_eq!(column(&hf, x, z), column_spans);
+        }
+    }
+    assert!(!expected.is_empty());
+    let spans: Vec<_> = hf
+        .spans()
+        .map(|(x, z, sp)| (x, z, sp.smin(), sp.smax(), sp.area()))
+        .collect();
+    assert_eq!(spans, expected);
+}
@@ -0,0 +1,79 @@
+use recastnavigation_rs::demo::*;
+use recastnavigation_rs::RcObjError;
+
+#[test]
+fn test_obj_mesh_same_as_cpp_loader() {
+    for name in ["dungeon", "nav_test", "undulating"] {
+        let path = format!("./resource/{}.obj", name);
+        let mut cpp_mesh = RcMeshLoaderObj::default();
+        assert!(cpp_mesh.load(&path));
+        let mesh = RcObjMesh::load(&path).unwrap();
+        assert_eq!(mesh.get_verts(), cpp_mesh.get_verts());
+        assert_eq!(mesh.get_tris(), cpp_mesh.get_tris());
+        assert_eq!(mesh.tri_groups().len(), mesh.get_tri_count() as usize);
+    }
+}
+
+#[test]
+fn test_obj_mesh_groups() {
+    let mesh = RcObjMesh::load("./resource/nav_test.obj").unwrap();
+    assert_eq!(mesh.groups().len(), 3);
+    assert_eq!(mesh.materials(), &["".to_string(), "Default".to_string()]);
+    assert_eq!(mesh.tri_materials().len(), mesh.get_tri_count() as usize);
+    assert!((0..mesh.get_tri_count() as usize).all(|i| mesh.tri_material(i) == "Default"));
+}
+
+#[test]
+fn test_obj_mesh_parse() {
+    let text = "\
+# comment
+v 0 0 0
+v 1 0 0
+v 1 0 1
+v 0 0 1
+g floor
+usemtl grass
+f 1/1/1 2/2/2 3/3/3 4/4/4
+g wall # trailing comment
+usemtl stone
+f -4 -2 -1
+";
+    let mesh = RcObjMesh::parse(text).unwrap();
+    assert_eq!(mesh.get_vert_count(), 4);
+    assert_eq!(mesh.get_tris(), &[[0, 1, 2], [0, 2, 3], [0, 2, 3]]);
+    assert_eq!(mesh.tri_group(0), "floor");
+    assert_eq!(mesh.tri_group(1), "floor");
+    assert_eq!(mesh.tri_group(2), "wall");
+    assert_eq!(mesh.tri_material(1), "grass");
+    assert_eq!(mesh.tri_material(2), "stone");
+    assert_eq!(mesh.get_normals()[0], [0.0, -1.0, 0.0]);
+
+    let areas = mesh.tri_areas_by_material(|name| if name == "grass" { 1 } else { 2 });
+    assert_eq!(areas, vec![1, 1, 2]);
+}
+
+#[test]
+fn test_obj_mesh_errors() {
+    assert!(matches!(
+        RcObjMesh::load("./resource/not_exist.obj"),
+        Err(RcObjError::Io(_))
+    ));
+    assert!(matches!(RcObjMesh::parse(""), Err(RcObjError::Empty)));
+    assert!(matches!(RcObjMesh::parse("v 0 0 0\n"), Err(RcObjError::Empty)));
+    assert!(matches!(
+        RcObjMesh::parse("v 0 0 0\nv 1 0 0\nv 1 x 1\n"),
+        Err(RcObjError::Parse { line: 3, .. })
+    ));
+    assert!(matches!(
+        RcObjMesh::parse("v 0 0 0\nv 1 0 0\nv 1 0 1\n\nf 1 2\n"),
+        Err(RcObjError::Parse { line: 5, .. })
+    ));
+    assert!(matches!(
+        RcObjMesh::parse("v 0 0 0\nv 1 0 0\nv 1 0 1\nf 1 2 4\n"),
+        Err(RcObjError::Parse { line: 4, .. })
+    ));
+    assert!(matches!(
+        RcObjMesh::parse("v 0 0 0\nv 1 0 0\nv 1 0 1\nf 0 1 2\n"),
+        Err(RcObjError::Parse { line: 4, .. })
+    ));
+}
@@ -0,0 +1,225 @@
+use recastnavigation_rs::detour::*;
+use recastnavigation_rs::recast::*;
+use recastnavigation_rs::RNError;
+
+mod common;
+use common::*;
+
+fn build_meshes() -> (RcPolyMesh, RcPolyMeshDetail) {
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = load_mesh("dungeon");
+    let cfg = solo_config(&mesh_loader);
+    let solid = build_solid(&mut ctx, &cfg, &mesh_loader);
+    let chf = build_chf(&mut ctx, &cfg, &solid);
+    let cset = build_cset(&mut ctx, &cfg, &chf);
+    let pmesh = build_pmesh(&mut ctx, &cfg, &cset);
+    let dmesh = build_dmesh(&mut ctx, &cfg, &pmesh, &chf);
+    (pmesh, dmesh)
+}
+
+// The verts of a poly, without the unused slots.
+fn poly_verts(pmesh: &RcPolyMesh, poly: usize) -> Vec<u16> {
+    let nvp = pmesh.nvp();
+    pmesh.polys()[poly * 2 * nvp..poly * 2 * nvp + nvp]
+        .iter()
+        .copied()
+        .take_while(|v| *v != RC_MESH_NULL_IDX)
+        .collect()
+}
+
+#[test]
+fn test_poly_mesh_validate() {
+    let (pmesh, dmesh) = build_meshes();
+    let report = pmesh.validate();
+    assert!(report.is_valid(), "{:?}", report);
+    assert!(report
+        .warnings()
+        .all(|issue| matches!(issue, RcMeshIssue::NvpLimitReached { .. })));
+    let report = dmesh.validate(&pmesh);
+    assert!(report.is_valid(), "{:?}", report);
+    assert!(report.issues.is_empty());
+
+    assert_eq!(RcPolyMesh::new().validate().issues[0], RcMeshIssue::NoPolys);
+    let report = RcPolyMeshDetail::new().validate(&pmesh);
+    assert_eq!(
+        report.issues,
+        vec![RcMeshIssue::DetailMeshCount {
+            npolys: pmesh.npolys(),
+            nmeshes: 0,
+        }]
+    );
+}
+
+#[test]
+fn test_poly_mesh_validate_errors() {
+    // The meshes are built once, each case corrupts a copy.
+    let (pmesh, dmesh) = build_meshes();
+    let pmesh_data = RcPolyMeshData::from_poly_mesh(&pmesh);
+    let dmesh_data = RcPolyMeshDetailData::from_poly_mesh_detail(&dmesh);
+    let build_meshes = || {
+        (
+            pmesh_data.to_poly_mesh().unwrap(),
+            dmesh_data.to_poly_mesh_detail().unwrap(),
+        )
+    };
+    let nvp = pmesh.nvp();
+
+    // A vertex index out of range.
+    let (mut pmesh, _) = build_meshes();
+    let nverts = pmesh.nverts() as u16;
+    pmesh.polys_mut()[1] = nverts;
+    let report = pmesh.validate();
+    assert!(!report.is_valid());
+    assert!(report
+        .issues
+        .contains(&RcMeshIssue::VertOutOfRange { poly: 0, vert: 1 }));
+
+    // A degenerate poly.
+    let (mut pmesh, _) = build_meshes();
+    pmesh.polys_mut()[1] = pmesh.polys()[0];
+    assert!(pmesh
+        .validate()
+        .issues
+        .contains(&RcMeshIssue::DuplicateVert { poly: 0, vert: 1 }));
+
+    // A broken neighbour link, both sides report it.
+    let (mut pmesh, _) = build_meshes();
+    let (poly, edge) = (0..pmesh.npolys())
+        .flat_map(|poly| (0..nvp).map(move |edge| (poly, edge)))
+        .find(|&(poly, edge)| pmesh.polys()[poly * 2 * nvp + nvp + edge] < 0x8000)
+        .unwrap();
+    let other = pmesh.polys()[poly * 2 * nvp + nvp + edge] as usize;
+    pmesh.polys_mut()[poly * 2 * nvp + nvp + edge] = RC_MESH_NULL_IDX;
+    let report = pmesh.validate();
+    assert!(report
+        .errors()
+        .all(|issue| matches!(issue, RcMeshIssue::BadNeighbour { .. })));
+    assert!(report
+        .errors()
+        .any(|issue| matches!(issue, RcMeshIssue::BadNeighbour { poly, .. } if *poly == other)));
+    pmesh.polys_mut()[poly * 2 * nvp + nvp + edge] = pmesh.npolys() as u16;
+    assert!(pmesh
+        .validate()
+        .issues
+        .contains(&RcMeshIssue::NeighbourOutOfRange { poly, edge }));
+
+    // A vertex mirrored across the chord of its neighbours, inside the poly.
+    let (mut pmesh, dmesh) = build_meshes();
+    let pos = |pmesh: &RcPolyMesh, v: u16| {
+        let v = pmesh.verts()[v as usize];
+        [v[0] as i64, v[1] as i64, v[2] as i64]
+    };
+    let area2 = |pts: &[[i64; 3]]| -> i64 {
+        (0..pts.len())
+            .map(|i| {
+                let (a, b) = (pts[i], pts[(i + 1) % pts.len()]);
+                a[0] * b[2] - b[0] * a[2]
+            })
+            .sum()
+    };
+    // The cut triangle must be less than half of the poly, so the area keeps its sign.
+    let (poly, vert, mirrored) = (0..pmesh.npolys())
+        .flat_map(|poly| {
+            let verts = poly_verts(&pmesh, poly);
+            (0..verts.len()).map(move |vert| (poly, verts.clone(), vert))
+        })
+        .find_map(|(poly, verts, vert)| {
+            let n = verts.len();
+            let pts: Vec<_> = verts.iter().map(|v| pos(&pmesh, *v)).collect();
+            let (a, b, c) = (pts[(vert + n - 1) % n], pts[vert], pts[(vert + 1) % n]);
+            let tri = area2(&[a, b, c]);
+            let m = [a[0] + c[0] - b[0], b[1], a[2] + c[2] - b[2]];
+            let fits = m[0] >= 0 && m[2] >= 0 && m[0] < 0xffff && m[2] < 0xffff;
+            (tri != 0 && 2 * tri.abs() < area2(&pts).abs() && fits).then_some((poly, verts[vert], m))
+        })
+        .unwrap();
+    pmesh.verts_mut()[vert as usize] = mirrored.map(|c| c as u16);
+    assert!(pmesh
+        .validate()
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, RcMeshIssue::NonConvex { poly: p, .. } if *p == poly)));
+    // The detail tris still cover the old poly.
+    assert!(dmesh
+        .validate(&pmesh)
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, RcMeshIssue::DetailTriOutside { poly: p, .. } if *p == poly)));
+
+    // Detail tris escaping their poly, and out of range sub-meshes.
+    let (pmesh, mut dmesh) = build_meshes();
+    let vbase = dmesh.meshes()[0][0] as usize;
+    dmesh.verts_mut()[vbase][0] += 100.0;
+    let report = dmesh.validate(&pmesh);
+    assert!(report
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, RcMeshIssue::DetailTriOutside { poly: 0, .. })));
+    let ntris = dmesh.ntris() as u32;
+    dmesh.meshes_mut()[1][3] = ntris;
+    assert!(dmesh
+        .validate(&pmesh)
+        .issues
+        .contains(&RcMeshIssue::DetailOutOfRange { poly: 1 }));
+}
+
+fn create_params<'a>(pmesh: &'a RcPolyMesh, dmesh: &'a RcPolyMeshDetail) -> DtNavMeshCreateParams<'a> {
+    DtNavMeshCreateParams {
+        verts: Some(pmesh.verts()),
+        polys: Some(pmesh.polys()),
+        poly_areas: Some(pmesh.areas()),
+        poly_flags: Some(pmesh.flags()),
+        nvp: pmesh.nvp(),
+        detail_meshes: Some(dmesh.meshes()),
+        detail_verts: Some(dmesh.verts()),
+        detail_tris: Some(dmesh.tris()),
+        walkable_height: 2.0,
+        walkable_radius: 0.6,
+        walkable_climb: 0.9,
+        bmin: pmesh.bmin,
+        bmax: pmesh.bmax,
+        cs: pmesh.cs,
+        ch: pmesh.ch,
+        build_bv_tree: true,
+        ..DtNavMeshCreateParams::default()
+    }
+}
+
+#[test]
+fn test_create_nav_mesh_data_params() {
+    let (pmesh, dmesh) = build_meshes();
+
+    // The meshes of the build pipeline pass, with or without the detail mesh.
+    assert!(dt_create_nav_mesh_data(&mut create_params(&pmesh, &dmesh)).is_ok());
+    let mut params = create_params(&pmesh, &dmesh);
+    params.detail_meshes = None;
+    params.detail_verts = None;
+    params.detail_tris = None;
+    assert!(dt_create_nav_mesh_data(&mut params).is_ok());
+
+    // Indices out of the vertex or detail arrays are rejected.
+    let mut polys = pmesh.polys().to_vec();
+    polys[0] = pmesh.verts().len() as u16;
+    let mut params = create_params(&pmesh, &dmesh);
+    params.polys = Some(&polys);
+    assert!(matches!(
+        dt_create_nav_mesh_data(&mut params),
+        Err(RNError::InvalidParam)
+    ));
+
+    let mut meshes = dmesh.meshes().to_vec();
+    meshes[0][1] = dmesh.verts().len() as u32 + 1;
+    let mut params = create_params(&pmesh, &dmesh);
+    params.detail_meshes = Some(&meshes);
+    assert!(matches!(
+        dt_create_nav_mesh_data(&mut params),
+        Err(RNError::InvalidParam)
+    ));
+
+    let mut params = create_params(&pmesh, &dmesh);
+    params.detail_meshes = Some(&dmesh.meshes()[1..]);
+    assert!(matches!(
+        dt_create_nav_mesh_data(&mut params),
+        Err(RNError::InvalidParam)
+    ));
+}
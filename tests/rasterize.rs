@@ -0,0 +1,255 @@
+use recastnavigation_rs::recast::*;
+
+mod common;
+use common::*;
+
+const GRID_W: usize = 33;
+const GRID_H: usize = 25;
+
+fn sample_heights() -> Vec<f32> {
+    let mut heights = Vec::with_capacity(GRID_W * GRID_H);
+    for z in 0..GRID_H {
+        for x in 0..GRID_W {
+            heights.push((x as f32 * 0.35).sin() * 2.0 + (z as f32 * 0.5).cos() * 1.5 + (x * z) as f32 * 0.01);
+        }
+    }
+    heights
+}
+
+fn new_heightfield(ctx: &mut RcContext, bmin: [f32; 3], bmax: [f32; 3]) -> RcHeightfield {
+    let cs = 0.3;
+    let ch = 0.2;
+    let (w, h) = rc_calc_grid_size(&bmin, &bmax, cs);
+    let mut hf = RcHeightfield::new();
+    rc_create_heightfield(ctx, &mut hf, w, h, &bmin, &bmax, cs, ch).unwrap();
+    hf
+}
+
+fn rasterize_soup(ctx: &mut RcContext, grid: &RcHeightGrid, slope: f32) -> RcHeightfield {
+    let (bmin, bmax) = grid.bounds();
+    let mut hf = new_heightfield(ctx, bmin, bmax);
+    let mesh = grid.triangulate();
+    let areas = mesh.tri_areas.unwrap_or_else(|| {
+        let mut areas = vec![RC_NULL_AREA; mesh.tris.len()];
+        rc_mark_walkable_triangles(ctx, slope, &mesh.verts, &mesh.tris, &mut areas).unwrap();
+        areas
+    });
+    assert!(rc_rasterize_triangles_1(ctx, &mesh.verts, &mesh.tris, &areas, &mut hf, 1).unwrap());
+    hf
+}
+
+fn rasterize_grid(ctx: &mut RcContext, grid: &RcHeightGrid, slope: f32) -> RcHeightfield {
+    let (bmin, bmax) = grid.bounds();
+    let mut hf = new_heightfield(ctx, bmin, bmax);
+    rc_rasterize_height_grid(ctx, grid, slope, &mut hf, 1).unwrap();
+    hf
+}
+
+#[test]
+fn test_rasterize_height_grid() {
+    let mut ctx = RcContext::new(true);
+    let heights = sample_heights();
+    let grid = RcHeightGrid::new([-4.0, 1.0, 2.5], [0.7, 0.55], GRID_W, GRID_H, &heights);
+
+    let expected = dump_heightfield_state(&rasterize_soup(&mut ctx, &grid, 45.0));
+    let actual = dump_heightfield_state(&rasterize_grid(&mut ctx, &grid, 45.0));
+    assert!(!actual.spans.is_empty());
+    assert!(actual.spans.iter().any(|s| s.area == RC_NULL_AREA as u32));
+    assert!(actual.spans.iter().any(|s| s.area == RC_WALKABLE_AREA as u32));
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_rasterize_height_grid_areas_and_holes() {
+    let mut ctx = RcContext::new(true);
+    let heights = sample_heights();
+    let cells = (GRID_W - 1) * (GRID_H - 1);
+    let areas: Vec<u8> = (0..cells).map(|i| (i % 5) as u8 + 1).collect();
+    let holes: Vec<bool> = (0..cells).map(|i| i % 7 == 3 || (100..140).contains(&i)).collect();
+    let mut grid = RcHeightGrid::new([10.0, -3.0, -6.0], [0.45, 0.45], GRID_W, GRID_H, &heights);
+    grid.areas = Some(&areas);
+    grid.holes = Some(&holes);
+
+    let expected = dump_heightfield_state(&rasterize_soup(&mut ctx, &grid, 45.0));
+    let actual = dump_heightfield_state(&rasterize_grid(&mut ctx, &grid, 45.0));
+    assert_eq!(expected, actual);
+
+    let mesh = grid.triangulate();
+    let hole_count = holes.iter().filter(|h| **h).count();
+    assert_eq!(mesh.tris.len(), (cells - hole_count) * 2);
+    assert_eq!(mesh.tri_areas.map(|a| a.len()), Some(mesh.tris.len()));
+
+    grid.holes = Some(&holes[1..]);
+    let mut hf = new_heightfield(&mut ctx, grid.bounds().0, grid.bounds().1);
+    assert!(rc_rasterize_height_grid(&mut ctx, &grid, 45.0, &mut hf, 1).is_err());
+}
+
+fn shape_heightfield(ctx: &mut RcContext) -> RcHeightfield {
+    new_heightfield(ctx, [-5.0, -5.0, -5.0], [5.0, 5.0, 5.0])
+}
+
+fn column_spans(hf: &RcHeightfield, x: i32, z: i32) -> Vec<(u32, u32, u32)> {
+    let mut spans = Vec::new();
+    let mut span = hf.get_span((x + z * hf.width()) as usize);
+    while let Some(sp) = span {
+        spans.push((sp.smin(), sp.smax(), sp.area()));
+        span = sp.next();
+    }
+    spans
+}
+
+#[test]
+fn test_rasterize_box() {
+    let mut ctx = RcContext::new(true);
+    let mut hf = shape_heightfield(&mut ctx);
+    let center = [0.52, 0.13, -1.01];
+    let half_extents = [1.1, 0.77, 0.63];
+    rc_rasterize_box(&mut ctx, &center, &half_extents, &RC_BOX_AXES_IDENTITY, 7, &mut hf, 1).unwrap();
+
+    let cell = |v: f32, min: f32, size: f32| ((v - min) / size).floor() as i32;
+    let x_range =
+        cell(center[0] - half_extents[0], hf.bmin[0], hf.cs)..=cell(center[0] + half_extents[0], hf.bmin[0], hf.cs);
+    let z_range =
+        cell(center[2] - half_extents[2], hf.bmin[2], hf.cs)..=cell(center[2] + half_extents[2], hf.bmin[2], hf.cs);
+    let smin = ((center[1] - half_extents[1] - hf.bmin[1]) / hf.ch).floor() as u32;
+    let smax = ((center[1] + half_extents[1] - hf.bmin[1]) / hf.ch).ceil() as u32;
+    for z in 0..hf.height() {
+        for x in 0..hf.width() {
+            let spans = column_spans(&hf, x, z);
+            if x_range.contains(&x) && z_range.contains(&z) {
+                assert_eq!(spans, vec![(smin, smax, 7)], "column {} {}", x, z);
+            } else {
+                assert!(spans.is_empty(), "column {} {}", x, z);
+            }
+        }
+    }
+
+    // A box rotated by 45 degrees around y covers a diamond.
+    let mut hf = shape_heightfield(&mut ctx);
+    let s = std::f32::consts::FRAC_1_SQRT_2;
+    let axes = [[s, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, s]];
+    rc_rasterize_box(&mut ctx, &[0.01, 0.0, 0.01], &[1.0, 1.0, 1.0], &axes, 7, &mut hf, 1).unwrap();
+    let center_cell = ((0.01 - hf.bmin[0]) / hf.cs) as i32;
+    assert!(!column_spans(&hf, center_cell, center_cell).is_empty());
+    assert!(!column_spans(&hf, center_cell + 4, center_cell).is_empty());
+    assert!(column_spans(&hf, center_cell + 4, center_cell + 4).is_empty());
+
+    assert!(rc_rasterize_box(
+        &mut ctx,
+        &center,
+        &[-1.0, 1.0, 1.0],
+        &RC_BOX_AXES_IDENTITY,
+        7,
+        &mut hf,
+        1
+    )
+    .is_err());
+}
+
+#[test]
+fn test_rasterize_box_above_span_range() {
+    // The heightfield is taller than RC_SPAN_MAX_HEIGHT cells, the box is clamped to the top span.
+    let mut ctx = RcContext::new(true);
+    let mut hf = new_heightfield(&mut ctx, [-5.0, -5.0, -5.0], [5.0, 2000.0, 5.0]);
+    assert!((hf.bmax[1] - hf.bmin[1]) / hf.ch > RC_SPAN_MAX_HEIGHT as f32);
+    let (center, half_extents) = ([0.0, 1990.0, 0.0], [1.0, 20.0, 1.0]);
+    rc_rasterize_box(&mut ctx, &center, &half_extents, &RC_BOX_AXES_IDENTITY, 7, &mut hf, 1).unwrap();
+
+    let x = ((center[0] - hf.bmin[0]) / hf.cs) as i32;
+    let z = ((center[2] - hf.bmin[2]) / hf.cs) as i32;
+    assert_eq!(
+        column_spans(&hf, x, z),
+        vec![(RC_SPAN_MAX_HEIGHT - 1, RC_SPAN_MAX_HEIGHT, 7)]
+    );
+}
+
+#[test]
+fn test_rasterize_sphere_covers_tessellation() {
+    let mut ctx = RcContext::new(true);
+    let center = [0.37, 0.21, -0.43];
+    let radius = 2.3;
+
+    // A UV sphere inscribed in the sphere.
+    let (rings, sectors) = (16i32, 32i32);
+    let mut verts = Vec::new();
+    for r in 0..=rings {
+        let phi = std::f32::consts::PI * r as f32 / rings as f32;
+        for s in 0..sectors {
+            let theta = std::f32::consts::TAU * s as f32 / sectors as f32;
+            verts.push([
+                center[0] + radius * phi.sin() * theta.cos(),
+                center[1] + radius * phi.cos(),
+                center[2] + radius * phi.sin() * theta.sin(),
+            ]);
+        }
+    }
+    let mut tris = Vec::new();
+    for r in 0..rings {
+        for s in 0..sectors {
+            let i00 = r * sectors + s;
+            let i01 = r * sectors + (s + 1) % sectors;
+            let i10 = i00 + sectors;
+            let i11 = i01 + sectors;
+            tris.push([i00, i10, i11]);
+            tris.push([i00, i11, i01]);
+        }
+    }
+
+    let mut hull = shape_heightfield(&mut ctx);
+    rc_rasterize_convex_hull(&mut ctx, &verts, &tris, 5, &mut hull, 1).unwrap();
+    let mut sphere = shape_heightfield(&mut ctx);
+    rc_rasterize_sphere(&mut ctx, &center, radius, 5, &mut sphere, 1).unwrap();
+
+    let mut columns = 0;
+    for z in 0..hull.height() {
+        for x in 0..hull.width() {
+            let hull_spans = column_spans(&hull, x, z);
+            let sphere_spans = column_spans(&sphere, x, z);
+            assert!(hull_spans.len() <= 1 && sphere_spans.len() <= 1);
+            if let Some(&(smin, smax, _)) = hull_spans.first() {
+                let (sphere_min, sphere_max, area) = sphere_spans[0];
+                assert!(sphere_min <= smin && sphere_max >= smax, "column {} {}", x, z);
+                assert_eq!(area, 5);
+                columns += 1;
+            }
+        }
+    }
+    assert!(columns > 100);
+}
+
+#[test]
+fn test_rasterize_capsule() {
+    let mut ctx = RcContext::new(true);
+    let a = [0.41, -1.27, 0.16];
+    let b = [0.41, 1.63, 0.16];
+    let radius = 0.93;
+
+    // A vertical capsule is the union of a cylinder and two spheres.
+    let mut capsule = shape_heightfield(&mut ctx);
+    rc_rasterize_capsule(&mut ctx, &a, &b, radius, 3, &mut capsule, 1).unwrap();
+    let mut union = shape_heightfield(&mut ctx);
+    rc_rasterize_sphere(&mut ctx, &a, radius, 3, &mut union, 1).unwrap();
+    rc_rasterize_sphere(&mut ctx, &b, radius, 3, &mut union, 1).unwrap();
+    rc_rasterize_cylinder(&mut ctx, &a, radius, b[1] - a[1], 3, &mut union, 1).unwrap();
+    assert_eq!(dump_heightfield_state(&capsule), dump_heightfield_state(&union));
+
+    // A tilted capsule covers both end spheres and the segment between them.
+    let b = [2.13, 0.54, -1.72];
+    let mut capsule = shape_heightfield(&mut ctx);
+    rc_rasterize_capsule(&mut ctx, &a, &b, radius, 3, &mut capsule, 1).unwrap();
+    for end in [&a, &b] {
+        let mut sphere = shape_heightfield(&mut ctx);
+        rc_rasterize_sphere(&mut ctx, end, radius, 3, &mut sphere, 1).unwrap();
+        for z in 0..sphere.height() {
+            for x in 0..sphere.width() {
+                if let Some(&(smin, smax, _)) = column_spans(&sphere, x, z).first() {
+                    let (cmin, cmax, _) = column_spans(&capsule, x, z)[0];
+                    assert!(cmin <= smin && cmax >= smax, "column {} {}", x, z);
+                }
+            }
+        }
+    }
+
+    assert!(rc_rasterize_capsule(&mut ctx, &a, &b, 0.0, 3, &mut capsule, 1).is_err());
+    assert!(rc_rasterize_convex_hull(&mut ctx, &[a, b], &[[0, 1, 2]], 3, &mut capsule, 1).is_err());
+}
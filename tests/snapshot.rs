@@ -0,0 +1,175 @@
+#![cfg(feature = "rkyv")]
+
+use recastnavigation_rs::recast::*;
+
+mod common;
+use common::*;
+
+fn round_trip<T>(data: &T) -> T
+where
+    T: for<'a> rkyv::Serialize<
+        rkyv::api::high::HighSerializer<
+            rkyv::util::AlignedVec,
+            rkyv::ser::allocator::ArenaHandle<'a>,
+            rkyv::rancor::Error,
+        >,
+    >,
+    T::Archived: rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>,
+{
+    let buf = rkyv::to_bytes::<rkyv::rancor::Error>(data).unwrap();
+    let archived = unsafe { rkyv::access_unchecked::<T::Archived>(&buf) };
+    rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap()
+}
+
+#[test]
+fn test_snapshot_dungeon() {
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = load_mesh("dungeon");
+    let cfg = solo_config(&mesh_loader);
+
+    let solid = build_solid(&mut ctx, &cfg, &mesh_loader);
+    let chf = build_chf(&mut ctx, &cfg, &solid);
+    let cset = build_cset(&mut ctx, &cfg, &chf);
+    let pmesh = build_pmesh(&mut ctx, &cfg, &cset);
+    let dmesh = build_dmesh(&mut ctx, &cfg, &pmesh, &chf);
+
+    // Save, load and restore every stage.
+    let solid_data = round_trip(&RcHeightfieldData::from_heightfield(&solid));
+    let chf_data = round_trip(&RcCompactHeightfieldData::from_compact_heightfield(&chf));
+    let cset_data = round_trip(&RcContourSetData::from_contour_set(&cset));
+    let pmesh_data = round_trip(&RcPolyMeshData::from_poly_mesh(&pmesh));
+    let dmesh_data = round_trip(&RcPolyMeshDetailData::from_poly_mesh_detail(&dmesh));
+
+    let solid2 = solid_data.to_heightfield().unwrap();
+    let chf2 = chf_data.to_compact_heightfield().unwrap();
+    let cset2 = cset_data.to_contour_set().unwrap();
+    let pmesh2 = pmesh_data.to_poly_mesh().unwrap();
+    let dmesh2 = dmesh_data.to_poly_mesh_detail().unwrap();
+
+    assert_eq!(dump_heightfield_state(&solid), dump_heightfield_state(&solid2));
+    assert_eq!(
+        dump_compact_heightfield_state(&chf),
+        dump_compact_heightfield_state(&chf2)
+    );
+    assert_eq!(dump_contour_set_state(&cset), dump_contour_set_state(&cset2));
+    assert_eq!(dump_poly_mesh_state(&pmesh), dump_poly_mesh_state(&pmesh2));
+    assert_eq!(
+        dump_poly_mesh_detail_state(&dmesh),
+        dump_poly_mesh_detail_state(&dmesh2)
+    );
+
+    // Later stages built from restored structures are identical.
+    let chf3 = build_chf(&mut ctx, &cfg, &solid2);
+    assert_eq!(
+        dump_compact_heightfield_state(&chf),
+        dump_compact_heightfield_state(&chf3)
+    );
+    let cset3 = build_cset(&mut ctx, &cfg, &chf2);
+    assert_eq!(dump_contour_set_state(&cset), dump_contour_set_state(&cset3));
+    let pmesh3 = build_pmesh(&mut ctx, &cfg, &cset2);
+    assert_eq!(dump_poly_mesh_state(&pmesh), dump_poly_mesh_state(&pmesh3));
+    let dmesh3 = build_dmesh(&mut ctx, &cfg, &pmesh2, &chf2);
+    assert_eq!(
+        dump_poly_mesh_detail_state(&dmesh),
+        dump_poly_mesh_detail_state(&dmesh3)
+    );
+}
+
+#[test]
+fn test_snapshot_invalid_data() {
+    let empty = RcHeightfieldData::default().to_heightfield().unwrap();
+    assert_eq!(empty.width(), 0);
+
+    let mut solid = RcHeightfieldData {
+        width: 2,
+        height: 2,
+        counts: vec![0, 1, 0, 0],
+        spans: vec![[1, 4, RC_WALKABLE_AREA as u32]],
+        ..Default::default()
+    };
+    assert!(solid.to_heightfield().is_ok());
+    solid.counts.pop();
+    assert!(solid.to_heightfield().is_err());
+    solid.counts = vec![0, 2, 0, 0];
+    assert!(solid.to_heightfield().is_err());
+
+    let chf = RcCompactHeightfieldData {
+        width: 1,
+        height: 1,
+        cells: vec![[0, 2]],
+        spans: vec![[0, 0, 0, 0]],
+        areas: vec![RC_WALKABLE_AREA],
+        ..Default::default()
+    };
+    assert!(chf.to_compact_heightfield().is_err());
+    let mut solid = RcHeightfieldData {
+        width: 1 << 16,
+        height: 1 << 16,
+        ..Default::default()
+    };
+    assert!(solid.to_heightfield().is_err());
+    solid.width = -1;
+    assert!(solid.to_heightfield().is_err());
+}
+
+#[test]
+fn test_snapshot_invalid_indices() {
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = load_mesh("dungeon");
+    let cfg = solo_config(&mesh_loader);
+    let solid = build_solid(&mut ctx, &cfg, &mesh_loader);
+    let chf = build_chf(&mut ctx, &cfg, &solid);
+    let cset = build_cset(&mut ctx, &cfg, &chf);
+    let pmesh = build_pmesh(&mut ctx, &cfg, &cset);
+    let dmesh = build_dmesh(&mut ctx, &cfg, &pmesh, &chf);
+
+    let chf_data = RcCompactHeightfieldData::from_compact_heightfield(&chf);
+    let (x, z, i) = chf
+        .span_indices()
+        .find(|&(x, z, i)| chf.neighbour_at(x, z, i, 2).is_some())
+        .unwrap();
+    let con = chf_data.spans[i][2];
+    // A connection past the spans of the neighbour cell.
+    let mut data = chf_data.clone();
+    let count = chf.cell_spans(x + 1, z).len() as u32;
+    data.spans[i][2] = (con & !(0x3f << 12)) | (count << 12);
+    assert!(data.to_compact_heightfield().is_err());
+    // A cell past the spans, and a cell index overflowing with its count.
+    let mut data = chf_data.clone();
+    data.cells[0] = [chf_data.spans.len() as u32, 1];
+    assert!(data.to_compact_heightfield().is_err());
+    data.cells[0] = [u32::MAX, 2];
+    assert!(data.to_compact_heightfield().is_err());
+    // A connection out of the grid.
+    let mut data = chf_data.clone();
+    let first = chf.span_indices().find(|&(x, _, _)| x == 0).unwrap().2;
+    data.spans[first][2] &= !0x3f;
+    assert!(data.to_compact_heightfield().is_err());
+
+    let pmesh_data = RcPolyMeshData::from_poly_mesh(&pmesh);
+    let nvp = pmesh_data.nvp;
+    let mut data = pmesh_data.clone();
+    data.polys[0] = data.verts.len() as u16;
+    assert!(data.to_poly_mesh().is_err());
+    let mut data = pmesh_data.clone();
+    data.polys[nvp] = data.regs.len() as u16;
+    assert!(data.to_poly_mesh().is_err());
+    let mut data = pmesh_data.clone();
+    data.polys[2] = RC_MESH_NULL_IDX;
+    assert!(data.to_poly_mesh().is_err());
+    let mut data = pmesh_data.clone();
+    data.maxpolys = usize::MAX;
+    assert!(data.to_poly_mesh().is_err());
+
+    let dmesh_data = RcPolyMeshDetailData::from_poly_mesh_detail(&dmesh);
+    let mut data = dmesh_data.clone();
+    data.meshes[0][1] = data.verts.len() as u32 + 1;
+    assert!(data.to_poly_mesh_detail().is_err());
+    let mut data = dmesh_data.clone();
+    data.meshes[0][2] = u32::MAX;
+    assert!(data.to_poly_mesh_detail().is_err());
+    let mut data = dmesh_data.clone();
+    let [_, nv, tbase, _] = data.meshes[0];
+    data.tris[tbase as usize][0] = nv as u8;
+    assert!(data.to_poly_mesh_detail().is_err());
+}
@@ -3,6 +3,7 @@
 use recastnavigation_rs::demo::*;
 use recastnavigation_rs::detour::*;
 use recastnavigation_rs::recast::*;
+use recastnavigation_rs::{RNError, RcBuildStage};
 
 mod common;
 use common::*;
@@ -22,6 +23,130 @@ fn test_solo_mesh_undulating() {
     let _ = build_nav_mesh("solo_mesh", "undulating");
 }
 
+#[test]
+fn test_solo_mesh_builder() {
+    for name in ["dungeon", "nav_test", "undulating"] {
+        let mut mesh_loader = RcMeshLoaderObj::default();
+        mesh_loader.load(&format!("./resource/{}.obj", name));
+
+        let mut cfg = RcConfig::default();
+        cfg.cs = 0.3;
+        cfg.ch = 0.2;
+        cfg.walkable_slope_angle = 45.0;
+        cfg.walkable_height = (2.0 / cfg.ch).ceil() as i32;
+        cfg.walkable_climb = (0.9 / cfg.ch).floor() as i32;
+        cfg.walkable_radius = (0.6 / cfg.cs).floor() as i32;
+        cfg.max_edge_len = (12.0 / cfg.cs) as i32;
+        cfg.max_simplification_error = 1.3;
+        cfg.min_region_area = 8 * 8;
+        cfg.merge_region_area = 20 * 20;
+        cfg.max_verts_per_poly = 6;
+        cfg.detail_sample_dist = cfg.cs * 6.0;
+        cfg.detail_sample_max_error = cfg.ch * 1.0;
+
+        let mut builder = SoloMeshBuilder::new(cfg, &mesh_loader);
+        builder.walkable_height = 2.0;
+        builder.walkable_radius = 0.6;
+        builder.walkable_climb = 0.9;
+        builder.poly_flags = Some(&sample_poly_flags);
+
+        let mut ctx = RcContext::new(true);
+        let nav_mesh = builder.build(&mut ctx).unwrap();
+        compare_with_cpp_out(&nav_mesh, "solo_mesh", name).unwrap();
+    }
+}
+
+#[test]
+fn test_solo_mesh_builder_from_settings() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let settings = RcBuildSettings::default();
+    let mut builder = SoloMeshBuilder::from_settings(&settings, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    let mut ctx = RcContext::new(true);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+    compare_with_cpp_out(&nav_mesh, "solo_mesh", "dungeon").unwrap();
+}
+
+#[test]
+fn test_build_settings_validation() {
+    let cfg = RcBuildSettings::default().to_config().unwrap();
+    assert_eq!(cfg.walkable_height, 10);
+    assert_eq!(cfg.walkable_climb, 4);
+    assert_eq!(cfg.walkable_radius, 2);
+    assert_eq!(cfg.max_edge_len, 40);
+    assert_eq!(cfg.min_region_area, 64);
+    assert_eq!(cfg.merge_region_area, 400);
+
+    let invalid = [
+        RcBuildSettings {
+            agent_height: 0.4,
+            ..Default::default()
+        },
+        RcBuildSettings {
+            agent_max_slope: 90.0,
+            ..Default::default()
+        },
+        RcBuildSettings {
+            verts_per_poly: 7,
+            ..Default::default()
+        },
+        RcBuildSettings {
+            cell_size: 0.0,
+            ..Default::default()
+        },
+    ];
+    for settings in invalid {
+        assert!(matches!(settings.to_config(), Err(RNError::InvalidSettings(_))));
+    }
+
+    // A tiled mesh needs a tile size.
+    let mut settings = RcBuildSettings::default();
+    assert!(matches!(settings.to_tiled_config(), Err(RNError::InvalidSettings(_))));
+    settings.tile_size = -1;
+    assert!(matches!(settings.to_config(), Err(RNError::InvalidSettings(_))));
+    settings.tile_size = 32;
+    assert_eq!(settings.to_tiled_config().unwrap().tile_size, 32);
+}
+
+#[test]
+fn test_solo_mesh_builder_empty_input() {
+    let geometry = RcInputMesh::default();
+    let builder = SoloMeshBuilder::new(RcConfig::default(), &geometry);
+    let mut ctx = RcContext::new(true);
+    assert!(matches!(
+        builder.build(&mut ctx),
+        Err(RNError::BuildFailed(RcBuildStage::Input))
+    ));
+}
+
+#[test]
+fn test_solo_mesh_builder_invalid_input() {
+    let verts = [[0.0, 0.0, 0.0], [0.0, 0.0, 8.0], [8.0, 0.0, 8.0], [8.0, 0.0, 0.0]];
+    let mut ctx = RcContext::new(true);
+    for tris in [[[0, 1, 2], [0, 2, 4]], [[0, 1, 2], [0, -1, 3]]] {
+        let geometry = RcInputMesh::new(&verts, &tris);
+        let builder = SoloMeshBuilder::new(sample_config(), &geometry);
+        assert!(matches!(
+            builder.build(&mut ctx),
+            Err(RNError::BuildFailed(RcBuildStage::Input))
+        ));
+    }
+
+    let tris = [[0, 1, 2], [0, 2, 3]];
+    let geometry = RcInputMesh {
+        tri_areas: Some(&[RC_WALKABLE_AREA]),
+        ..RcInputMesh::new(&verts, &tris)
+    };
+    let builder = SoloMeshBuilder::new(sample_config(), &geometry);
+    assert!(matches!(
+        builder.build(&mut ctx),
+        Err(RNError::BuildFailed(RcBuildStage::Input))
+    ));
+}
+
 fn build_nav_mesh(folder: &str, name: &str) -> DtNavMesh {
     let cell_size = 0.3;
     let cell_height = 0.2;
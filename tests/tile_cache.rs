@@ -0,0 +1,788 @@
+use recastnavigation_rs::detour::*;
+use recastnavigation_rs::detour_tile_cache::*;
+use recastnavigation_rs::recast::*;
+use recastnavigation_rs::RNError;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+use common::*;
+
+const TILE_SIZE: i32 = 48;
+const MAX_LAYERS: i32 = 4;
+
+struct TileCacheScene {
+    tile_cache: DtTileCache,
+    nav_mesh: DtNavMesh,
+    tiles: (i32, i32),
+    // The first layer of the first tile with layers, to check the decompression.
+    first_layer: Option<(DtCompressedTileRef, Vec<u8>, Vec<u8>)>,
+}
+
+fn tile_config(bmin: &[f32; 3], bmax: &[f32; 3], tx: i32, ty: i32) -> RcConfig {
+    let mut cfg = sample_config();
+    cfg.tile_size = TILE_SIZE;
+    cfg.border_size = cfg.walkable_radius + 3;
+    cfg.width = cfg.tile_size + cfg.border_size * 2;
+    cfg.height = cfg.tile_size + cfg.border_size * 2;
+
+    let tcs = cfg.tile_size as f32 * cfg.cs;
+    let border = cfg.border_size as f32 * cfg.cs;
+    cfg.bmin = [
+        bmin[0] + tx as f32 * tcs - border,
+        bmin[1],
+        bmin[2] + ty as f32 * tcs - border,
+    ];
+    cfg.bmax = [
+        bmin[0] + (tx + 1) as f32 * tcs + border,
+        bmax[1],
+        bmin[2] + (ty + 1) as f32 * tcs + border,
+    ];
+    cfg
+}
+
+fn build_scene(compressor: Box<dyn DtTileCacheCompressor>) -> TileCacheScene {
+    build_scene_with(compressor, Box::new(DtDefaultMeshProcess::new()))
+}
+
+fn build_scene_with(
+    compressor: Box<dyn DtTileCacheCompressor>,
+    mesh_process: Box<dyn DtTileCacheMeshProcess>,
+) -> TileCacheScene {
+    build_scene_with_obstacles(compressor, mesh_process, 16)
+}
+
+fn build_scene_with_obstacles(
+    compressor: Box<dyn DtTileCacheCompressor>,
+    mesh_process: Box<dyn DtTileCacheMeshProcess>,
+    max_obstacles: i32,
+) -> TileCacheScene {
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = load_mesh("dungeon");
+    let (bmin, bmax) = rc_calc_bounds(mesh_loader.get_verts());
+    let base_cfg = tile_config(&bmin, &bmax, 0, 0);
+    let (gw, gh) = rc_calc_grid_size(&bmin, &bmax, base_cfg.cs);
+    let tiles = ((gw + TILE_SIZE - 1) / TILE_SIZE, (gh + TILE_SIZE - 1) / TILE_SIZE);
+
+    let params = DtTileCacheParams {
+        orig: bmin,
+        cs: base_cfg.cs,
+        ch: base_cfg.ch,
+        width: TILE_SIZE,
+        height: TILE_SIZE,
+        walkable_height: 2.0,
+        walkable_radius: 0.6,
+        walkable_climb: 0.9,
+        max_simplification_error: base_cfg.max_simplification_error,
+        max_tiles: tiles.0 * tiles.1 * MAX_LAYERS,
+        max_obstacles,
+    };
+    let mut tile_cache = DtTileCache::with_params(&params, compressor, mesh_process).unwrap();
+    assert_eq!(tile_cache.params(), &params);
+
+    let tcs = TILE_SIZE as f32 * base_cfg.cs;
+    let mut nav_mesh = DtNavMesh::with_params(&DtNavMeshParams {
+        orig: bmin,
+        tile_width: tcs,
+        tile_height: tcs,
+        max_tiles: 128,
+        max_polys: 1 << 15,
+    })
+    .unwrap();
+
+    let mut first_layer = None;
+    for ty in 0..tiles.1 {
+        for tx in 0..tiles.0 {
+            let cfg = tile_config(&bmin, &bmax, tx, ty);
+            let solid = build_solid(&mut ctx, &cfg, &mesh_loader);
+            let chf = build_compact_heightfield(&mut ctx, &cfg, &solid);
+            let mut lset = RcHeightfieldLayerSet::new();
+            rc_build_heightfield_layers(&mut ctx, &chf, cfg.border_size, cfg.walkable_height, &mut lset).unwrap();
+
+            let datas = dt_build_tile_cache_layers(tile_cache.compressor(), &lset, tx, ty).unwrap();
+            assert_eq!(datas.len(), lset.layers().len());
+            for (i, (layer, data)) in lset.layers().iter().zip(datas).enumerate().take(MAX_LAYERS as usize) {
+                let header = DtTileCacheLayerHeader::from_layer(layer, tx, ty, i as i32).unwrap();
+                assert!(dt_build_tile_cache_layer(
+                    tile_cache.compressor(),
+                    &header,
+                    &layer.heights()[1..],
+                    layer.areas(),
+                    layer.cons()
+                )
+                .is_err());
+                let re = tile_cache.add_tile(data).unwrap();
+                assert_eq!(tile_cache.get_tile_by_ref(re).unwrap().header(), Some(&header));
+                if first_layer.is_none() {
+                    first_layer = Some((re, layer.heights().to_vec(), layer.areas().to_vec()));
+                }
+            }
+            tile_cache.build_nav_mesh_tiles_at(tx, ty, &mut nav_mesh).unwrap();
+        }
+    }
+
+    TileCacheScene {
+        tile_cache,
+        nav_mesh,
+        tiles,
+        first_layer,
+    }
+}
+
+fn poly_count(nav_mesh: &DtNavMesh) -> usize {
+    (0..nav_mesh.max_tiles())
+        .filter_map(|idx| nav_mesh.get_tile(idx))
+        .map(|tile| tile.polys().len())
+        .sum()
+}
+
+fn layer_count(tile_cache: &DtTileCache) -> usize {
+    (0..tile_cache.get_tile_count())
+        .filter_map(|i| tile_cache.get_tile(i))
+        .filter(|tile| tile.header().is_some())
+        .count()
+}
+
+fn update_until_done(tile_cache: &mut DtTileCache, nav_mesh: &mut DtNavMesh) {
+    for _ in 0..100 {
+        if tile_cache.update(0.1, nav_mesh).unwrap() {
+            return;
+        }
+    }
+    panic!("tile cache not up to date");
+}
+
+// Same as `get_obstacle_by_ref()`, without borrowing the tile cache mutably.
+fn find_obstacle(tile_cache: &DtTileCache, re: DtObstacleRef) -> Option<&DtTileCacheObstacle> {
+    (0..tile_cache.get_obstacle_count())
+        .filter_map(|i| tile_cache.get_obstacle(i))
+        .find(|ob| tile_cache.get_obstacle_ref(ob) == re)
+}
+
+// A point below the center of the first poly, to put an obstacle on it.
+fn obstacle_center(nav_mesh: &DtNavMesh) -> [f32; 3] {
+    let tile = (0..nav_mesh.max_tiles())
+        .filter_map(|idx| nav_mesh.get_tile(idx))
+        .find(|tile| !tile.polys().is_empty())
+        .unwrap();
+    let poly = &tile.polys()[0];
+    let mut center = [0.0; 3];
+    for i in 0..poly.vert_count as usize {
+        let v = tile.verts()[poly.verts[i] as usize];
+        center = [center[0] + v[0], center[1] + v[1], center[2] + v[2]];
+    }
+    let n = poly.vert_count as f32;
+    [center[0] / n, center[1] / n - 0.5, center[2] / n]
+}
+
+#[cfg(feature = "rkyv")]
+fn compressed_size(tile_cache: &DtTileCache) -> usize {
+    (0..tile_cache.get_tile_count())
+        .filter_map(|i| tile_cache.get_tile(i))
+        .map(|tile| tile.compressed().len())
+        .sum()
+}
+
+#[test]
+fn test_tile_cache_layers() {
+    let scene = build_scene(Box::new(DtPassthroughCompressor));
+    let tile_cache = &scene.tile_cache;
+    assert!(poly_count(&scene.nav_mesh) > 0);
+
+    let (re, heights, areas) = scene.first_layer.unwrap();
+    let tile = tile_cache.get_tile_by_ref(re).unwrap();
+    assert_eq!(tile_cache.get_tile_ref(tile), re);
+    let header = tile.header().unwrap().clone();
+    assert_eq!(header.magic, DT_TILECACHE_MAGIC);
+
+    let mut layer = dt_decompress_tile_cache_layer(tile_cache.compressor(), tile.data()).unwrap();
+    assert_eq!(layer.header(), &header);
+    assert_eq!(layer.heights(), &heights[..]);
+    assert_eq!(layer.areas(), &areas[..]);
+    dt_build_tile_cache_regions(&mut layer, 4).unwrap();
+    assert!(layer.reg_count() > 0);
+    assert!(dt_decompress_tile_cache_layer(tile_cache.compressor(), &tile.data()[..8]).is_err());
+    // A truncated grid, and data not aligned for the header.
+    let data = tile.data();
+    assert!(dt_decompress_tile_cache_layer(tile_cache.compressor(), &data[..data.len() - 1]).is_err());
+    let mut unaligned = vec![0u8; data.len() + 1];
+    unaligned[1..].copy_from_slice(data);
+    let layer = dt_decompress_tile_cache_layer(tile_cache.compressor(), &unaligned[1..]).unwrap();
+    assert_eq!(layer.header(), &header);
+    assert_eq!(layer.heights(), &heights[..]);
+
+    let (bmin, bmax) = tile_cache.calc_tight_tile_bounds(&header);
+    assert!(bmin[0] >= header.bmin[0] && bmax[0] <= header.bmax[0]);
+
+    let mut refs = [DtCompressedTileRef::default(); MAX_LAYERS as usize];
+    let count = tile_cache.get_tiles_at(header.tx, header.ty, &mut refs);
+    assert!(refs[..count].contains(&re));
+    let count = tile_cache.query_tiles(&bmin, &bmax, &mut refs).unwrap();
+    assert!(refs[..count].contains(&re));
+
+    assert!(layer_count(tile_cache) >= (scene.tiles.0 * scene.tiles.1) as usize / 2);
+}
+
+#[test]
+fn test_tile_cache_layer_header() {
+    let scene = build_scene(Box::new(DtPassthroughCompressor));
+    let (re, _, _) = scene.first_layer.unwrap();
+    let header = scene.tile_cache.get_tile_by_ref(re).unwrap().header().unwrap();
+    assert_eq!(header.magic, DT_TILECACHE_MAGIC);
+    assert_eq!(header.version, DT_TILECACHE_VERSION);
+    assert_eq!(header.tlayer, 0);
+    assert_eq!(header.width as i32, TILE_SIZE);
+    assert_eq!(header.height as i32, TILE_SIZE);
+    assert!(header.minx <= header.maxx && header.maxx < header.width);
+    assert!(header.miny <= header.maxy && header.maxy < header.height);
+    assert!(header.hmin <= header.hmax);
+    let tcs = TILE_SIZE as f32 * scene.tile_cache.params().cs;
+    let orig = scene.tile_cache.params().orig;
+    assert!((header.bmin[0] - (orig[0] + header.tx as f32 * tcs)).abs() < 1e-3);
+    assert!((header.bmin[2] - (orig[2] + header.ty as f32 * tcs)).abs() < 1e-3);
+    assert!((header.bmax[0] - header.bmin[0] - tcs).abs() < 1e-3);
+}
+
+#[test]
+fn test_tile_mesh_builder_tile_cache_layers() {
+    let scene = build_scene(Box::new(DtPassthroughCompressor));
+
+    let mut ctx = RcContext::new(true);
+    let mesh_loader = load_mesh("dungeon");
+    let (bmin, bmax) = rc_calc_bounds(mesh_loader.get_verts());
+    let mut builder = TileMeshBuilder::new(tile_config(&bmin, &bmax, 0, 0), &mesh_loader).unwrap();
+    builder.walkable_climb = 0.9;
+    assert_eq!(builder.tile_count(), scene.tiles);
+
+    let params = scene.tile_cache.params();
+    assert_eq!(
+        &builder.tile_cache_params(params.max_tiles, params.max_obstacles),
+        params
+    );
+
+    // The layers are precomputed, then added to a new tile cache as if they were loaded from a file.
+    let compressor = DtPassthroughCompressor;
+    let mut layers = Vec::new();
+    for ty in 0..scene.tiles.1 {
+        for tx in 0..scene.tiles.0 {
+            let datas = builder.build_tile_cache_layers(&mut ctx, &compressor, tx, ty).unwrap();
+            for data in datas.into_iter().take(MAX_LAYERS as usize) {
+                layers.push((tx, ty, data.as_slice().to_vec()));
+            }
+        }
+    }
+    assert!(builder.build_tile_cache_layers(&mut ctx, &compressor, -1, 0).is_err());
+
+    let mut tile_cache =
+        DtTileCache::with_params(params, Box::new(compressor), Box::new(DtDefaultMeshProcess::new())).unwrap();
+    let mut nav_mesh = DtNavMesh::with_params(scene.nav_mesh.params()).unwrap();
+    for (tx, ty, data) in &layers {
+        let layer = dt_decompress_tile_cache_layer(&compressor, data).unwrap();
+        assert_eq!((layer.header().tx, layer.header().ty), (*tx, *ty));
+        tile_cache.add_tile(DtBuf::from_slice(data).unwrap()).unwrap();
+    }
+    // A buffer shorter than the layer header is rejected before the tile cache reads it.
+    let short = DtBuf::from_slice(&layers[0].2[..mem::size_of::<DtTileCacheLayerHeader>() - 1]).unwrap();
+    assert!(matches!(tile_cache.add_tile(short), Err(RNError::InvalidParam)));
+    for ty in 0..scene.tiles.1 {
+        for tx in 0..scene.tiles.0 {
+            tile_cache.build_nav_mesh_tiles_at(tx, ty, &mut nav_mesh).unwrap();
+        }
+    }
+    assert_eq!(layer_count(&tile_cache), layer_count(&scene.tile_cache));
+    assert_eq!(poly_count(&nav_mesh), poly_count(&scene.nav_mesh));
+}
+
+#[test]
+fn test_tile_cache_obstacles() {
+    let TileCacheScene {
+        mut tile_cache,
+        mut nav_mesh,
+        ..
+    } = build_scene(Box::new(DtPassthroughCompressor));
+    let polys = poly_count(&nav_mesh);
+
+    let center = obstacle_center(&nav_mesh);
+
+    let cylinder = tile_cache.add_obstacle(&center, 1.5, 2.0).unwrap();
+    let ob = tile_cache.get_obstacle_by_ref(cylinder).unwrap();
+    assert!(ob.state() == DtObstacleState::DT_OBSTACLE_PROCESSING);
+    assert_eq!(
+        ob.shape(),
+        DtObstacleShape::Cylinder {
+            pos: center,
+            radius: 1.5,
+            height: 2.0
+        }
+    );
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    let ob = find_obstacle(&tile_cache, cylinder).unwrap();
+    assert!(ob.state() == DtObstacleState::DT_OBSTACLE_PROCESSED);
+    assert!(!ob.touched().is_empty());
+    assert!(ob.pending().is_empty());
+    let (bmin, bmax) = tile_cache.get_obstacle_bounds(ob);
+    assert_eq!(bmin, [center[0] - 1.5, center[1], center[2] - 1.5]);
+    assert_eq!(bmax, [center[0] + 1.5, center[1] + 2.0, center[2] + 1.5]);
+    assert_ne!(poly_count(&nav_mesh), polys);
+
+    tile_cache.remove_obstacle(cylinder).unwrap();
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    assert!(tile_cache.get_obstacle_by_ref(cylinder).is_none());
+    assert_eq!(poly_count(&nav_mesh), polys);
+
+    let boxed = tile_cache
+        .add_box_obstacle(
+            &[center[0] - 1.0, center[1], center[2] - 1.0],
+            &[center[0] + 1.0, center[1] + 2.0, center[2] + 1.0],
+        )
+        .unwrap();
+    let oriented = tile_cache
+        .add_oriented_box_obstacle(&center, &[1.0, 1.0, 0.5], 0.5)
+        .unwrap();
+    assert_ne!(boxed, oriented);
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    let ob = find_obstacle(&tile_cache, oriented).unwrap();
+    assert!(ob.obstacle_type() == DtObstacleType::DT_OBSTACLE_ORIENTED_BOX);
+    match ob.shape() {
+        DtObstacleShape::OrientedBox {
+            center: c,
+            half_extents,
+            rot_aux,
+        } => {
+            assert_eq!(c, center);
+            assert_eq!(half_extents, [1.0, 1.0, 0.5]);
+            // cos(0.25) * sin(-0.25) and cos(0.25)^2 - 0.5.
+            assert!((rot_aux[0] + 0.239_712_8).abs() < 1e-5);
+            assert!((rot_aux[1] - 0.438_791_3).abs() < 1e-5);
+        }
+        shape => panic!("unexpected shape {:?}", shape),
+    }
+    assert_eq!(tile_cache.get_obstacle_ref(ob), oriented);
+    assert_ne!(poly_count(&nav_mesh), polys);
+
+    tile_cache.remove_obstacle(boxed).unwrap();
+    tile_cache.remove_obstacle(oriented).unwrap();
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    assert_eq!(poly_count(&nav_mesh), polys);
+    assert!(tile_cache.remove_obstacle(DtObstacleRef::default()).is_ok());
+}
+
+fn update_manager_until_done(manager: &mut DtObstacleManager, nav_mesh: &mut DtNavMesh) -> Vec<DtRebuiltTile> {
+    let mut rebuilt = Vec::new();
+    for _ in 0..100 {
+        let update = manager.update(0.1, nav_mesh).unwrap();
+        assert!(update.rebuilt.len() <= manager.max_rebuilds());
+        rebuilt.extend(update.rebuilt);
+        if update.up_to_date {
+            return rebuilt;
+        }
+    }
+    panic!("obstacle manager not up to date");
+}
+
+// The navigation mesh tile refs of all layers of the tile cache.
+fn all_tile_refs(tile_cache: &DtTileCache, nav_mesh: &DtNavMesh) -> Vec<(i32, i32, i32, DtTileRef)> {
+    (0..tile_cache.get_tile_count())
+        .filter_map(|i| tile_cache.get_tile(i)?.header())
+        .map(|h| (h.tx, h.ty, h.tlayer, nav_mesh.get_tile_ref_at(h.tx, h.ty, h.tlayer)))
+        .collect()
+}
+
+#[test]
+fn test_obstacle_manager() {
+    let TileCacheScene {
+        tile_cache,
+        mut nav_mesh,
+        ..
+    } = build_scene(Box::new(DtPassthroughCompressor));
+    let polys = poly_count(&nav_mesh);
+    let center = obstacle_center(&nav_mesh);
+
+    let query = DtNavMeshQuery::with_mesh(&nav_mesh, 2048).unwrap();
+    let filter = DtQueryFilter::default();
+    let (poly_ref, _) = query.find_nearest_poly_1(&center, &[0.5, 1.0, 0.5], &filter).unwrap();
+    assert!(query.is_valid_poly_ref(poly_ref, &filter));
+
+    let mut manager = DtObstacleManager::new(tile_cache);
+    assert_eq!(manager.max_rebuilds(), 1);
+    let cylinder = DtObstacle::Cylinder {
+        pos: center,
+        radius: 1.5,
+        height: 2.0,
+    };
+    let handle = manager.add(cylinder).unwrap();
+    assert_eq!(manager.len(), 1);
+    assert_eq!(manager.get(handle), Some(&cylinder));
+    assert!(manager.state(handle) == Some(DtObstacleState::DT_OBSTACLE_PROCESSING));
+
+    let before = all_tile_refs(manager.tile_cache(), &nav_mesh);
+    let rebuilt = update_manager_until_done(&mut manager, &mut nav_mesh);
+    assert!(!rebuilt.is_empty());
+    // Only the tiles of the obstacle are diffed, but no rebuilt tile is missed.
+    let after = all_tile_refs(manager.tile_cache(), &nav_mesh);
+    let changed = before.iter().zip(after.iter()).filter(|(b, a)| b != a).count();
+    assert_eq!(rebuilt.len(), changed);
+    for tile in &rebuilt {
+        assert_ne!(tile.old_ref, tile.new_ref);
+        assert_eq!(nav_mesh.get_tile_ref_at(tile.tx, tile.ty, tile.tlayer), tile.new_ref);
+    }
+    assert!(manager.state(handle) == Some(DtObstacleState::DT_OBSTACLE_PROCESSED));
+    assert_ne!(poly_count(&nav_mesh), polys);
+
+    // The poly under the obstacle is gone, but the query still works on the rebuilt tiles.
+    assert!(!query.is_valid_poly_ref(poly_ref, &filter));
+    let (new_ref, _) = query.find_nearest_poly_1(&center, &[4.0, 2.0, 4.0], &filter).unwrap();
+    assert!(query.is_valid_poly_ref(new_ref, &filter));
+
+    // Moving keeps the handle.
+    let old_re = manager.obstacle_ref(handle).unwrap();
+    let moved = cylinder.translated(&[2.0, 0.0, 0.0]);
+    manager.move_obstacle(handle, moved).unwrap();
+    assert_ne!(manager.obstacle_ref(handle).unwrap(), old_re);
+    assert_eq!(manager.get(handle), Some(&moved));
+    assert!(!update_manager_until_done(&mut manager, &mut nav_mesh).is_empty());
+    assert_eq!(manager.iter().collect::<Vec<_>>(), vec![(handle, &moved)]);
+
+    assert_eq!(manager.remove(handle).unwrap(), moved);
+    assert!(manager.remove(handle).is_err());
+    assert!(manager.move_obstacle(handle, cylinder).is_err());
+    assert!(manager.is_empty());
+    manager.set_max_rebuilds(4);
+    update_manager_until_done(&mut manager, &mut nav_mesh);
+    assert_eq!(poly_count(&nav_mesh), polys);
+
+    // The slot is reused, the old handle stays invalid.
+    let boxed = DtObstacle::Box {
+        bmin: [center[0] - 1.0, center[1], center[2] - 1.0],
+        bmax: [center[0] + 1.0, center[1] + 2.0, center[2] + 1.0],
+    };
+    let handle2 = manager.add(boxed).unwrap();
+    assert_ne!(handle2, handle);
+    assert!(!manager.contains(handle));
+    assert!(manager.get(handle).is_none());
+
+    // The tile cache has room for 16 obstacles.
+    let oriented = DtObstacle::OrientedBox {
+        center,
+        half_extents: [1.0, 1.0, 0.5],
+        y_radians: 0.5,
+    };
+    for _ in 1..16 {
+        manager.add(oriented).unwrap();
+    }
+    assert!(manager.add(oriented).is_err());
+    assert_eq!(manager.len(), 16);
+}
+
+fn live_obstacle_count(tile_cache: &DtTileCache) -> usize {
+    (0..tile_cache.get_obstacle_count())
+        .filter_map(|i| tile_cache.get_obstacle(i))
+        .filter(|ob| ob.state() != DtObstacleState::DT_OBSTACLE_EMPTY)
+        .count()
+}
+
+#[test]
+fn test_obstacle_manager_request_budget() {
+    let TileCacheScene {
+        tile_cache,
+        mut nav_mesh,
+        ..
+    } = build_scene_with_obstacles(
+        Box::new(DtPassthroughCompressor),
+        Box::new(DtDefaultMeshProcess::new()),
+        128,
+    );
+    let center = obstacle_center(&nav_mesh);
+    let mut manager = DtObstacleManager::new(tile_cache);
+
+    // A box over several tiles, so one rebuild per update leaves tiles queued.
+    manager
+        .add(DtObstacle::Box {
+            bmin: [center[0] - 20.0, center[1], center[2] - 20.0],
+            bmax: [center[0] + 20.0, center[1] + 2.0, center[2] + 20.0],
+        })
+        .unwrap();
+    assert!(!manager.update(0.1, &mut nav_mesh).unwrap().up_to_date);
+
+    // The tile cache keeps these requests until the queued tiles are rebuilt, so they still count after
+    // the next update.
+    let cylinder = DtObstacle::Cylinder {
+        pos: center,
+        radius: 0.5,
+        height: 2.0,
+    };
+    let handle = manager.add(cylinder).unwrap();
+    assert!(!manager.update(0.1, &mut nav_mesh).unwrap().up_to_date);
+
+    // Moves fail on the manager budget, never halfway in the tile cache.
+    let mut moves = 0;
+    let err = loop {
+        match manager.move_obstacle(handle, cylinder.translated(&[0.1 * moves as f32, 0.0, 0.0])) {
+            Ok(()) => moves += 1,
+            Err(err) => break err,
+        }
+    };
+    assert!(matches!(err, RNError::BufferTooSmall));
+    assert_eq!(moves, 31);
+    assert_eq!(manager.len(), 2);
+
+    update_manager_until_done(&mut manager, &mut nav_mesh);
+    assert_eq!(live_obstacle_count(manager.tile_cache()), 2);
+    assert!(manager.state(handle) == Some(DtObstacleState::DT_OBSTACLE_PROCESSED));
+    manager.move_obstacle(handle, cylinder).unwrap();
+}
+
+fn all_polys(nav_mesh: &DtNavMesh) -> Vec<DtPoly> {
+    (0..nav_mesh.max_tiles())
+        .filter_map(|idx| nav_mesh.get_tile(idx))
+        .flat_map(|tile| tile.polys().iter().cloned())
+        .collect()
+}
+
+fn off_mesh_count(nav_mesh: &DtNavMesh) -> usize {
+    (0..nav_mesh.max_tiles())
+        .filter_map(|idx| nav_mesh.get_tile(idx))
+        .map(|tile| tile.off_mesh_cons().len())
+        .sum()
+}
+
+// Flags by area, counting the processed tiles.
+struct AreaFlagsProcess(Arc<AtomicUsize>);
+
+impl DtTileCacheMeshProcess for AreaFlagsProcess {
+    fn process<'a>(
+        &'a mut self,
+        params: &mut DtNavMeshCreateParams<'a>,
+        poly_areas: &mut [u8],
+        poly_flags: &mut [u16],
+    ) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        assert!(params.verts.is_some() && params.polys.is_some());
+        assert!(params.poly_flags.is_none() && params.poly_areas.is_none());
+        assert_eq!(poly_areas.len(), poly_flags.len());
+        for (area, flags) in poly_areas.iter().zip(poly_flags.iter_mut()) {
+            *flags = if *area == DT_TILECACHE_WALKABLE_AREA { 0x3 } else { 0x4 };
+        }
+    }
+}
+
+#[test]
+fn test_tile_cache_mesh_process() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let TileCacheScene {
+        mut tile_cache,
+        mut nav_mesh,
+        ..
+    } = build_scene_with(
+        Box::new(DtPassthroughCompressor),
+        Box::new(AreaFlagsProcess(calls.clone())),
+    );
+    let built = calls.load(Ordering::Relaxed);
+    assert!(built > 0);
+    let check_flags = |nav_mesh: &DtNavMesh| {
+        for poly in all_polys(nav_mesh) {
+            let expected = if poly.area() == DT_TILECACHE_WALKABLE_AREA {
+                0x3
+            } else {
+                0x4
+            };
+            assert_eq!(poly.flags, expected);
+        }
+    };
+    check_flags(&nav_mesh);
+
+    // The flags are set again on the rebuilt tiles.
+    let center = obstacle_center(&nav_mesh);
+    tile_cache.add_obstacle(&center, 1.5, 2.0).unwrap();
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    assert!(calls.load(Ordering::Relaxed) > built);
+    check_flags(&nav_mesh);
+
+    // Closures work too.
+    let process = |_: &mut DtNavMeshCreateParams<'_>, _: &mut [u8], poly_flags: &mut [u16]| poly_flags.fill(0x8);
+    let TileCacheScene { nav_mesh, .. } = build_scene_with(Box::new(DtPassthroughCompressor), Box::new(process));
+    assert!(all_polys(&nav_mesh).iter().all(|poly| poly.flags == 0x8));
+}
+
+#[test]
+fn test_tile_cache_off_mesh_process() {
+    let scene = build_scene(Box::new(DtPassthroughCompressor));
+    assert_eq!(off_mesh_count(&scene.nav_mesh), 0);
+    let center = obstacle_center(&scene.nav_mesh);
+    let start = [center[0], center[1] + 0.5, center[2]];
+
+    let mut off_mesh = OffMeshConnectionSet::new();
+    off_mesh.add(OffMeshConnection {
+        start,
+        end: [start[0] + 1.0, start[1], start[2]],
+        radius: 0.6,
+        bidirectional: true,
+        area: DT_TILECACHE_WALKABLE_AREA,
+        flags: 1,
+        user_id: 7,
+    });
+    let process = DtDefaultMeshProcess::with_off_mesh(off_mesh);
+    assert_eq!(process.off_mesh().len(), 1);
+    let TileCacheScene {
+        mut tile_cache,
+        mut nav_mesh,
+        ..
+    } = build_scene_with(Box::new(DtPassthroughCompressor), Box::new(process));
+    assert_eq!(off_mesh_count(&nav_mesh), 1);
+    assert!(all_polys(&nav_mesh).iter().all(|poly| poly.flags == 1));
+
+    // The connection is kept when its tile is rebuilt.
+    let [tx, ty] = nav_mesh.calc_tile_loc(&start);
+    let tile_refs: Vec<_> = (0..MAX_LAYERS).map(|l| nav_mesh.get_tile_ref_at(tx, ty, l)).collect();
+    tile_cache.add_obstacle(&center, 0.3, 2.0).unwrap();
+    update_until_done(&mut tile_cache, &mut nav_mesh);
+    assert!((0..MAX_LAYERS).any(|l| nav_mesh.get_tile_ref_at(tx, ty, l) != tile_refs[l as usize]));
+    assert_eq!(off_mesh_count(&nav_mesh), 1);
+}
+
+// Sets off-mesh connections whose radii are missing.
+struct MismatchedOffMeshProcess {
+    verts: Vec<DtAABB>,
+    rad: Vec<f32>,
+    flags: Vec<u16>,
+    areas: Vec<u8>,
+    dir: Vec<u8>,
+    user_id: Vec<u32>,
+}
+
+impl DtTileCacheMeshProcess for MismatchedOffMeshProcess {
+    fn process<'a>(
+        &'a mut self,
+        params: &mut DtNavMeshCreateParams<'a>,
+        _poly_areas: &mut [u8],
+        poly_flags: &mut [u16],
+    ) {
+        poly_flags.fill(1);
+        let center = [
+            (params.bmin[0] + params.bmax[0]) * 0.5,
+            params.bmin[1],
+            (params.bmin[2] + params.bmax[2]) * 0.5,
+        ];
+        self.verts = vec![DtAABB { a: center, b: center }; 4];
+        params.off_mesh_con_verts = Some(&self.verts);
+        params.off_mesh_con_rad = Some(&self.rad);
+        params.off_mesh_con_flags = Some(&self.flags);
+        params.off_mesh_con_areas = Some(&self.areas);
+        params.off_mesh_con_dir = Some(&self.dir);
+        params.off_mesh_con_user_id = Some(&self.user_id);
+    }
+}
+
+#[test]
+fn test_tile_cache_invalid_mesh_process() {
+    let expected = poly_count(&build_scene(Box::new(DtPassthroughCompressor)).nav_mesh);
+
+    // Inconsistent changes are dropped, the tiles are built from the original params.
+    let process = MismatchedOffMeshProcess {
+        verts: Vec::new(),
+        rad: vec![0.5; 1],
+        flags: vec![1; 4],
+        areas: vec![DT_TILECACHE_WALKABLE_AREA; 4],
+        dir: vec![1; 4],
+        user_id: vec![0; 4],
+    };
+    let scene = build_scene_with(Box::new(DtPassthroughCompressor), Box::new(process));
+    assert_eq!(off_mesh_count(&scene.nav_mesh), 0);
+    assert_eq!(poly_count(&scene.nav_mesh), expected);
+    assert!(all_polys(&scene.nav_mesh).iter().all(|poly| poly.flags == 1));
+
+    // Same for geometry changes which no longer match the poly count.
+    let process = |params: &mut DtNavMeshCreateParams<'_>, _: &mut [u8], poly_flags: &mut [u16]| {
+        poly_flags.fill(1);
+        params.nvp = 3;
+    };
+    let scene = build_scene_with(Box::new(DtPassthroughCompressor), Box::new(process));
+    assert_eq!(poly_count(&scene.nav_mesh), expected);
+    let process = |params: &mut DtNavMeshCreateParams<'_>, _: &mut [u8], _: &mut [u16]| {
+        params.verts = params.verts.map(|verts| &verts[..2]);
+    };
+    let scene = build_scene_with(Box::new(DtPassthroughCompressor), Box::new(process));
+    assert_eq!(poly_count(&scene.nav_mesh), expected);
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn test_tile_cache_deflate() {
+    let passthrough = build_scene(Box::new(DtPassthroughCompressor));
+    let deflate = build_scene(Box::new(DtDeflateCompressor::default()));
+    assert!(compressed_size(&deflate.tile_cache) < compressed_size(&passthrough.tile_cache));
+    assert_eq!(poly_count(&deflate.nav_mesh), poly_count(&passthrough.nav_mesh));
+
+    let (re, heights, areas) = deflate.first_layer.unwrap();
+    let tile = deflate.tile_cache.get_tile_by_ref(re).unwrap();
+    let layer = dt_decompress_tile_cache_layer(deflate.tile_cache.compressor(), tile.data()).unwrap();
+    assert_eq!(layer.heights(), &heights[..]);
+    assert_eq!(layer.areas(), &areas[..]);
+
+    // The layers of another compressor are rejected.
+    let (re, _, _) = passthrough.first_layer.unwrap();
+    let tile = passthrough.tile_cache.get_tile_by_ref(re).unwrap();
+    assert!(dt_decompress_tile_cache_layer(&DtDeflateCompressor::default(), tile.data()).is_err());
+}
+
+// A layer like buffer: ramped heights, walkable areas with holes, repeated connections.
+#[cfg(feature = "rkyv")]
+fn sample_layer_buffer() -> Vec<u8> {
+    (0..3 * 32 * 32u32)
+        .map(|i| match i / 1024 {
+            0 => (i % 32 + i / 32 % 32 / 4) as u8,
+            1 if i % 7 == 0 => 0,
+            1 => 63,
+            _ => (i % 16) as u8,
+        })
+        .collect()
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn test_deflate_compressor_stable() {
+    // Lockstep peers exchange the compressed layers, the bytes must not change across platforms.
+    const EXPECTED: [u8; 101] = [
+        120, 156, 237, 213, 183, 13, 192, 48, 12, 68, 81, 57, 231, 156, 115, 246, 26, 218, 127, 43, 195, 173, 26, 86,
+        87, 137, 108, 14, 31, 111, 0, 10, 195, 180, 108, 199, 245, 252, 32, 140, 226, 36, 205, 242, 162, 172, 234, 166,
+        237, 250, 97, 156, 230, 101, 221, 4, 216, 9, 222, 209, 78, 240, 129, 118, 130, 79, 180, 19, 124, 161, 157, 224,
+        27, 237, 4, 63, 104, 39, 248, 69, 187, 252, 79, 72, 30, 30, 30, 13, 71, 253, 207, 220, 220, 220, 250, 244, 7,
+        142, 78, 66, 34,
+    ];
+
+    let compressor = DtDeflateCompressor::default();
+    let buffer = sample_layer_buffer();
+    let mut compressed = vec![0; compressor.max_compressed_size(buffer.len())];
+    let size = compressor.compress(&buffer, &mut compressed).unwrap();
+    assert_eq!(&compressed[..size], &EXPECTED[..]);
+
+    let mut decompressed = vec![0; buffer.len()];
+    assert_eq!(
+        compressor.decompress(&EXPECTED, &mut decompressed).unwrap(),
+        buffer.len()
+    );
+    assert_eq!(decompressed, buffer);
+
+    let mut small = vec![0; buffer.len() - 1];
+    assert!(compressor.decompress(&EXPECTED, &mut small).is_err());
+    assert!(compressor.decompress(&EXPECTED[..50], &mut decompressed).is_err());
+    assert!(compressor.compress(&buffer, &mut compressed[..50]).is_err());
+}
+
+#[test]
+fn test_passthrough_compressor() {
+    let compressor = DtPassthroughCompressor;
+    let buffer: Vec<u8> = (0..=255).collect();
+    let mut compressed = vec![0; compressor.max_compressed_size(buffer.len())];
+    assert_eq!(compressor.compress(&buffer, &mut compressed).unwrap(), buffer.len());
+    assert_eq!(compressed, buffer);
+
+    let mut decompressed = vec![0; buffer.len()];
+    assert_eq!(
+        compressor.decompress(&compressed, &mut decompressed).unwrap(),
+        buffer.len()
+    );
+    assert_eq!(decompressed, buffer);
+    assert!(compressor.decompress(&compressed, &mut decompressed[..10]).is_err());
+}
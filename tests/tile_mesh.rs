@@ -4,33 +4,485 @@
 use recastnavigation_rs::demo::*;
 use recastnavigation_rs::detour::*;
 use recastnavigation_rs::recast::*;
+use recastnavigation_rs::RNError;
 
 mod common;
 use common::*;
 
-#[derive(Debug, Clone, Copy)]
-enum PartitionType {
-    // Watershed,
-    Monotone,
-    Layers,
-}
-
 #[test]
 fn test_tile_mesh_dungeon() {
-    let _ = build_nav_mesh("tile_mesh", "dungeon", 64.0, PartitionType::Monotone);
+    let _ = build_nav_mesh("tile_mesh", "dungeon", 64.0, RcPartitionType::Monotone);
 }
 
 #[test]
 fn test_tile_mesh_nav_test() {
-    let _ = build_nav_mesh("tile_mesh", "nav_test", 64.0, PartitionType::Monotone);
+    let _ = build_nav_mesh("tile_mesh", "nav_test", 64.0, RcPartitionType::Monotone);
 }
 
 #[test]
 fn test_tile_mesh_undulating() {
-    let _ = build_nav_mesh("tile_mesh", "undulating", 96.0, PartitionType::Layers);
+    let _ = build_nav_mesh("tile_mesh", "undulating", 96.0, RcPartitionType::Layers);
+}
+
+#[test]
+fn test_tile_mesh_builder_dungeon() {
+    let nav_mesh = build_with_builder("dungeon", 64, RcPartitionType::Monotone);
+    compare_with_cpp_out(&nav_mesh, "tile_mesh", "dungeon").unwrap();
+}
+
+#[test]
+fn test_tile_mesh_builder_undulating() {
+    let nav_mesh = build_with_builder("undulating", 96, RcPartitionType::Layers);
+    compare_with_cpp_out(&nav_mesh, "tile_mesh", "undulating").unwrap();
+}
+
+// The goldens come from RecastDemo's Sample_TileMesh with the Watershed partition and the same tile sizes,
+// saved as expected/tile_mesh/<name>_watershed_cpp_out.bin. They are not generated yet.
+#[test]
+#[ignore = "needs expected/tile_mesh/*_watershed_cpp_out.bin from RecastDemo"]
+fn test_tile_mesh_builder_watershed_cpp() {
+    for (name, tile_size) in [("dungeon", 64), ("nav_test", 64), ("undulating", 96)] {
+        let nav_mesh = build_with_builder(name, tile_size, RcPartitionType::Watershed);
+        compare_with_cpp_out(&nav_mesh, "tile_mesh", &format!("{}_watershed", name)).unwrap();
+    }
+}
+
+#[test]
+fn test_tile_mesh_builder_watershed() {
+    for (name, tile_size) in [("dungeon", 64), ("nav_test", 64), ("undulating", 96)] {
+        let expected = build_nav_mesh_tiles(name, tile_size as f32, RcPartitionType::Watershed);
+        let nav_mesh = build_with_builder(name, tile_size, RcPartitionType::Watershed);
+        assert_same_tiles(&nav_mesh, &expected);
+    }
+}
+
+// Geometry owned by the caller, e.g. a scene graph.
+struct SceneGeometry {
+    positions: Vec<[f32; 3]>,
+    indices: Vec<[i32; 3]>,
+    areas: Vec<u8>,
+}
+
+impl InputGeometry for SceneGeometry {
+    fn verts(&self) -> &[[f32; 3]] {
+        &self.positions
+    }
+
+    fn tris(&self) -> &[[i32; 3]] {
+        &self.indices
+    }
+
+    fn tri_areas(&self) -> Option<&[u8]> {
+        Some(&self.areas)
+    }
+}
+
+#[test]
+fn test_tile_mesh_builder_input_geometry() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 64;
+
+    // Areas marked by slope on the whole mesh must give the same result as the per-tile marking.
+    let mut ctx = RcContext::new(true);
+    let mut areas = vec![RC_NULL_AREA; mesh_loader.get_tris().len()];
+    rc_mark_walkable_triangles(
+        &mut ctx,
+        cfg.walkable_slope_angle,
+        mesh_loader.get_verts(),
+        mesh_loader.get_tris(),
+        &mut areas,
+    )
+    .unwrap();
+    let geometry = SceneGeometry {
+        positions: mesh_loader.get_verts().to_vec(),
+        indices: mesh_loader.get_tris().to_vec(),
+        areas,
+    };
+
+    let nav_mesh = TileMeshBuilder::new(cfg.clone(), &geometry)
+        .unwrap()
+        .build(&mut ctx)
+        .unwrap();
+    let expected = TileMeshBuilder::new(cfg.clone(), &mesh_loader)
+        .unwrap()
+        .build(&mut ctx)
+        .unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+
+    // Triangles indexing past the vertices are rejected.
+    let mut geometry = geometry;
+    geometry.indices[7][1] = geometry.positions.len() as i32;
+    assert!(TileMeshBuilder::new(cfg.clone(), &geometry).is_err());
+    geometry.indices[7][1] = -1;
+    assert!(TileMeshBuilder::new(cfg, &geometry).is_err());
+}
+
+#[test]
+fn test_tile_mesh_builder_from_settings() {
+    let mesh_loader = load_mesh("dungeon");
+    let mut settings = RcBuildSettings::default();
+    assert!(matches!(
+        TileMeshBuilder::from_settings(&settings, &mesh_loader),
+        Err(RNError::InvalidSettings(_))
+    ));
+    settings.tile_size = 64;
+    let builder = TileMeshBuilder::from_settings(&settings, &mesh_loader).unwrap();
+    assert_eq!(builder.cfg.tile_size, 64);
+    assert_eq!(builder.walkable_radius, settings.agent_radius);
+}
+
+#[test]
+fn test_tile_mesh_builder_convex_volumes() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 32;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    // A water zone over the whole height of the mesh, spanning several tiles.
+    let (bmin, bmax) = builder.bounds();
+    let center = [(bmin[0] + bmax[0]) * 0.5, (bmin[2] + bmax[2]) * 0.5];
+    let mut volumes = ConvexVolumeSet::new();
+    volumes
+        .add(ConvexVolume {
+            verts: vec![
+                [center[0] - 15.0, bmin[1], center[1] - 15.0],
+                [center[0] - 15.0, bmin[1], center[1] + 15.0],
+                [center[0] + 15.0, bmin[1], center[1] + 15.0],
+                [center[0] + 15.0, bmin[1], center[1] - 15.0],
+            ],
+            hmin: bmin[1],
+            hmax: bmax[1],
+            area: SamplePolyAreas::Water as u8,
+        })
+        .unwrap();
+    assert!(volumes.add(ConvexVolume::default()).is_err());
+
+    let count_water_polys = |nav_mesh: &DtNavMesh| {
+        (0..nav_mesh.max_tiles())
+            .filter_map(|idx| nav_mesh.get_tile(idx))
+            .flat_map(|tile| tile.polys())
+            .filter(|poly| poly.area() == SamplePolyAreas::Water as u8)
+            .count()
+    };
+
+    let mut ctx = RcContext::new(true);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+    assert_eq!(count_water_polys(&nav_mesh), 0);
+
+    builder.convex_volumes = Some(&volumes);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+    assert!(count_water_polys(&nav_mesh) > 0);
+}
+
+#[test]
+fn test_tile_mesh_builder_off_mesh_connections() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 32;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    let mut ctx = RcContext::new(true);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+
+    // Connect the centers of the first polys of the first and the last non-empty tiles.
+    let tiles: Vec<_> = (0..nav_mesh.max_tiles())
+        .filter_map(|idx| nav_mesh.get_tile(idx))
+        .filter(|tile| !tile.polys().is_empty())
+        .collect();
+    let poly_center = |tile: &DtMeshTile| {
+        let poly = &tile.polys()[0];
+        let mut center = [0.0; 3];
+        for i in 0..poly.vert_count as usize {
+            let v = tile.verts()[poly.verts[i] as usize];
+            center = [center[0] + v[0], center[1] + v[1], center[2] + v[2]];
+        }
+        let n = poly.vert_count as f32;
+        [center[0] / n, center[1] / n, center[2] / n]
+    };
+    let start_tile = tiles[0].header().unwrap();
+    let (start_x, start_y) = (start_tile.x, start_tile.y);
+    let mut connections = OffMeshConnectionSet::new();
+    connections.add(OffMeshConnection {
+        start: poly_center(tiles[0]),
+        end: poly_center(tiles[tiles.len() - 1]),
+        radius: 0.6,
+        bidirectional: true,
+        area: SamplePolyAreas::Jump as u8,
+        flags: SamplePolyFlags::Jump as u16,
+        user_id: 42,
+    });
+
+    builder.off_mesh_connections = Some(&connections);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+    for idx in 0..nav_mesh.max_tiles() {
+        let tile = nav_mesh.get_tile(idx).unwrap();
+        let Some(header) = tile.header() else {
+            continue;
+        };
+        if (header.x, header.y) == (start_x, start_y) {
+            assert_eq!(tile.off_mesh_cons().len(), 1);
+            assert_eq!(tile.off_mesh_cons()[0].user_id, 42);
+        } else {
+            assert!(tile.off_mesh_cons().is_empty());
+        }
+    }
+
+    // Round trip through rkyv.
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&connections).unwrap();
+    let archived = unsafe { rkyv::access_unchecked::<ArchivedOffMeshConnectionSet>(&bytes) };
+    let loaded: OffMeshConnectionSet = rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+    assert_eq!(loaded, connections);
+}
+
+#[test]
+fn test_tile_mesh_builder_off_mesh_connection_at_bmax() {
+    // A plane covering exactly 2x2 tiles, the far edges of the last tiles are the world bounds.
+    let verts = [[0.0, 0.0, 0.0], [0.0, 0.0, 8.0], [8.0, 0.0, 8.0], [8.0, 0.0, 0.0]];
+    let tris = [[0, 1, 2], [0, 2, 3]];
+    let geometry = RcInputMesh::new(&verts, &tris);
+    let mut cfg = sample_config();
+    cfg.cs = 0.25;
+    cfg.tile_size = 16;
+    let mut builder = TileMeshBuilder::new(cfg, &geometry).unwrap();
+    assert_eq!(builder.tile_count(), (2, 2));
+    assert_eq!(builder.tile_bounds(1, 1).1, builder.bounds().1);
+
+    let mut connections = OffMeshConnectionSet::new();
+    for start in [[8.0, 0.0, 4.0], [4.0, 0.0, 8.0], [8.0, 0.0, 8.0]] {
+        connections.add(OffMeshConnection {
+            start,
+            end: [2.0, 0.0, 2.0],
+            radius: 0.5,
+            ..Default::default()
+        });
+    }
+    builder.off_mesh_connections = Some(&connections);
+    let nav_mesh = builder.build(&mut RcContext::new(true)).unwrap();
+    let mut cons = Vec::new();
+    for idx in 0..nav_mesh.max_tiles() {
+        let tile = nav_mesh.get_tile(idx).unwrap();
+        if let Some(header) = tile.header() {
+            cons.extend(
+                tile.off_mesh_cons()
+                    .iter()
+                    .map(|con| (header.x, header.y, con.pos.a[0], con.pos.a[2])),
+            );
+        }
+    }
+    cons.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(cons, vec![(1, 1, 4.0, 8.0), (1, 1, 8.0, 4.0), (1, 1, 8.0, 8.0)]);
+}
+
+#[test]
+fn test_tile_mesh_builder_rebuild_tile() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 64;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    let mut ctx = RcContext::new(true);
+    let mut nav_mesh = builder.build(&mut ctx).unwrap();
+    let (tw, th) = builder.tile_count();
+    assert_eq!(nav_mesh.params(), &builder.nav_mesh_params());
+
+    for ty in 0..th {
+        for tx in 0..tw {
+            // Links are allocated in a different order after rebuild, so only compare the tile geometry.
+            let dump = |nav_mesh: &DtNavMesh| {
+                nav_mesh
+                    .get_tile_at(tx, ty, 0)
+                    .map(|t| (t.header().cloned(), t.verts().to_vec(), t.detail_verts().to_vec()))
+            };
+            let old_tile = dump(&nav_mesh);
+            let re = builder.rebuild_tile(&mut ctx, &mut nav_mesh, tx, ty).unwrap();
+            let new_tile = dump(&nav_mesh);
+            assert_eq!(re.is_some(), new_tile.is_some());
+            assert_eq!(old_tile, new_tile);
+
+            builder.remove_tile(&mut nav_mesh, tx, ty).unwrap();
+            assert!(nav_mesh.get_tile_at(tx, ty, 0).is_none());
+        }
+    }
+}
+
+#[test]
+fn test_tile_mesh_builder_parallel() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 32;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    let mut ctx = RcContext::new(true);
+    let nav_mesh = builder.build(&mut ctx).unwrap();
+    for threads in [1, 3, 0] {
+        let par_nav_mesh = builder.build_parallel(threads).unwrap();
+        assert_eq!(nav_mesh.max_tiles(), par_nav_mesh.max_tiles());
+        for idx in 0..nav_mesh.max_tiles() {
+            let tile = nav_mesh.get_tile(idx).unwrap();
+            let par_tile = par_nav_mesh.get_tile(idx).unwrap();
+            assert_eq!(tile.data(), par_tile.data());
+        }
+    }
+}
+
+#[test]
+fn test_tile_mesh_builder_timings() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 64;
+    let builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    let (tw, th) = builder.tile_count();
+
+    let mut ctx = RcContext::new(true);
+    let (_, tile_timings) = builder.build_with_timings(&mut ctx).unwrap();
+    let (_, par_tile_timings) = builder.build_parallel_with_timings(2).unwrap();
+    for tile_timings in [tile_timings, par_tile_timings] {
+        assert_eq!(tile_timings.len(), (tw * th) as usize);
+        for (idx, tile) in tile_timings.iter().enumerate() {
+            assert_eq!((tile.tx, tile.ty), (idx as i32 % tw, idx as i32 / tw));
+            assert!(tile.timings.total().is_some());
+        }
+        assert!(tile_timings
+            .iter()
+            .any(|tile| tile.timings.get(RcTimerLabel::RC_TIMER_RASTERIZE_TRIANGLES).is_some()));
+    }
+}
+
+#[test]
+fn test_tile_mesh_builder_cache() {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load("./resource/dungeon.obj");
+
+    let mut cfg = sample_config();
+    cfg.tile_size = 32;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.poly_flags = Some(&sample_poly_flags);
+    let (tiles_x, tiles_y) = builder.tile_count();
+    let tile_count = (tiles_x * tiles_y) as usize;
+
+    let dir = "./output/tile_build_cache";
+    let _ = std::fs::remove_dir_all(dir);
+    let cache = TileBuildCache::open(dir).unwrap();
+
+    let mut ctx = RcContext::new(true);
+    let expected = builder.build(&mut ctx).unwrap();
+
+    // The first build fills the cache, the second one only loads it.
+    let (nav_mesh, stats) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert_eq!((stats.hits, stats.misses, stats.store_failures), (0, tile_count, 0));
+    let (nav_mesh, stats2) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert_eq!((stats2.hits, stats2.misses), (tile_count, 0));
+    assert_eq!(stats.hashes, stats2.hashes);
+    assert_eq!(builder.tile_hash(1, 2), stats.hashes[(2 * tiles_x + 1) as usize]);
+
+    // The salt invalidates every tile, e.g. after changing the poly flags.
+    builder.cache_salt = 1;
+    let (nav_mesh, stats3) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert_eq!((stats3.hits, stats3.misses), (0, tile_count));
+    assert!(stats3.hashes.iter().zip(&stats.hashes).all(|(a, b)| a != b));
+    builder.cache_salt = 0;
+    assert_eq!(cache.prune(&stats.hashes).unwrap(), tile_count);
+
+    // A volume only changes the hashes of the tiles it overlaps.
+    let (bmin, bmax) = builder.tile_bounds(1, 1);
+    let mut volumes = ConvexVolumeSet::new();
+    volumes
+        .add(ConvexVolume {
+            verts: vec![
+                [bmin[0] + 2.0, 0.0, bmin[2] + 2.0],
+                [bmin[0] + 2.0, 0.0, bmin[2] + 4.0],
+                [bmin[0] + 4.0, 0.0, bmin[2] + 4.0],
+            ],
+            hmin: bmin[1],
+            hmax: bmax[1],
+            area: SamplePolyAreas::Water as u8,
+        })
+        .unwrap();
+    builder.convex_volumes = Some(&volumes);
+    let expected = builder.build(&mut ctx).unwrap();
+    let (nav_mesh, stats) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert!(stats.misses > 0 && stats.misses < tile_count / 2);
+    assert_eq!(stats.hits + stats.misses, tile_count);
+    assert_eq!(cache.prune(&stats.hashes).unwrap(), stats.misses);
+
+    // Invalid entries are rebuilt.
+    std::fs::write(cache.path(stats.hashes[0]), b"garbage").unwrap();
+    assert!(cache.load(stats.hashes[0]).unwrap().is_none());
+    let (nav_mesh, stats) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert_eq!((stats.hits, stats.misses), (tile_count - 1, 1));
+
+    // So are entries with a flipped payload byte, e.g. in a poly count.
+    let hash = *stats
+        .hashes
+        .iter()
+        .find(|hash| !cache.load(**hash).unwrap().unwrap().is_empty())
+        .unwrap();
+    let mut bytes = std::fs::read(cache.path(hash)).unwrap();
+    bytes[28 + 24] ^= 0x40;
+    std::fs::write(cache.path(hash), &bytes).unwrap();
+    assert!(cache.load(hash).unwrap().is_none());
+    let (nav_mesh, stats) = builder.build_cached(&mut ctx, &cache).unwrap();
+    assert_same_tiles(&nav_mesh, &expected);
+    assert_eq!((stats.hits, stats.misses), (tile_count - 1, 1));
+    assert!(cache.load(hash).unwrap().is_some());
 }
 
-fn build_nav_mesh(folder: &str, name: &str, tile_size: f32, part: PartitionType) -> DtNavMesh {
+fn build_with_builder(name: &str, tile_size: i32, part: RcPartitionType) -> DtNavMesh {
+    let mut mesh_loader = RcMeshLoaderObj::default();
+    mesh_loader.load(&format!("./resource/{}.obj", name));
+
+    let mut cfg = sample_config();
+    cfg.tile_size = tile_size;
+    let mut builder = TileMeshBuilder::new(cfg, &mesh_loader).unwrap();
+    builder.walkable_height = 2.0;
+    builder.walkable_radius = 0.6;
+    builder.walkable_climb = 0.9;
+    builder.partition = part;
+    builder.poly_flags = Some(&sample_poly_flags);
+
+    let mut ctx = RcContext::new(true);
+    builder.build(&mut ctx).unwrap()
+}
+
+fn assert_same_tiles(nav_mesh: &DtNavMesh, expected: &DtNavMesh) {
+    assert_eq!(nav_mesh.params(), expected.params());
+    assert_eq!(nav_mesh.max_tiles(), expected.max_tiles());
+    for idx in 0..nav_mesh.max_tiles() {
+        let tile = nav_mesh.get_tile(idx).unwrap();
+        let expected_tile = expected.get_tile(idx).unwrap();
+        assert_eq!(tile.data(), expected_tile.data(), "tile[{}]", idx);
+    }
+}
+
+fn build_nav_mesh(folder: &str, name: &str, tile_size: f32, part: RcPartitionType) -> DtNavMesh {
+    let nav_mesh = build_nav_mesh_tiles(name, tile_size, part);
+    compare_with_cpp_out(&nav_mesh, folder, name).unwrap();
+    nav_mesh
+}
+
+fn build_nav_mesh_tiles(name: &str, tile_size: f32, part: RcPartitionType) -> DtNavMesh {
     let cell_size = 0.3;
 
     let mut mesh_loader = RcMeshLoaderObj::default();
@@ -38,7 +490,7 @@ fn build_nav_mesh(folder: &str, name: &str, tile_size: f32, part: PartitionType)
     mesh_loader.get_file_name();
 
     let mut chunky_mesh = RcChunkyTriMesh::new();
-    rc_create_chunky_tri_mesh(&mut chunky_mesh, mesh_loader.get_verts(), mesh_loader.get_tris(), 256).unwrap();
+    rc_create_chunky_tri_mesh(&mut chunky_mesh, &mesh_loader, 256).unwrap();
 
     let (bmin, bmax) = rc_calc_bounds(mesh_loader.get_verts());
     let (grid_width, grid_height) = rc_calc_grid_size(&bmin, &bmax, cell_size);
@@ -78,8 +530,6 @@ fn build_nav_mesh(folder: &str, name: &str, tile_size: f32, part: PartitionType)
         }
     }
 
-    compare_with_cpp_out(&nav_mesh, folder, name).unwrap();
-
     nav_mesh
 }
 
@@ -87,7 +537,7 @@ fn build_nav_mesh_tile(
     mesh_loader: &RcMeshLoaderObj,
     chunky_mesh: &RcChunkyTriMesh,
     tile_size: f32,
-    part: PartitionType,
+    part: RcPartitionType,
     tx: i32,
     ty: i32,
     bmin: &[f32; 3],
@@ -231,14 +681,25 @@ fn build_nav_mesh_tile(
 
     // Partition the heightfield so that we can use simple algorithm later to triangulate the walkable areas.
     match part {
-        PartitionType::Monotone => rc_build_regions_monotone(
+        RcPartitionType::Watershed => {
+            // Compute the distance field for watershed partitioning.
+            rc_build_distance_field(&mut ctx, &mut chf).unwrap();
+            rc_build_regions(
+                &mut ctx,
+                &mut chf,
+                cfg.border_size,
+                cfg.min_region_area,
+                cfg.merge_region_area,
+            )
+        }
+        RcPartitionType::Monotone => rc_build_regions_monotone(
             &mut ctx,
             &mut chf,
             cfg.border_size,
             cfg.min_region_area,
             cfg.merge_region_area,
         ),
-        PartitionType::Layers => rc_build_layer_regions(&mut ctx, &mut chf, cfg.border_size, cfg.min_region_area),
+        RcPartitionType::Layers => rc_build_layer_regions(&mut ctx, &mut chf, cfg.border_size, cfg.min_region_area),
     }
     .unwrap();
 